@@ -0,0 +1,466 @@
+//! Type checking of rule sets, which finds references to names that aren't in scope and
+//! functions called with the wrong types of values without evaluating anything.
+
+use std::fmt;
+
+use serde_json::Value;
+
+use laws_schema::{
+    Endpoint, EndpointRule, EndpointRuleConditionFn, EndpointRuleExpr, EndpointRuleItem,
+    EndpointRuleSet, EndpointRuleSetParamType,
+};
+
+use crate::{template_parts, TemplatePart};
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum TypeError {
+    #[error("{path}: {name} is not defined")]
+    #[diagnostic(
+        code(laws_endpoint_rules::check::undefined),
+        help("references must name a parameter, or a value assigned by an earlier condition")
+    )]
+    Undefined { path: String, name: String },
+    #[error("{path}: {name} is already defined")]
+    #[diagnostic(code(laws_endpoint_rules::check::redefined))]
+    Redefined { path: String, name: String },
+    #[error("{path}: expected {expected}, found {actual}")]
+    #[diagnostic(code(laws_endpoint_rules::check::mismatch))]
+    Mismatch {
+        path: String,
+        expected: Type,
+        actual: Type,
+    },
+    #[error("{path}: {type_} has no attribute {attr:?}")]
+    #[diagnostic(code(laws_endpoint_rules::check::no_attribute))]
+    NoAttribute {
+        path: String,
+        type_: Type,
+        attr: String,
+    },
+    #[error("{path}: {placeholder} should be a string, found {actual}")]
+    #[diagnostic(code(laws_endpoint_rules::check::placeholder))]
+    Placeholder {
+        path: String,
+        placeholder: String,
+        actual: Type,
+    },
+    #[error("{path}: invalid template {template:?}")]
+    #[diagnostic(
+        code(laws_endpoint_rules::check::template),
+        help("braces that aren't part of a `{{name}}` or `{{name#path}}` are escaped by doubling")
+    )]
+    Template { path: String, template: String },
+    #[error("{path}: default {value} is not a {expected}")]
+    #[diagnostic(code(laws_endpoint_rules::check::default))]
+    Default {
+        path: String,
+        expected: Type,
+        value: Value,
+    },
+}
+
+/// The type of a value in a rule set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    String,
+    Boolean,
+    /// An array of strings, such as an ARN's `resourceId`.
+    StringArray,
+    /// The object `parseURL` returns.
+    Url,
+    /// The object `aws.partition` returns.
+    Partition,
+    /// The object `aws.parseArn` returns.
+    Arn,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Type::String => "string",
+            Type::Boolean => "boolean",
+            Type::StringArray => "array<string>",
+            Type::Url => "url",
+            Type::Partition => "partition",
+            Type::Arn => "arn",
+        })
+    }
+}
+
+impl Type {
+    /// The type of an attribute of an object.
+    fn attr(self, name: &str) -> Option<Type> {
+        match (self, name) {
+            (Type::Url, "scheme" | "authority" | "path" | "normalizedPath") => Some(Type::String),
+            (Type::Url, "isIp") => Some(Type::Boolean),
+            (
+                Type::Partition,
+                "name" | "dnsSuffix" | "dualStackDnsSuffix" | "implicitGlobalRegion",
+            ) => Some(Type::String),
+            (Type::Partition, "supportsFIPS" | "supportsDualStack") => Some(Type::Boolean),
+            (Type::Arn, "partition" | "service" | "region" | "accountId") => Some(Type::String),
+            (Type::Arn, "resourceId") => Some(Type::StringArray),
+            _ => None,
+        }
+    }
+
+    /// The type at a `getAttr` path such as `resourceId[0]`, or the part of the path that
+    /// doesn't exist.
+    pub fn path(self, path: &str) -> Result<Type, &str> {
+        let mut current = self;
+        for part in path.split('.') {
+            let (key, index) = match part.strip_suffix(']').and_then(|part| part.split_once('[')) {
+                Some((key, index)) => (key, Some(index)),
+                None => (part, None),
+            };
+            if !key.is_empty() {
+                current = current.attr(key).ok_or(key)?;
+            }
+            if let Some(index) = index {
+                if current != Type::StringArray || index.parse::<usize>().is_err() {
+                    return Err(part);
+                }
+                current = Type::String;
+            }
+        }
+        Ok(current)
+    }
+}
+
+/// Checks that every reference in the rule set is to a parameter or an assignment in scope,
+/// and that every value has the type it's used as, returning all the problems found.
+pub fn check(rule_set: &EndpointRuleSet) -> Vec<TypeError> {
+    let mut checker = Checker { errors: vec![] };
+    let mut scope = Types::new();
+    for (name, param) in &rule_set.parameters {
+        let type_ = match param.type_ {
+            EndpointRuleSetParamType::String => Type::String,
+            EndpointRuleSetParamType::Boolean => Type::Boolean,
+        };
+        match (type_, &param.default) {
+            (_, None) | (Type::String, Some(Value::String(_))) => {}
+            (Type::Boolean, Some(Value::Bool(_))) => {}
+            (expected, Some(value)) => checker.errors.push(TypeError::Default {
+                path: format!("parameters.{name}.default"),
+                expected,
+                value: value.clone(),
+            }),
+        }
+        scope.push((name, Some(type_)));
+    }
+    checker.rules("rules", &rule_set.rules, &mut scope);
+    checker.errors
+}
+
+/// The names in scope and their types, innermost last, where the type of a value that failed
+/// to check is unknown.
+type Types<'r> = Vec<(&'r str, Option<Type>)>;
+
+struct Checker {
+    errors: Vec<TypeError>,
+}
+
+impl Checker {
+    fn rules<'r>(&mut self, path: &str, rules: &'r [EndpointRuleItem], scope: &mut Types<'r>) {
+        for (index, rule) in rules.iter().enumerate() {
+            let path = format!("{path}[{index}]");
+            let depth = scope.len();
+            for (index, condition) in rule.conditions.iter().enumerate() {
+                let path = format!("{path}.conditions[{index}]");
+                // Any value can be a condition: it matches unless it's `false` or unset.
+                let type_ = self.call(&path, &condition.function, scope);
+                if let Some(name) = &condition.assign {
+                    if scope.iter().any(|(scope_name, _)| scope_name == name) {
+                        self.errors.push(TypeError::Redefined {
+                            path: path.clone(),
+                            name: name.clone(),
+                        });
+                    }
+                    scope.push((name, type_));
+                }
+            }
+            match &rule.rule {
+                EndpointRule::Tree { rules } => self.rules(&format!("{path}.rules"), rules, scope),
+                EndpointRule::Error { error } => {
+                    self.template(&format!("{path}.error"), error, scope);
+                }
+                EndpointRule::Endpoint { endpoint } => {
+                    self.endpoint(&format!("{path}.endpoint"), endpoint, scope);
+                }
+            }
+            scope.truncate(depth);
+        }
+    }
+
+    fn endpoint(&mut self, path: &str, endpoint: &Endpoint, scope: &Types) {
+        let url_path = format!("{path}.url");
+        match self.expr(&url_path, &endpoint.url, scope) {
+            None | Some(Type::String) => {}
+            Some(actual) => self.errors.push(TypeError::Mismatch {
+                path: url_path,
+                expected: Type::String,
+                actual,
+            }),
+        }
+        let properties =
+            serde_json::to_value(&endpoint.properties).expect("properties should serialize");
+        self.templates(&format!("{path}.properties"), &properties, scope);
+        for (name, values) in &endpoint.headers {
+            for (index, value) in values.iter().enumerate() {
+                self.template(&format!("{path}.headers.{name}[{index}]"), value, scope);
+            }
+        }
+    }
+
+    /// Checks the strings in a property value, which are templates.
+    fn templates(&mut self, path: &str, value: &Value, scope: &Types) {
+        match value {
+            Value::String(template) => self.template(path, template, scope),
+            Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.templates(&format!("{path}[{index}]"), item, scope);
+                }
+            }
+            Value::Object(object) => {
+                for (key, value) in object {
+                    self.templates(&format!("{path}.{key}"), value, scope);
+                }
+            }
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+    }
+
+    /// The type a function returns, if its arguments check.
+    fn call(
+        &mut self,
+        path: &str,
+        function: &EndpointRuleConditionFn,
+        scope: &Types,
+    ) -> Option<Type> {
+        use EndpointRuleConditionFn as Fn;
+        let args = function
+            .args()
+            .into_iter()
+            .enumerate()
+            .map(|(index, arg)| self.expr(&format!("{path}.argv[{index}]"), arg, scope))
+            .collect::<Vec<_>>();
+        let (params, returns): (&[Type], _) = match function {
+            Fn::Not(_) => (&[Type::Boolean], Type::Boolean),
+            Fn::BooleanEquals(..) => (&[Type::Boolean, Type::Boolean], Type::Boolean),
+            Fn::StringEquals(..) => (&[Type::String, Type::String], Type::Boolean),
+            // Any value can be set or not.
+            Fn::IsSet(_) => (&[], Type::Boolean),
+            Fn::GetAttr(_, attr) => {
+                return match args[0]?.path(attr) {
+                    Ok(type_) => Some(type_),
+                    Err(part) => {
+                        self.errors.push(TypeError::NoAttribute {
+                            path: format!("{path}.argv[1]"),
+                            type_: args[0]?,
+                            attr: part.to_string(),
+                        });
+                        None
+                    }
+                };
+            }
+            Fn::ParseURL(_) => (&[Type::String], Type::Url),
+            Fn::IsValidHostLabel(..) | Fn::AwsIsVirtualHostableS3Bucket(..) => {
+                (&[Type::String], Type::Boolean)
+            }
+            Fn::Substring(..) | Fn::UriEncode(_) => (&[Type::String], Type::String),
+            Fn::AwsPartition(_) => (&[Type::String], Type::Partition),
+            Fn::AwsParseArn(_) => (&[Type::String], Type::Arn),
+        };
+        let mut valid = true;
+        for (index, (&expected, actual)) in params.iter().zip(args).enumerate() {
+            match actual {
+                Some(actual) if actual == expected => {}
+                Some(actual) => {
+                    self.errors.push(TypeError::Mismatch {
+                        path: format!("{path}.argv[{index}]"),
+                        expected,
+                        actual,
+                    });
+                    valid = false;
+                }
+                None => valid = false,
+            }
+        }
+        valid.then_some(returns)
+    }
+
+    fn expr(&mut self, path: &str, expr: &EndpointRuleExpr, scope: &Types) -> Option<Type> {
+        match expr {
+            EndpointRuleExpr::Condition(condition) => self.call(path, &condition.function, scope),
+            EndpointRuleExpr::Reference { name } => self.lookup(path, name, scope),
+            EndpointRuleExpr::String(template) => {
+                self.template(path, template, scope);
+                Some(Type::String)
+            }
+            EndpointRuleExpr::Boolean(_) => Some(Type::Boolean),
+        }
+    }
+
+    /// Checks that a template's placeholders are strings in scope.
+    fn template(&mut self, path: &str, template: &str, scope: &Types) {
+        let Ok(parts) = template_parts(template) else {
+            self.errors.push(TypeError::Template {
+                path: path.to_string(),
+                template: template.to_string(),
+            });
+            return;
+        };
+        for part in parts {
+            let TemplatePart::Value {
+                name,
+                path: attr,
+                source,
+            } = part
+            else {
+                continue;
+            };
+            let Some(type_) = self.lookup(path, name, scope) else {
+                continue;
+            };
+            let actual = match attr.map(|attr| type_.path(attr)) {
+                None => type_,
+                Some(Ok(actual)) => actual,
+                Some(Err(part)) => {
+                    self.errors.push(TypeError::NoAttribute {
+                        path: path.to_string(),
+                        type_,
+                        attr: part.to_string(),
+                    });
+                    continue;
+                }
+            };
+            if actual != Type::String {
+                self.errors.push(TypeError::Placeholder {
+                    path: path.to_string(),
+                    placeholder: source.to_string(),
+                    actual,
+                });
+            }
+        }
+    }
+
+    fn lookup(&mut self, path: &str, name: &str, scope: &Types) -> Option<Type> {
+        match scope
+            .iter()
+            .rev()
+            .find(|(scope_name, _)| *scope_name == name)
+        {
+            Some((_, type_)) => *type_,
+            None => {
+                self.errors.push(TypeError::Undefined {
+                    path: path.to_string(),
+                    name: name.to_string(),
+                });
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn checks() {
+        let rule_set = serde_json::from_value::<EndpointRuleSet>(json!({
+            "parameters": {
+                "Region": { "required": false, "documentation": "", "type": "String" },
+                "UseFIPS": {
+                    "required": true,
+                    "default": "false",
+                    "documentation": "",
+                    "type": "Boolean",
+                },
+                "Endpoint": { "required": false, "documentation": "", "type": "String" },
+            },
+            "rules": [
+                {
+                    "conditions": [
+                        { "fn": "parseURL", "argv": [{ "ref": "Endpoint" }], "assign": "url" },
+                        { "fn": "booleanEquals", "argv": [{ "ref": "url" }, true] },
+                    ],
+                    "endpoint": { "url": { "ref": "url" }, "properties": {}, "headers": {} },
+                    "type": "endpoint",
+                },
+                {
+                    "conditions": [
+                        {
+                            "fn": "aws.partition",
+                            "argv": [{ "ref": "Region" }],
+                            "assign": "partition",
+                        },
+                    ],
+                    "rules": [
+                        {
+                            "conditions": [
+                                {
+                                    "fn": "getAttr",
+                                    "argv": [{ "ref": "partition" }, "supportsFips"],
+                                },
+                                { "fn": "isSet", "argv": [{ "ref": "url" }] },
+                                {
+                                    "fn": "aws.parseArn",
+                                    "argv": [{ "ref": "Region" }],
+                                    "assign": "partition",
+                                },
+                            ],
+                            "error": "FIPS is not supported in {partition#name}",
+                            "type": "error",
+                        },
+                        {
+                            "conditions": [],
+                            "endpoint": {
+                                "url": "https://example.{Region}.{partition#dnsSuffix}",
+                                "properties": {
+                                    "authSchemes": [{
+                                        "name": "sigv4",
+                                        "signingName": "example",
+                                        "signingRegion": "{partition}",
+                                    }],
+                                },
+                                "headers": { "x-fips": ["{UseFIPS}", "{Region"] },
+                            },
+                            "type": "endpoint",
+                        },
+                    ],
+                    "type": "tree",
+                },
+            ],
+        }))
+        .unwrap();
+
+        let errors = check(&rule_set)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "parameters.UseFIPS.default: default \"false\" is not a boolean",
+                "rules[0].conditions[1].argv[0]: expected boolean, found url",
+                "rules[0].endpoint.url: expected string, found url",
+                "rules[1].rules[0].conditions[0].argv[1]: partition has no attribute \
+                 \"supportsFips\"",
+                "rules[1].rules[0].conditions[1].argv[0]: url is not defined",
+                "rules[1].rules[0].conditions[2]: partition is already defined",
+                "rules[1].rules[0].error: arn has no attribute \"name\"",
+                "rules[1].rules[1].endpoint.properties.authSchemes[0].signingRegion: \
+                 {partition} should be a string, found partition",
+                "rules[1].rules[1].endpoint.headers.x-fips[0]: {UseFIPS} should be a string, \
+                 found boolean",
+                "rules[1].rules[1].endpoint.headers.x-fips[1]: invalid template \"{Region\"",
+            ]
+        );
+
+        assert_eq!(Type::Arn.path("resourceId[2]"), Ok(Type::String));
+        assert_eq!(Type::Arn.path("region[0]"), Err("region[0]"));
+    }
+}
@@ -0,0 +1,229 @@
+//! The functions endpoint rule conditions can call, from the Smithy rules engine standard
+//! library and the AWS extensions to it.
+
+use serde_json::{json, Value};
+
+use laws_schema::arn::Arn;
+
+/// `getAttr`: looks up a path such as `resourceId[0]` or `a.b` in an object, or returns null.
+pub fn get_attr(value: &Value, path: &str) -> Value {
+    let mut current = value;
+    for part in path.split('.') {
+        let (key, index) = match part.strip_suffix(']').and_then(|part| part.split_once('[')) {
+            Some((key, index)) => (key, Some(index)),
+            None => (part, None),
+        };
+        if !key.is_empty() {
+            let Some(value) = current.as_object().and_then(|object| object.get(key)) else {
+                return Value::Null;
+            };
+            current = value;
+        }
+        if let Some(index) = index {
+            let value = index
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| current.as_array()?.get(index));
+            let Some(value) = value else {
+                return Value::Null;
+            };
+            current = value;
+        }
+    }
+    current.clone()
+}
+
+/// `parseURL`: splits a URL without a query string into its parts, or returns null.
+pub fn parse_url(url: &str) -> Value {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return Value::Null;
+    };
+    let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+    if !valid_scheme || rest.contains(['?', '#']) || rest.contains(char::is_whitespace) {
+        return Value::Null;
+    }
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = if host.starts_with('[') {
+        let Some(end) = host.find(']') else {
+            return Value::Null;
+        };
+        &host[..=end]
+    } else {
+        host.split_once(':').map_or(host, |(host, _)| host)
+    };
+    if host.is_empty() {
+        return Value::Null;
+    }
+    let is_ipv4 = {
+        let parts = host.split('.').collect::<Vec<_>>();
+        parts.len() == 4 && parts.iter().all(|part| part.parse::<u8>().is_ok())
+    };
+    let mut normalized_path = path.to_string();
+    if !normalized_path.starts_with('/') {
+        normalized_path.insert(0, '/');
+    }
+    if !normalized_path.ends_with('/') {
+        normalized_path.push('/');
+    }
+    json!({
+        "scheme": scheme,
+        "authority": authority,
+        "path": path,
+        "normalizedPath": normalized_path,
+        "isIp": is_ipv4 || host.starts_with('['),
+    })
+}
+
+/// `isValidHostLabel`: whether the value is a DNS host label, or dot separated labels if
+/// `allow_sub_domains` is set.
+pub fn is_valid_host_label(value: &str, allow_sub_domains: bool) -> bool {
+    if allow_sub_domains {
+        return value
+            .split('.')
+            .all(|label| is_valid_host_label(label, false));
+    }
+    value.len() <= 63
+        && value.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// `substring`: the ASCII characters from `start` up to `stop`, counted from the end if
+/// `reverse` is set, or null if out of range.
+pub fn substring(value: &str, start: usize, stop: usize, reverse: bool) -> Value {
+    if !value.is_ascii() || start >= stop || stop > value.len() {
+        return Value::Null;
+    }
+    let (start, stop) = if reverse {
+        (value.len() - stop, value.len() - start)
+    } else {
+        (start, stop)
+    };
+    Value::String(value[start..stop].to_string())
+}
+
+/// `uriEncode`: percent-encodes everything but the RFC 3986 unreserved characters.
+pub fn uri_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// `aws.parseArn`: splits an ARN into its parts, with the resource split on `:` and `/`, or
+/// returns null.
+pub fn parse_arn(value: &str) -> Value {
+    let Ok(arn) = value.parse::<Arn>() else {
+        return Value::Null;
+    };
+    json!({
+        "partition": arn.partition,
+        "service": arn.service,
+        "region": arn.region,
+        "accountId": arn.account_id,
+        "resourceId": arn.resource_id(),
+    })
+}
+
+/// `aws.isVirtualHostableS3Bucket`: whether the bucket name can be used as a host label, or as
+/// dot separated labels if `allow_sub_domains` is set.
+pub fn is_virtual_hostable_s3_bucket(value: &str, allow_sub_domains: bool) -> bool {
+    let is_edge_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+    let is_label = |label: &str| {
+        label.starts_with(is_edge_char)
+            && label.ends_with(is_edge_char)
+            && label.chars().all(|c| is_edge_char(c) || c == '-')
+    };
+    // As in Smithy's reference implementation, no two dots or dashes may be adjacent.
+    let has_adjacent_separators = value
+        .as_bytes()
+        .windows(2)
+        .any(|pair| pair.iter().all(|&b| b == b'.' || b == b'-'));
+    if !(3..=63).contains(&value.len()) || has_adjacent_separators {
+        return false;
+    }
+    if !allow_sub_domains {
+        return is_label(value);
+    }
+    let looks_like_ip = value.split('.').count() == 4
+        && value
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+    value.split('.').all(is_label) && !looks_like_ip
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn functions() {
+        let arn = parse_arn("arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap:object");
+        assert_eq!(
+            arn,
+            json!({
+                "partition": "aws",
+                "service": "s3",
+                "region": "us-west-2",
+                "accountId": "123456789012",
+                "resourceId": ["accesspoint", "my-ap", "object"],
+            })
+        );
+        assert_eq!(get_attr(&arn, "resourceId[1]"), json!("my-ap"));
+        assert_eq!(get_attr(&arn, "resourceId[3]"), Value::Null);
+        assert_eq!(get_attr(&arn, "partition.name"), Value::Null);
+        assert_eq!(parse_arn("arn:aws:s3:::"), Value::Null);
+        assert_eq!(parse_arn("not:an:arn:at:all:x"), Value::Null);
+
+        assert_eq!(
+            parse_url("https://example.com:8443/a/b"),
+            json!({
+                "scheme": "https",
+                "authority": "example.com:8443",
+                "path": "/a/b",
+                "normalizedPath": "/a/b/",
+                "isIp": false,
+            })
+        );
+        assert_eq!(parse_url("http://127.0.0.1")["isIp"], json!(true));
+        assert_eq!(parse_url("http://127.0.0.1")["normalizedPath"], json!("/"));
+        assert_eq!(parse_url("https://[::1]:80/")["isIp"], json!(true));
+        assert_eq!(parse_url("https://example.com/?query"), Value::Null);
+        assert_eq!(parse_url("example.com"), Value::Null);
+
+        assert!(is_valid_host_label("us-east-1", false));
+        assert!(!is_valid_host_label("-leading", false));
+        assert!(!is_valid_host_label("a.b", false));
+        assert!(is_valid_host_label("a.b", true));
+        assert!(!is_valid_host_label("a..b", true));
+
+        assert_eq!(substring("abcdef", 0, 4, false), json!("abcd"));
+        assert_eq!(substring("abcdef", 0, 4, true), json!("cdef"));
+        assert_eq!(substring("abc", 1, 4, false), Value::Null);
+        assert_eq!(substring("ab\u{e9}", 0, 1, false), Value::Null);
+
+        assert_eq!(uri_encode("a b/c~d\u{e9}"), "a%20b%2Fc~d%C3%A9");
+
+        assert!(is_virtual_hostable_s3_bucket("my-bucket", false));
+        assert!(!is_virtual_hostable_s3_bucket("My-Bucket", false));
+        assert!(!is_virtual_hostable_s3_bucket("my.bucket", false));
+        assert!(is_virtual_hostable_s3_bucket("my.bucket", true));
+        assert!(!is_virtual_hostable_s3_bucket("192.168.0.1", true));
+        assert!(!is_virtual_hostable_s3_bucket("abc.-de", true));
+        assert!(!is_virtual_hostable_s3_bucket("ab-.cd", true));
+        assert!(!is_virtual_hostable_s3_bucket("ab..cd", true));
+        assert!(!is_virtual_hostable_s3_bucket("ab--cd.ef", true));
+        assert!(!is_virtual_hostable_s3_bucket("ab--cd", false));
+        assert!(!is_virtual_hostable_s3_bucket("ab", false));
+    }
+}
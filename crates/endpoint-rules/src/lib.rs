@@ -0,0 +1,533 @@
+//! Evaluation of Smithy endpoint rule sets, which resolve the endpoint to call from the client's
+//! configuration and the operation's input.
+
+use indexmap::IndexMap;
+use serde_json::{Map, Value};
+
+use laws_schema::{
+    Endpoint, EndpointRule, EndpointRuleCondition, EndpointRuleConditionFn, EndpointRuleExpr,
+    EndpointRuleItem, EndpointRuleSet, EndpointRuleSetParam, EndpointRuleSetParamType,
+};
+
+mod check;
+pub mod functions;
+mod optimize;
+mod partial;
+mod partitions;
+
+pub use check::*;
+pub use partitions::*;
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum ResolveError {
+    /// An error rule matched, with this message.
+    #[error("{0}")]
+    #[diagnostic(code(laws_endpoint_rules::rule))]
+    Rule(String),
+    #[error("no rule matched")]
+    #[diagnostic(code(laws_endpoint_rules::no_match))]
+    NoMatch,
+    #[error("missing required parameter {0}")]
+    #[diagnostic(code(laws_endpoint_rules::missing_parameter))]
+    MissingParameter(String),
+    #[error("no partitions to evaluate aws.partition with")]
+    #[diagnostic(code(laws_endpoint_rules::missing_partitions))]
+    MissingPartitions,
+    #[error("invalid rule set: {0}")]
+    #[diagnostic(code(laws_endpoint_rules::invalid))]
+    Invalid(String),
+}
+
+/// Looks up the partition a region is in, for `aws.partition`.
+pub trait PartitionResolver {
+    /// The partition's `name`, `dnsSuffix`, `dualStackDnsSuffix`, `supportsFIPS`,
+    /// `supportsDualStack` and `implicitGlobalRegion`.
+    fn partition(&self, region: &str) -> Option<Value>;
+}
+
+/// An endpoint resolved by a rule set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedEndpoint {
+    pub url: String,
+    /// Properties such as `authSchemes`, with any templates expanded.
+    pub properties: Map<String, Value>,
+    pub headers: IndexMap<String, Vec<String>>,
+}
+
+/// Evaluates rule sets with concrete parameter values.
+#[derive(Default)]
+pub struct Evaluator<'a> {
+    partitions: Option<&'a dyn PartitionResolver>,
+}
+
+/// The parameters and assigned values in scope, innermost last.
+type Scope<'r> = Vec<(&'r str, Value)>;
+
+impl<'a> Evaluator<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets where `aws.partition` looks up regions. Without partitions, rule sets that call it
+    /// fail with [`ResolveError::MissingPartitions`].
+    pub fn partitions(&mut self, partitions: &'a dyn PartitionResolver) -> &mut Self {
+        self.partitions = Some(partitions);
+        self
+    }
+
+    /// Resolves the endpoint for the parameter values, by name. Parameters that aren't given
+    /// take their default, if any.
+    pub fn resolve(
+        &self,
+        rule_set: &EndpointRuleSet,
+        params: &IndexMap<String, Value>,
+    ) -> Result<ResolvedEndpoint, ResolveError> {
+        let mut scope = Scope::new();
+        for (name, param) in &rule_set.parameters {
+            scope.push((name, param_value(name, param, params.get(name))?));
+        }
+        self.rules(&rule_set.rules, &mut scope)?
+            .ok_or(ResolveError::NoMatch)
+    }
+
+    /// Evaluates the first rule whose conditions match, if any.
+    fn rules<'r>(
+        &self,
+        rules: &'r [EndpointRuleItem],
+        scope: &mut Scope<'r>,
+    ) -> Result<Option<ResolvedEndpoint>, ResolveError> {
+        for rule in rules {
+            let depth = scope.len();
+            if !self.conditions(&rule.conditions, scope)? {
+                scope.truncate(depth);
+                continue;
+            }
+            let endpoint = match &rule.rule {
+                // A tree whose conditions matched must resolve to something.
+                EndpointRule::Tree { rules } => {
+                    self.rules(rules, scope)?.ok_or(ResolveError::NoMatch)?
+                }
+                EndpointRule::Error { error } => {
+                    return Err(ResolveError::Rule(self.template(error, scope)?));
+                }
+                EndpointRule::Endpoint { endpoint } => self.endpoint(endpoint, scope)?,
+            };
+            return Ok(Some(endpoint));
+        }
+        Ok(None)
+    }
+
+    /// Whether all the conditions match, adding their assignments to the scope. A condition
+    /// matches unless it gives `false` or an unset value.
+    fn conditions<'r>(
+        &self,
+        conditions: &'r [EndpointRuleCondition],
+        scope: &mut Scope<'r>,
+    ) -> Result<bool, ResolveError> {
+        for condition in conditions {
+            let value = self.call(&condition.function, scope)?;
+            if matches!(value, Value::Null | Value::Bool(false)) {
+                return Ok(false);
+            }
+            if let Some(name) = &condition.assign {
+                scope.push((name, value));
+            }
+        }
+        Ok(true)
+    }
+
+    fn endpoint(
+        &self,
+        endpoint: &Endpoint,
+        scope: &Scope,
+    ) -> Result<ResolvedEndpoint, ResolveError> {
+        let url = self.string(&endpoint.url, scope)?;
+        let Value::Object(mut properties) =
+            serde_json::to_value(&endpoint.properties).expect("properties should serialize")
+        else {
+            unreachable!("properties serialize to an object");
+        };
+        for value in properties.values_mut() {
+            self.expand_templates(value, scope)?;
+        }
+        let headers = endpoint
+            .headers
+            .iter()
+            .map(|(name, values)| {
+                let values = values
+                    .iter()
+                    .map(|value| self.template(value, scope))
+                    .collect::<Result<_, _>>()?;
+                Ok((name.clone(), values))
+            })
+            .collect::<Result<_, ResolveError>>()?;
+        Ok(ResolvedEndpoint {
+            url,
+            properties,
+            headers,
+        })
+    }
+
+    fn expand_templates(&self, value: &mut Value, scope: &Scope) -> Result<(), ResolveError> {
+        match value {
+            Value::String(template) => *template = self.template(template, scope)?,
+            Value::Array(items) => {
+                for item in items {
+                    self.expand_templates(item, scope)?;
+                }
+            }
+            Value::Object(object) => {
+                for value in object.values_mut() {
+                    self.expand_templates(value, scope)?;
+                }
+            }
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+        Ok(())
+    }
+
+    fn call(
+        &self,
+        function: &EndpointRuleConditionFn,
+        scope: &Scope,
+    ) -> Result<Value, ResolveError> {
+        let args = function
+            .args()
+            .into_iter()
+            .map(|arg| self.expr(arg, scope))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.apply(function, &args)
+    }
+
+    /// Calls a function with the values of its [`EndpointRuleConditionFn::args`].
+    fn apply(
+        &self,
+        function: &EndpointRuleConditionFn,
+        args: &[Value],
+    ) -> Result<Value, ResolveError> {
+        use EndpointRuleConditionFn as Fn;
+        let boolean = |index: usize| match &args[index] {
+            Value::Bool(value) => Ok(*value),
+            value => Err(ResolveError::Invalid(format!(
+                "expected a boolean, got {value}"
+            ))),
+        };
+        let string = |index: usize| match &args[index] {
+            Value::String(value) => Ok(value.as_str()),
+            value => Err(ResolveError::Invalid(format!(
+                "expected a string, got {value}"
+            ))),
+        };
+        Ok(match function {
+            Fn::Not(_) => Value::Bool(!boolean(0)?),
+            Fn::BooleanEquals(..) => Value::Bool(boolean(0)? == boolean(1)?),
+            Fn::StringEquals(..) => Value::Bool(string(0)? == string(1)?),
+            Fn::GetAttr(_, path) => functions::get_attr(&args[0], path),
+            Fn::IsSet(_) => Value::Bool(!args[0].is_null()),
+            Fn::ParseURL(_) => functions::parse_url(string(0)?),
+            Fn::IsValidHostLabel(_, allow_sub_domains) => Value::Bool(
+                functions::is_valid_host_label(string(0)?, *allow_sub_domains),
+            ),
+            Fn::Substring(_, start, stop, reverse) => {
+                functions::substring(string(0)?, *start, *stop, *reverse)
+            }
+            Fn::UriEncode(_) => Value::String(functions::uri_encode(string(0)?)),
+            Fn::AwsPartition(_) => {
+                let partitions = self.partitions.ok_or(ResolveError::MissingPartitions)?;
+                partitions.partition(string(0)?).unwrap_or(Value::Null)
+            }
+            Fn::AwsParseArn(_) => functions::parse_arn(string(0)?),
+            Fn::AwsIsVirtualHostableS3Bucket(_, allow_sub_domains) => Value::Bool(
+                functions::is_virtual_hostable_s3_bucket(string(0)?, *allow_sub_domains),
+            ),
+        })
+    }
+
+    fn expr(&self, expr: &EndpointRuleExpr, scope: &Scope) -> Result<Value, ResolveError> {
+        Ok(match expr {
+            EndpointRuleExpr::Condition(condition) => self.call(&condition.function, scope)?,
+            EndpointRuleExpr::Reference { name } => lookup(scope, name)?.clone(),
+            EndpointRuleExpr::String(template) => Value::String(self.template(template, scope)?),
+            EndpointRuleExpr::Boolean(value) => Value::Bool(*value),
+        })
+    }
+
+    fn string(&self, expr: &EndpointRuleExpr, scope: &Scope) -> Result<String, ResolveError> {
+        match self.expr(expr, scope)? {
+            Value::String(value) => Ok(value),
+            value => Err(ResolveError::Invalid(format!(
+                "expected a string, got {value}"
+            ))),
+        }
+    }
+
+    /// Expands `{name}` and `{name#path}` in a string.
+    fn template(&self, template: &str, scope: &Scope) -> Result<String, ResolveError> {
+        let mut expanded = String::new();
+        for part in template_parts(template)? {
+            match part {
+                TemplatePart::Text(text) => expanded.push_str(&text),
+                TemplatePart::Value { name, path, source } => {
+                    let value = lookup(scope, name)?;
+                    let value = match path {
+                        Some(path) => functions::get_attr(value, path),
+                        None => value.clone(),
+                    };
+                    let Value::String(value) = value else {
+                        return Err(template_type_error(template, source, &value));
+                    };
+                    expanded.push_str(&value);
+                }
+            }
+        }
+        Ok(expanded)
+    }
+}
+
+/// A parameter's value given its value from the caller, if any.
+fn param_value(
+    name: &str,
+    param: &EndpointRuleSetParam,
+    given: Option<&Value>,
+) -> Result<Value, ResolveError> {
+    let value = given
+        .or(param.default.as_ref())
+        .cloned()
+        .unwrap_or(Value::Null);
+    match (&param.type_, &value) {
+        (_, Value::Null) if param.required => Err(ResolveError::MissingParameter(name.to_string())),
+        (_, Value::Null)
+        | (EndpointRuleSetParamType::Boolean, Value::Bool(_))
+        | (EndpointRuleSetParamType::String, Value::String(_)) => Ok(value),
+        (type_, value) => Err(ResolveError::Invalid(format!(
+            "parameter {name} should be a {type_:?}, got {value}"
+        ))),
+    }
+}
+
+pub enum TemplatePart<'t> {
+    /// Text, with escaped braces unescaped.
+    Text(String),
+    /// `{name}` or `{name#path}`, where `source` is the whole placeholder.
+    Value {
+        name: &'t str,
+        path: Option<&'t str>,
+        source: &'t str,
+    },
+}
+
+/// Splits a template string into text and placeholders, where `{{` and `}}` are escaped braces.
+pub fn template_parts(template: &str) -> Result<Vec<TemplatePart<'_>>, ResolveError> {
+    let invalid = || ResolveError::Invalid(format!("invalid template {template:?}"));
+    let mut parts = vec![];
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(index) = rest.find(['{', '}']) {
+        text.push_str(&rest[..index]);
+        let brace = &rest[index..=index];
+        if let Some(after) = rest[index + 1..].strip_prefix(brace) {
+            text.push_str(brace);
+            rest = after;
+            continue;
+        }
+        let end = rest[index..]
+            .find('}')
+            .filter(|_| brace == "{")
+            .ok_or_else(invalid)?;
+        let source = &rest[index..index + end + 1];
+        let placeholder = &source[1..source.len() - 1];
+        let (name, path) = match placeholder.split_once('#') {
+            Some((name, path)) => (name, Some(path)),
+            None => (placeholder, None),
+        };
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+        }
+        parts.push(TemplatePart::Value { name, path, source });
+        rest = &rest[index + end + 1..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        parts.push(TemplatePart::Text(text));
+    }
+    Ok(parts)
+}
+
+/// Escapes the braces in text to put in a template.
+fn escape_template(text: &str) -> String {
+    text.replace('{', "{{").replace('}', "}}")
+}
+
+fn template_type_error(template: &str, source: &str, value: &Value) -> ResolveError {
+    ResolveError::Invalid(format!(
+        "{source} in {template:?} should be a string, got {value}"
+    ))
+}
+
+fn lookup<'s, T>(scope: &'s [(&str, T)], name: &str) -> Result<&'s T, ResolveError> {
+    scope
+        .iter()
+        .rev()
+        .find(|(scope_name, _)| *scope_name == name)
+        .map(|(_, value)| value)
+        .ok_or_else(|| ResolveError::Invalid(format!("{name} is not defined")))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    struct TestPartitions;
+
+    impl PartitionResolver for TestPartitions {
+        fn partition(&self, region: &str) -> Option<Value> {
+            let dns_suffix = if region.starts_with("cn-") {
+                "amazonaws.com.cn"
+            } else {
+                "amazonaws.com"
+            };
+            Some(json!({ "name": "aws", "dnsSuffix": dns_suffix, "supportsFIPS": true }))
+        }
+    }
+
+    #[test]
+    fn resolves() {
+        let rule_set = serde_json::from_value::<EndpointRuleSet>(json!({
+            "parameters": {
+                "Region": { "required": false, "documentation": "", "type": "String" },
+                "UseFIPS": {
+                    "required": true,
+                    "default": false,
+                    "documentation": "",
+                    "type": "Boolean",
+                },
+                "Endpoint": { "required": false, "documentation": "", "type": "String" },
+            },
+            "rules": [
+                {
+                    "conditions": [
+                        { "fn": "isSet", "argv": [{ "ref": "Endpoint" }] },
+                        { "fn": "parseURL", "argv": [{ "ref": "Endpoint" }], "assign": "url" },
+                    ],
+                    "endpoint": {
+                        "url": "{url#scheme}://{url#authority}{url#normalizedPath}v1",
+                        "properties": {},
+                        "headers": {},
+                    },
+                    "type": "endpoint",
+                },
+                {
+                    "conditions": [
+                        { "fn": "isSet", "argv": [{ "ref": "Region" }] },
+                        {
+                            "fn": "aws.partition",
+                            "argv": [{ "ref": "Region" }],
+                            "assign": "partition",
+                        },
+                    ],
+                    "rules": [
+                        {
+                            "conditions": [
+                                { "fn": "booleanEquals", "argv": [{ "ref": "UseFIPS" }, true] },
+                                {
+                                    "fn": "booleanEquals",
+                                    "argv": [
+                                        {
+                                            "fn": "getAttr",
+                                            "argv": [{ "ref": "partition" }, "supportsFIPS"],
+                                        },
+                                        false,
+                                    ],
+                                },
+                            ],
+                            "error": "FIPS is not supported in {partition#name}",
+                            "type": "error",
+                        },
+                        {
+                            "conditions": [
+                                { "fn": "booleanEquals", "argv": [{ "ref": "UseFIPS" }, true] },
+                            ],
+                            "endpoint": {
+                                "url": "https://example-fips.{Region}.{partition#dnsSuffix}",
+                                "properties": {},
+                                "headers": {},
+                            },
+                            "type": "endpoint",
+                        },
+                        {
+                            "conditions": [],
+                            "endpoint": {
+                                "url": "https://example.{Region}.{partition#dnsSuffix}",
+                                "properties": {
+                                    "authSchemes": [{
+                                        "name": "sigv4",
+                                        "signingName": "example",
+                                        "signingRegion": "{Region}",
+                                    }],
+                                },
+                                "headers": { "x-region": ["{Region}"] },
+                            },
+                            "type": "endpoint",
+                        },
+                    ],
+                    "type": "tree",
+                },
+                { "conditions": [], "error": "Missing Region", "type": "error" },
+            ],
+        }))
+        .unwrap();
+        let params =
+            |value: Value| serde_json::from_value::<IndexMap<String, Value>>(value).unwrap();
+        let mut evaluator = Evaluator::new();
+
+        let error = evaluator
+            .resolve(&rule_set, &params(json!({ "Region": "us-east-1" })))
+            .unwrap_err();
+        assert!(matches!(error, ResolveError::MissingPartitions));
+
+        evaluator.partitions(&TestPartitions);
+        let endpoint = evaluator
+            .resolve(&rule_set, &params(json!({ "Region": "cn-north-1" })))
+            .unwrap();
+        assert_eq!(endpoint.url, "https://example.cn-north-1.amazonaws.com.cn");
+        assert_eq!(
+            Value::Object(endpoint.properties),
+            json!({
+                "authSchemes": [{
+                    "name": "sigv4",
+                    "signingName": "example",
+                    "signingRegion": "cn-north-1",
+                }],
+            })
+        );
+        assert_eq!(endpoint.headers["x-region"], ["cn-north-1"]);
+
+        let endpoint = evaluator
+            .resolve(
+                &rule_set,
+                &params(json!({ "Region": "us-east-1", "UseFIPS": true })),
+            )
+            .unwrap();
+        assert_eq!(endpoint.url, "https://example-fips.us-east-1.amazonaws.com");
+
+        let endpoint = evaluator
+            .resolve(
+                &rule_set,
+                &params(json!({ "Endpoint": "https://localhost:8080" })),
+            )
+            .unwrap();
+        assert_eq!(endpoint.url, "https://localhost:8080/v1");
+
+        let error = evaluator
+            .resolve(&rule_set, &params(json!({})))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Missing Region");
+
+        let error = evaluator
+            .resolve(&rule_set, &params(json!({ "UseFIPS": "yes" })))
+            .unwrap_err();
+        assert!(matches!(error, ResolveError::Invalid(_)));
+    }
+}
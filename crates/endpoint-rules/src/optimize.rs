@@ -0,0 +1,437 @@
+//! Simplifying rule sets without changing the endpoints they resolve, so generated resolvers
+//! are smaller and faster.
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use laws_schema::{
+    EndpointRule, EndpointRuleCondition, EndpointRuleConditionFn, EndpointRuleExpr,
+    EndpointRuleItem, EndpointRuleSet,
+};
+
+use crate::{template_parts, Evaluator, ResolveError, TemplatePart};
+
+/// The most times to run the passes, which usually stop changing the rules after two or three.
+const MAX_PASSES: usize = 8;
+
+impl<'a> Evaluator<'a> {
+    /// Optimizes a rule set: folds constant expressions, removes rules that can never match or
+    /// are never reached, removes conditions already known to hold, hoists conditions repeated
+    /// at the start of consecutive rules into a tree, and flattens trees with a single rule.
+    /// The optimized rule set resolves the same endpoints and errors for any parameters.
+    pub fn optimize(&self, rule_set: &EndpointRuleSet) -> Result<EndpointRuleSet, ResolveError> {
+        let mut rules = self.partially_evaluate(rule_set, &IndexMap::new())?.rules;
+        for _ in 0..MAX_PASSES {
+            let optimized = flatten(hoist(simplify(&rules, &Facts::default())));
+            if optimized == rules {
+                break;
+            }
+            rules = optimized;
+        }
+        Ok(EndpointRuleSet {
+            parameters: rule_set.parameters.clone(),
+            rules,
+        })
+    }
+}
+
+/// What is known about conditions at a point in the rules.
+#[derive(Debug, Clone, Default)]
+struct Facts {
+    /// Conditions that held, so are truthy.
+    held: Vec<EndpointRuleCondition>,
+    /// Functions that gave `false` or an unset value.
+    failed: Vec<EndpointRuleConditionFn>,
+}
+
+impl Facts {
+    /// Whether the function is known to give a truthy value, or a falsy one.
+    fn truthy(&self, function: &EndpointRuleConditionFn) -> Option<bool> {
+        use EndpointRuleConditionFn as Fn;
+        if self.held.iter().any(|held| held.function == *function) {
+            return Some(true);
+        }
+        if self.failed.contains(function) {
+            return Some(false);
+        }
+        match function {
+            Fn::Not(EndpointRuleExpr::Condition(inner)) => match self.truthy(&inner.function)? {
+                true => Some(false),
+                // `false` or unset, but only `false` can be negated.
+                false if is_boolean(&inner.function) => Some(true),
+                false => None,
+            },
+            Fn::BooleanEquals(lhs, EndpointRuleExpr::Boolean(value)) => {
+                let opposite = Fn::BooleanEquals(lhs.clone(), EndpointRuleExpr::Boolean(!value));
+                self.held
+                    .iter()
+                    .any(|held| held.function == opposite)
+                    .then_some(false)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Whether the function always gives a boolean, rather than possibly an unset value.
+fn is_boolean(function: &EndpointRuleConditionFn) -> bool {
+    use EndpointRuleConditionFn as Fn;
+    matches!(
+        function,
+        Fn::Not(_)
+            | Fn::BooleanEquals(..)
+            | Fn::StringEquals(..)
+            | Fn::IsSet(_)
+            | Fn::IsValidHostLabel(..)
+            | Fn::AwsIsVirtualHostableS3Bucket(..)
+    )
+}
+
+/// Removes the conditions known to hold given those before them, and the rules with a
+/// condition known not to.
+fn simplify(rules: &[EndpointRuleItem], facts: &Facts) -> Vec<EndpointRuleItem> {
+    let mut facts = facts.clone();
+    let mut simplified = vec![];
+    'rules: for rule in rules {
+        let mut rule_facts = facts.clone();
+        let mut conditions = vec![];
+        for condition in &rule.conditions {
+            match rule_facts.truthy(&condition.function) {
+                Some(false) => continue 'rules,
+                // Assigning the same name the same value again does nothing.
+                Some(true) if condition.assign.is_none() || rule_facts.held.contains(condition) => {
+                }
+                _ => {
+                    rule_facts.held.push(condition.clone());
+                    conditions.push(condition.clone());
+                }
+            }
+        }
+
+        let body = match &rule.rule {
+            EndpointRule::Tree { rules } => {
+                let rules = simplify(rules, &rule_facts);
+                if conditions.is_empty() {
+                    simplified.extend(rules);
+                    break;
+                }
+                EndpointRule::Tree { rules }
+            }
+            body => body.clone(),
+        };
+        // The rules after one with a single condition are only reached if it failed.
+        if let [condition] = &conditions[..] {
+            facts.failed.push(condition.function.clone());
+        }
+        let always_matches = conditions.is_empty();
+        simplified.push(EndpointRuleItem {
+            conditions,
+            rule: body,
+        });
+        if always_matches {
+            break;
+        }
+    }
+    simplified
+}
+
+/// Moves a condition that consecutive rules start with into a tree of them, where that doesn't
+/// change which rule matches: when the last of them has no other conditions, so the tree always
+/// resolves if the condition holds, or when they are the last rules anyway.
+fn hoist(rules: Vec<EndpointRuleItem>) -> Vec<EndpointRuleItem> {
+    let mut rules = rules
+        .into_iter()
+        .map(|mut rule| {
+            if let EndpointRule::Tree { rules } = rule.rule {
+                rule.rule = EndpointRule::Tree {
+                    rules: hoist(rules),
+                };
+            }
+            rule
+        })
+        .collect::<Vec<_>>();
+
+    let mut hoisted = vec![];
+    while !rules.is_empty() {
+        let first = rules[0].conditions.first().cloned();
+        let run = match &first {
+            Some(first) => rules
+                .iter()
+                .take_while(|rule| rule.conditions.first() == Some(first))
+                .count(),
+            None => 1,
+        };
+        let group = rules.drain(..run).collect::<Vec<_>>();
+        let resolves = group.last().is_some_and(|rule| rule.conditions.len() == 1);
+        match first {
+            Some(condition) if run > 1 && (resolves || rules.is_empty()) => {
+                let group = group
+                    .into_iter()
+                    .map(|mut rule| {
+                        rule.conditions.remove(0);
+                        rule
+                    })
+                    .collect();
+                hoisted.push(EndpointRuleItem {
+                    conditions: vec![condition],
+                    rule: EndpointRule::Tree { rules: group },
+                });
+            }
+            _ => hoisted.extend(group),
+        }
+    }
+    hoisted
+}
+
+/// Merges trees of a single rule into that rule, where that doesn't change which rule matches:
+/// when the rule has no conditions, or the tree is the last rule anyway.
+fn flatten(rules: Vec<EndpointRuleItem>) -> Vec<EndpointRuleItem> {
+    let count = rules.len();
+    rules
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut rule)| {
+            let EndpointRule::Tree { rules } = rule.rule else {
+                return rule;
+            };
+            let mut rules = flatten(rules);
+            match &mut rules[..] {
+                [child] if child.conditions.is_empty() || index == count - 1 => {
+                    rule.conditions.append(&mut child.conditions);
+                    rule.rule = rules.remove(0).rule;
+                }
+                _ => rule.rule = EndpointRule::Tree { rules },
+            }
+            rule
+        })
+        .collect()
+}
+
+/// Whether the rule's conditions or body refer to the name.
+pub(crate) fn uses(name: &str, conditions: &[EndpointRuleCondition], rule: &EndpointRule) -> bool {
+    let condition_uses = |condition: &EndpointRuleCondition| {
+        condition
+            .function
+            .args()
+            .into_iter()
+            .any(|arg| expr_uses(name, arg))
+    };
+    conditions.iter().any(condition_uses)
+        || match rule {
+            EndpointRule::Tree { rules } => rules
+                .iter()
+                .any(|rule| uses(name, &rule.conditions, &rule.rule)),
+            EndpointRule::Error { error } => template_uses(name, error),
+            EndpointRule::Endpoint { endpoint } => {
+                let properties = serde_json::to_value(&endpoint.properties)
+                    .expect("properties should serialize");
+                expr_uses(name, &endpoint.url)
+                    || json_uses(name, &properties)
+                    || endpoint
+                        .headers
+                        .values()
+                        .flatten()
+                        .any(|value| template_uses(name, value))
+            }
+        }
+}
+
+fn expr_uses(name: &str, expr: &EndpointRuleExpr) -> bool {
+    match expr {
+        EndpointRuleExpr::Condition(condition) => condition
+            .function
+            .args()
+            .into_iter()
+            .any(|arg| expr_uses(name, arg)),
+        EndpointRuleExpr::Reference { name: reference } => reference == name,
+        EndpointRuleExpr::String(template) => template_uses(name, template),
+        EndpointRuleExpr::Boolean(_) => false,
+    }
+}
+
+fn json_uses(name: &str, value: &Value) -> bool {
+    match value {
+        Value::String(template) => template_uses(name, template),
+        Value::Array(items) => items.iter().any(|item| json_uses(name, item)),
+        Value::Object(object) => object.values().any(|value| json_uses(name, value)),
+        Value::Null | Value::Bool(_) | Value::Number(_) => false,
+    }
+}
+
+fn template_uses(name: &str, template: &str) -> bool {
+    // Assume an invalid template uses everything, so it stays as invalid.
+    let Ok(parts) = template_parts(template) else {
+        return true;
+    };
+    parts.iter().any(|part| match part {
+        TemplatePart::Value {
+            name: reference, ..
+        } => *reference == name,
+        TemplatePart::Text(_) => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn optimize() {
+        let rule_set = serde_json::from_value::<EndpointRuleSet>(json!({
+            "parameters": {
+                "Region": { "required": false, "documentation": "", "type": "String" },
+                "UseFIPS": {
+                    "required": true,
+                    "default": false,
+                    "documentation": "",
+                    "type": "Boolean",
+                },
+                "Endpoint": { "required": false, "documentation": "", "type": "String" },
+            },
+            "rules": [
+                {
+                    "conditions": [{ "fn": "isSet", "argv": [{ "ref": "Endpoint" }] }],
+                    "rules": [
+                        {
+                            "conditions": [
+                                { "fn": "booleanEquals", "argv": [{ "ref": "UseFIPS" }, true] },
+                            ],
+                            "error": "FIPS and custom endpoints are not supported",
+                            "type": "error",
+                        },
+                        {
+                            "conditions": [],
+                            "rules": [{
+                                "conditions": [],
+                                "endpoint": {
+                                    "url": { "ref": "Endpoint" },
+                                    "properties": {},
+                                    "headers": {},
+                                },
+                                "type": "endpoint",
+                            }],
+                            "type": "tree",
+                        },
+                    ],
+                    "type": "tree",
+                },
+                {
+                    "conditions": [
+                        { "fn": "isSet", "argv": [{ "ref": "Region" }] },
+                        { "fn": "booleanEquals", "argv": [{ "ref": "UseFIPS" }, true] },
+                    ],
+                    "endpoint": {
+                        "url": "https://example-fips.{Region}.amazonaws.com",
+                        "properties": {},
+                        "headers": {},
+                    },
+                    "type": "endpoint",
+                },
+                {
+                    "conditions": [
+                        { "fn": "isSet", "argv": [{ "ref": "Region" }] },
+                        { "fn": "isSet", "argv": [{ "ref": "UseFIPS" }] },
+                        { "fn": "isSet", "argv": [{ "ref": "Region" }] },
+                        { "fn": "booleanEquals", "argv": [{ "ref": "UseFIPS" }, true] },
+                    ],
+                    "error": "unreachable",
+                    "type": "error",
+                },
+                {
+                    "conditions": [
+                        { "fn": "isSet", "argv": [{ "ref": "Region" }] },
+                        { "fn": "booleanEquals", "argv": [true, true] },
+                    ],
+                    "endpoint": {
+                        "url": "https://example.{Region}.amazonaws.com",
+                        "properties": {},
+                        "headers": {},
+                    },
+                    "type": "endpoint",
+                },
+                { "conditions": [], "error": "Missing Region", "type": "error" },
+                { "conditions": [], "error": "unreachable", "type": "error" },
+            ],
+        }))
+        .unwrap();
+        let evaluator = Evaluator::new();
+        let optimized = evaluator.optimize(&rule_set).unwrap();
+        assert_eq!(
+            optimized.rules,
+            serde_json::from_value::<Vec<EndpointRuleItem>>(json!([
+                {
+                    "conditions": [{ "fn": "isSet", "argv": [{ "ref": "Endpoint" }] }],
+                    "rules": [
+                        {
+                            "conditions": [
+                                { "fn": "booleanEquals", "argv": [{ "ref": "UseFIPS" }, true] },
+                            ],
+                            "error": "FIPS and custom endpoints are not supported",
+                            "type": "error",
+                        },
+                        {
+                            "conditions": [],
+                            "endpoint": {
+                                "url": { "ref": "Endpoint" },
+                                "properties": {},
+                                "headers": {},
+                            },
+                            "type": "endpoint",
+                        },
+                    ],
+                    "type": "tree",
+                },
+                {
+                    "conditions": [{ "fn": "isSet", "argv": [{ "ref": "Region" }] }],
+                    "rules": [
+                        {
+                            "conditions": [
+                                { "fn": "booleanEquals", "argv": [{ "ref": "UseFIPS" }, true] },
+                            ],
+                            "endpoint": {
+                                "url": "https://example-fips.{Region}.amazonaws.com",
+                                "properties": {},
+                                "headers": {},
+                            },
+                            "type": "endpoint",
+                        },
+                        {
+                            "conditions": [],
+                            "endpoint": {
+                                "url": "https://example.{Region}.amazonaws.com",
+                                "properties": {},
+                                "headers": {},
+                            },
+                            "type": "endpoint",
+                        },
+                    ],
+                    "type": "tree",
+                },
+                { "conditions": [], "error": "Missing Region", "type": "error" },
+            ]))
+            .unwrap(),
+        );
+
+        for region in [json!("us-east-1"), Value::Null] {
+            for use_fips in [true, false] {
+                for endpoint in [json!("https://localhost"), Value::Null] {
+                    let params = serde_json::from_value::<IndexMap<String, Value>>(json!({
+                        "Region": region,
+                        "UseFIPS": use_fips,
+                        "Endpoint": endpoint,
+                    }))
+                    .unwrap();
+                    let original = evaluator.resolve(&rule_set, &params);
+                    let resolved = evaluator.resolve(&optimized, &params);
+                    assert_eq!(
+                        format!("{original:?}"),
+                        format!("{resolved:?}"),
+                        "{params:?}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,496 @@
+//! Partial evaluation of rule sets with only some of their parameters known, which simplifies
+//! them down to the rules that can still match.
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use laws_schema::{
+    Endpoint, EndpointPropertyMap, EndpointRule, EndpointRuleCondition, EndpointRuleConditionFn,
+    EndpointRuleExpr, EndpointRuleItem, EndpointRuleSet,
+};
+
+use crate::optimize::uses;
+use crate::{
+    escape_template, functions, lookup, param_value, template_parts, template_type_error,
+    Evaluator, ResolveError, TemplatePart,
+};
+
+#[derive(Debug, Clone)]
+enum Partial {
+    Known(Value),
+    /// Only known when the rule set is resolved, though known not to be unset if `set`.
+    Unknown {
+        set: bool,
+    },
+}
+
+type PartialScope<'r> = Vec<(&'r str, Partial)>;
+
+impl<'a> Evaluator<'a> {
+    /// Simplifies a rule set given the values of some of its parameters, by name, where null
+    /// means unset. Resolving the result with the same values gives the same endpoint.
+    ///
+    /// Conditions that always hold are removed, along with the rules that can never match and
+    /// the rules after one that always does. Known values are substituted for references to
+    /// them, except for objects, whose assignments are kept where they are used.
+    pub fn partially_evaluate(
+        &self,
+        rule_set: &EndpointRuleSet,
+        known: &IndexMap<String, Value>,
+    ) -> Result<EndpointRuleSet, ResolveError> {
+        if let Some(name) = known
+            .keys()
+            .find(|name| !rule_set.parameters.contains_key(*name))
+        {
+            return Err(ResolveError::Invalid(format!("{name} is not a parameter")));
+        }
+        let mut scope = PartialScope::new();
+        for (name, param) in &rule_set.parameters {
+            let partial = match known.get(name) {
+                Some(value) => Partial::Known(param_value(name, param, Some(value))?),
+                None => Partial::Unknown {
+                    set: param.required || param.default.is_some(),
+                },
+            };
+            scope.push((name, partial));
+        }
+        Ok(EndpointRuleSet {
+            parameters: rule_set.parameters.clone(),
+            rules: self.partial_rules(&rule_set.rules, &mut scope)?,
+        })
+    }
+
+    fn partial_rules<'r>(
+        &self,
+        rules: &'r [EndpointRuleItem],
+        scope: &mut PartialScope<'r>,
+    ) -> Result<Vec<EndpointRuleItem>, ResolveError> {
+        let mut simplified = vec![];
+        for rule in rules {
+            let depth = scope.len();
+            let Some((conditions, always_matches)) =
+                self.partial_conditions(&rule.conditions, scope)?
+            else {
+                scope.truncate(depth);
+                continue;
+            };
+            let body = match &rule.rule {
+                EndpointRule::Tree { rules } => EndpointRule::Tree {
+                    rules: self.partial_rules(rules, scope)?,
+                },
+                EndpointRule::Error { error } => EndpointRule::Error {
+                    error: self.partial_template(error, scope)?.0,
+                },
+                EndpointRule::Endpoint { endpoint } => EndpointRule::Endpoint {
+                    endpoint: self.partial_endpoint(endpoint, scope)?,
+                },
+            };
+            scope.truncate(depth);
+
+            // Known values are only assigned for what uses them, since they always match.
+            let mut needed = vec![];
+            for (index, (condition, known)) in conditions.iter().enumerate() {
+                let later = conditions[index + 1..]
+                    .iter()
+                    .map(|(condition, _)| condition.clone())
+                    .collect::<Vec<_>>();
+                let used = |name| uses(name, &later, &body);
+                if !known || condition.assign.as_deref().is_some_and(used) {
+                    needed.push(condition.clone());
+                }
+            }
+            match body {
+                // The tree always matches, so its rules replace it and those after it.
+                EndpointRule::Tree { rules } if needed.is_empty() => {
+                    simplified.extend(rules);
+                    break;
+                }
+                body => simplified.push(EndpointRuleItem {
+                    conditions: needed,
+                    rule: body,
+                }),
+            }
+            if always_matches {
+                break;
+            }
+        }
+        Ok(simplified)
+    }
+
+    /// The conditions that are still needed, with whether their value is known, and whether
+    /// the rule always matches, or `None` if it never does.
+    #[allow(clippy::type_complexity)]
+    fn partial_conditions<'r>(
+        &self,
+        conditions: &'r [EndpointRuleCondition],
+        scope: &mut PartialScope<'r>,
+    ) -> Result<Option<(Vec<(EndpointRuleCondition, bool)>, bool)>, ResolveError> {
+        let mut residual = vec![];
+        let mut always_matches = true;
+        for condition in conditions {
+            let (value, function) = self.partial_call(&condition.function, scope)?;
+            let assign = condition.assign.clone();
+            match value {
+                Partial::Known(Value::Null | Value::Bool(false)) => return Ok(None),
+                Partial::Known(value) => {
+                    if let Some(name) = &condition.assign {
+                        if literal(&value).is_none() {
+                            residual.push((EndpointRuleCondition { assign, function }, true));
+                        }
+                        scope.push((name, Partial::Known(value)));
+                    }
+                }
+                Partial::Unknown { .. } => {
+                    always_matches = false;
+                    if let Some(name) = &condition.assign {
+                        // Matching means the value isn't unset.
+                        scope.push((name, Partial::Unknown { set: true }));
+                    }
+                    residual.push((EndpointRuleCondition { assign, function }, false));
+                }
+            }
+        }
+        Ok(Some((residual, always_matches)))
+    }
+
+    fn partial_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        scope: &PartialScope,
+    ) -> Result<Endpoint, ResolveError> {
+        let url = self.partial_expr(&endpoint.url, scope)?.1;
+        let mut properties =
+            serde_json::to_value(&endpoint.properties).expect("properties should serialize");
+        self.partial_templates(&mut properties, scope)?;
+        let properties = serde_json::from_value::<EndpointPropertyMap>(properties)
+            .expect("properties should still deserialize with templates expanded");
+        let headers = endpoint
+            .headers
+            .iter()
+            .map(|(name, values)| {
+                let values = values
+                    .iter()
+                    .map(|value| Ok(self.partial_template(value, scope)?.0))
+                    .collect::<Result<_, _>>()?;
+                Ok((name.clone(), values))
+            })
+            .collect::<Result<_, ResolveError>>()?;
+        Ok(Endpoint {
+            url,
+            properties,
+            headers,
+        })
+    }
+
+    fn partial_templates(
+        &self,
+        value: &mut Value,
+        scope: &PartialScope,
+    ) -> Result<(), ResolveError> {
+        match value {
+            Value::String(template) => *template = self.partial_template(template, scope)?.0,
+            Value::Array(items) => {
+                for item in items {
+                    self.partial_templates(item, scope)?;
+                }
+            }
+            Value::Object(object) => {
+                for value in object.values_mut() {
+                    self.partial_templates(value, scope)?;
+                }
+            }
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+        Ok(())
+    }
+
+    /// The function's value, if known, and the function with its arguments simplified.
+    fn partial_call(
+        &self,
+        function: &EndpointRuleConditionFn,
+        scope: &PartialScope,
+    ) -> Result<(Partial, EndpointRuleConditionFn), ResolveError> {
+        use EndpointRuleConditionFn as Fn;
+        let mut residual = function.clone();
+        let mut args = vec![];
+        for arg in residual.args_mut() {
+            let (value, expr) = self.partial_expr(arg, scope)?;
+            *arg = expr;
+            args.push(value);
+        }
+        let known_args = args
+            .iter()
+            .map(|arg| match arg {
+                Partial::Known(value) => Some(value.clone()),
+                Partial::Unknown { .. } => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        let can_apply = !matches!(function, Fn::AwsPartition(_)) || self.partitions.is_some();
+        let value = match (known_args, function, &args[..]) {
+            (Some(known_args), ..) if can_apply => {
+                Partial::Known(self.apply(function, &known_args)?)
+            }
+            (_, Fn::IsSet(_), [Partial::Unknown { set: true }]) => {
+                Partial::Known(Value::Bool(true))
+            }
+            (
+                _,
+                Fn::Not(_)
+                | Fn::BooleanEquals(..)
+                | Fn::StringEquals(..)
+                | Fn::IsSet(_)
+                | Fn::IsValidHostLabel(..)
+                | Fn::UriEncode(_)
+                | Fn::AwsIsVirtualHostableS3Bucket(..),
+                _,
+            ) => Partial::Unknown { set: true },
+            _ => Partial::Unknown { set: false },
+        };
+        Ok((value, residual))
+    }
+
+    /// The expression's value, if known, and the expression simplified, which is a literal if
+    /// the value can be one.
+    fn partial_expr(
+        &self,
+        expr: &EndpointRuleExpr,
+        scope: &PartialScope,
+    ) -> Result<(Partial, EndpointRuleExpr), ResolveError> {
+        let (value, residual) = match expr {
+            EndpointRuleExpr::Boolean(value) => (Partial::Known(Value::Bool(*value)), expr.clone()),
+            EndpointRuleExpr::String(template) => {
+                let (template, complete) = self.partial_template(template, scope)?;
+                let value = if complete {
+                    Partial::Known(Value::String(self.template(&template, &Vec::new())?))
+                } else {
+                    Partial::Unknown { set: true }
+                };
+                (value, EndpointRuleExpr::String(template))
+            }
+            EndpointRuleExpr::Reference { name } => (lookup(scope, name)?.clone(), expr.clone()),
+            EndpointRuleExpr::Condition(condition) => {
+                let (value, function) = self.partial_call(&condition.function, scope)?;
+                let condition = EndpointRuleCondition {
+                    assign: condition.assign.clone(),
+                    function,
+                };
+                (value, EndpointRuleExpr::Condition(Box::new(condition)))
+            }
+        };
+        let residual = match &value {
+            Partial::Known(value) => literal(value).unwrap_or(residual),
+            Partial::Unknown { .. } => residual,
+        };
+        Ok((value, residual))
+    }
+
+    /// Substitutes the known values into a template, returning whether they all were.
+    fn partial_template(
+        &self,
+        template: &str,
+        scope: &PartialScope,
+    ) -> Result<(String, bool), ResolveError> {
+        let mut residual = String::new();
+        let mut complete = true;
+        for part in template_parts(template)? {
+            match part {
+                TemplatePart::Text(text) => residual.push_str(&escape_template(&text)),
+                TemplatePart::Value { name, path, source } => {
+                    let Partial::Known(value) = lookup(scope, name)? else {
+                        complete = false;
+                        residual.push_str(source);
+                        continue;
+                    };
+                    let value = match path {
+                        Some(path) => functions::get_attr(value, path),
+                        None => value.clone(),
+                    };
+                    let Value::String(value) = value else {
+                        return Err(template_type_error(template, source, &value));
+                    };
+                    residual.push_str(&escape_template(&value));
+                }
+            }
+        }
+        Ok((residual, complete))
+    }
+}
+
+/// The value as a literal expression, if it can be one.
+fn literal(value: &Value) -> Option<EndpointRuleExpr> {
+    match value {
+        Value::Bool(value) => Some(EndpointRuleExpr::Boolean(*value)),
+        Value::String(value) => Some(EndpointRuleExpr::String(escape_template(value))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::PartitionResolver;
+
+    struct TestPartitions;
+
+    impl PartitionResolver for TestPartitions {
+        fn partition(&self, _region: &str) -> Option<Value> {
+            Some(json!({ "name": "aws", "dnsSuffix": "amazonaws.com", "supportsFIPS": false }))
+        }
+    }
+
+    #[test]
+    fn partially_evaluates() {
+        let rule_set = serde_json::from_value::<EndpointRuleSet>(json!({
+            "parameters": {
+                "Region": { "required": false, "documentation": "", "type": "String" },
+                "UseFIPS": {
+                    "required": true,
+                    "default": false,
+                    "documentation": "",
+                    "type": "Boolean",
+                },
+                "Endpoint": { "required": false, "documentation": "", "type": "String" },
+            },
+            "rules": [
+                {
+                    "conditions": [{ "fn": "isSet", "argv": [{ "ref": "Endpoint" }] }],
+                    "endpoint": { "url": { "ref": "Endpoint" }, "properties": {}, "headers": {} },
+                    "type": "endpoint",
+                },
+                {
+                    "conditions": [
+                        { "fn": "isSet", "argv": [{ "ref": "Region" }] },
+                        {
+                            "fn": "aws.partition",
+                            "argv": [{ "ref": "Region" }],
+                            "assign": "partition",
+                        },
+                    ],
+                    "rules": [
+                        {
+                            "conditions": [
+                                { "fn": "booleanEquals", "argv": [{ "ref": "UseFIPS" }, true] },
+                                {
+                                    "fn": "booleanEquals",
+                                    "argv": [
+                                        {
+                                            "fn": "getAttr",
+                                            "argv": [{ "ref": "partition" }, "supportsFIPS"],
+                                        },
+                                        false,
+                                    ],
+                                },
+                            ],
+                            "error": "FIPS is not supported in {Region}",
+                            "type": "error",
+                        },
+                        {
+                            "conditions": [],
+                            "endpoint": {
+                                "url": "https://example.{Region}.{partition#dnsSuffix}",
+                                "properties": {
+                                    "authSchemes": [{
+                                        "name": "sigv4",
+                                        "signingName": "example",
+                                        "signingRegion": "{Region}",
+                                    }],
+                                },
+                                "headers": {},
+                            },
+                            "type": "endpoint",
+                        },
+                    ],
+                    "type": "tree",
+                },
+                { "conditions": [], "error": "Missing Region", "type": "error" },
+            ],
+        }))
+        .unwrap();
+        let params =
+            |value: Value| serde_json::from_value::<IndexMap<String, Value>>(value).unwrap();
+        let rules = |value: Value| serde_json::from_value::<Vec<EndpointRuleItem>>(value).unwrap();
+        let mut evaluator = Evaluator::new();
+
+        // Without partitions, only what doesn't depend on the partition is simplified.
+        let known = params(json!({ "Region": "us-east-1", "UseFIPS": true, "Endpoint": null }));
+        let simplified = evaluator.partially_evaluate(&rule_set, &known).unwrap();
+        assert_eq!(
+            simplified.rules,
+            rules(json!([
+                {
+                    "conditions": [{
+                        "fn": "aws.partition",
+                        "argv": ["us-east-1"],
+                        "assign": "partition",
+                    }],
+                    "rules": [
+                        {
+                            "conditions": [{
+                                "fn": "booleanEquals",
+                                "argv": [
+                                    {
+                                        "fn": "getAttr",
+                                        "argv": [{ "ref": "partition" }, "supportsFIPS"],
+                                    },
+                                    false,
+                                ],
+                            }],
+                            "error": "FIPS is not supported in us-east-1",
+                            "type": "error",
+                        },
+                        {
+                            "conditions": [],
+                            "endpoint": {
+                                "url": "https://example.us-east-1.{partition#dnsSuffix}",
+                                "properties": {
+                                    "authSchemes": [{
+                                        "name": "sigv4",
+                                        "signingName": "example",
+                                        "signingRegion": "us-east-1",
+                                    }],
+                                },
+                                "headers": {},
+                            },
+                            "type": "endpoint",
+                        },
+                    ],
+                    "type": "tree",
+                },
+                { "conditions": [], "error": "Missing Region", "type": "error" },
+            ])),
+        );
+
+        evaluator.partitions(&TestPartitions);
+        let simplified = evaluator.partially_evaluate(&rule_set, &known).unwrap();
+        assert_eq!(
+            simplified.rules,
+            rules(json!([{
+                "conditions": [],
+                "error": "FIPS is not supported in us-east-1",
+                "type": "error",
+            }])),
+        );
+
+        // Unknown parameters keep the conditions on them, and the simplified rules resolve the
+        // same as the original ones.
+        let known = params(json!({ "UseFIPS": false }));
+        let simplified = evaluator.partially_evaluate(&rule_set, &known).unwrap();
+        assert_eq!(simplified.rules.len(), 3);
+        for region in [json!("us-west-2"), Value::Null] {
+            let params = params(json!({ "Region": region, "UseFIPS": false }));
+            let original = evaluator.resolve(&rule_set, &params);
+            let simplified = evaluator.resolve(&simplified, &params);
+            assert_eq!(format!("{original:?}"), format!("{simplified:?}"));
+        }
+
+        let error = evaluator
+            .partially_evaluate(&rule_set, &params(json!({ "Regoin": "us-east-1" })))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid rule set: Regoin is not a parameter"
+        );
+    }
+}
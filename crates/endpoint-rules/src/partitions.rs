@@ -0,0 +1,227 @@
+//! The AWS partitions, from the `partitions.json` shipped alongside the models, which
+//! `aws.partition` looks regions up in.
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::PartitionResolver;
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("invalid partitions: {0}")]
+#[diagnostic(code(laws_endpoint_rules::partitions))]
+pub struct PartitionsError(#[from] serde_json::Error);
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Partitions {
+    pub version: String,
+    pub partitions: Vec<Partition>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Partition {
+    /// The partition id, e.g. `aws-cn`.
+    pub id: String,
+    /// Matches the names of regions in the partition, including those not listed yet.
+    pub region_regex: RegionRegex,
+    pub regions: IndexMap<String, Region>,
+    pub outputs: PartitionOutputs,
+}
+
+/// The properties `aws.partition` returns for a region.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionOutputs {
+    pub name: String,
+    pub dns_suffix: String,
+    pub dual_stack_dns_suffix: String,
+    #[serde(rename = "supportsFIPS")]
+    pub supports_fips: bool,
+    pub supports_dual_stack: bool,
+    /// The region to sign requests to global endpoints for, e.g. `us-east-1`.
+    pub implicit_global_region: String,
+}
+
+/// A region listed in a partition, which may override some of the partition's outputs.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_suffix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dual_stack_dns_suffix: Option<String>,
+    #[serde(
+        default,
+        rename = "supportsFIPS",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub supports_fips: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_dual_stack: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implicit_global_region: Option<String>,
+}
+
+/// A compiled `regionRegex`, which (de)serializes as its pattern.
+#[derive(Debug, Clone)]
+pub struct RegionRegex(pub regex::Regex);
+
+impl PartialEq for RegionRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for RegionRegex {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        regex::Regex::new(&pattern)
+            .map(RegionRegex)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl serde::Serialize for RegionRegex {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl Partitions {
+    pub fn from_json(source: &str) -> Result<Self, PartitionsError> {
+        Ok(serde_json::from_str(source)?)
+    }
+
+    /// Finds the partition a region is in: the one listing it, else the first whose
+    /// `regionRegex` matches, else `aws`.
+    pub fn find(&self, region: &str) -> Option<&Partition> {
+        self.partitions
+            .iter()
+            .find(|partition| partition.regions.contains_key(region))
+            .or_else(|| {
+                self.partitions
+                    .iter()
+                    .find(|partition| partition.region_regex.0.is_match(region))
+            })
+            .or_else(|| {
+                self.partitions
+                    .iter()
+                    .find(|partition| partition.id == "aws")
+            })
+    }
+
+    /// The outputs of the region's partition, with the region's overrides applied.
+    pub fn outputs(&self, region: &str) -> Option<PartitionOutputs> {
+        let partition = self.find(region)?;
+        let mut outputs = partition.outputs.clone();
+        if let Some(overrides) = partition.regions.get(region) {
+            let Region {
+                description: _,
+                dns_suffix,
+                dual_stack_dns_suffix,
+                supports_fips,
+                supports_dual_stack,
+                implicit_global_region,
+            } = overrides.clone();
+            outputs.dns_suffix = dns_suffix.unwrap_or(outputs.dns_suffix);
+            outputs.dual_stack_dns_suffix =
+                dual_stack_dns_suffix.unwrap_or(outputs.dual_stack_dns_suffix);
+            outputs.supports_fips = supports_fips.unwrap_or(outputs.supports_fips);
+            outputs.supports_dual_stack =
+                supports_dual_stack.unwrap_or(outputs.supports_dual_stack);
+            outputs.implicit_global_region =
+                implicit_global_region.unwrap_or(outputs.implicit_global_region);
+        }
+        Some(outputs)
+    }
+}
+
+impl PartitionResolver for Partitions {
+    fn partition(&self, region: &str) -> Option<Value> {
+        let outputs = self.outputs(region)?;
+        Some(serde_json::to_value(outputs).expect("partition outputs should serialize"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        let partitions = Partitions::from_json(
+            &json!({
+                "version": "1.1",
+                "partitions": [
+                    {
+                        "id": "aws",
+                        "regionRegex": "^(us|eu|ap|sa|ca|me|af|il)\\-\\w+\\-\\d+$",
+                        "regions": {
+                            "aws-global": { "description": "AWS Standard global region" },
+                            "us-east-1": { "description": "US East (N. Virginia)" },
+                        },
+                        "outputs": {
+                            "name": "aws",
+                            "dnsSuffix": "amazonaws.com",
+                            "dualStackDnsSuffix": "api.aws",
+                            "supportsFIPS": true,
+                            "supportsDualStack": true,
+                            "implicitGlobalRegion": "us-east-1",
+                        },
+                    },
+                    {
+                        "id": "aws-cn",
+                        "regionRegex": "^cn\\-\\w+\\-\\d+$",
+                        "regions": {
+                            "aws-cn-global": {
+                                "description": "AWS China global region",
+                                "supportsFIPS": true,
+                            },
+                        },
+                        "outputs": {
+                            "name": "aws-cn",
+                            "dnsSuffix": "amazonaws.com.cn",
+                            "dualStackDnsSuffix": "api.amazonwebservices.com.cn",
+                            "supportsFIPS": false,
+                            "supportsDualStack": true,
+                            "implicitGlobalRegion": "cn-northwest-1",
+                        },
+                    },
+                ],
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(partitions.find("us-east-1").unwrap().id, "aws");
+        assert_eq!(partitions.find("cn-north-1").unwrap().id, "aws-cn");
+        assert_eq!(partitions.find("mars-west-1").unwrap().id, "aws");
+
+        let outputs = partitions.outputs("cn-north-1").unwrap();
+        assert_eq!(outputs.dns_suffix, "amazonaws.com.cn");
+        assert!(!outputs.supports_fips);
+        assert!(partitions.outputs("aws-cn-global").unwrap().supports_fips);
+
+        assert_eq!(
+            partitions.partition("eu-west-1"),
+            Some(json!({
+                "name": "aws",
+                "dnsSuffix": "amazonaws.com",
+                "dualStackDnsSuffix": "api.aws",
+                "supportsFIPS": true,
+                "supportsDualStack": true,
+                "implicitGlobalRegion": "us-east-1",
+            }))
+        );
+
+        let error = Partitions::from_json(r#"{ "version": "1.1", "partitions": [{ "id": "aws", "regionRegex": "(", "regions": {}, "outputs": {} }] }"#)
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("invalid partitions: regex parse error"));
+    }
+}
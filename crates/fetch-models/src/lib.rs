@@ -3,6 +3,35 @@ use std::path::Path;
 use miette::{IntoDiagnostic, Result, WrapErr};
 
 pub fn fetch_models(path: &Path) -> Result<()> {
+    let repo = fetch_repository()?;
+    let root_tree = origin_main_tree(&repo)?;
+    let models_tree = root_tree
+        .get_path(Path::new("codegen/sdk-codegen/aws-models"))
+        .into_diagnostic()
+        .wrap_err("finding codegen models tree in origin/main root tree")?;
+
+    repo.checkout_tree(
+        &models_tree
+            .to_object(&repo)
+            .into_diagnostic()
+            .wrap_err("converting models tree to object")?,
+        Some(git2::build::CheckoutBuilder::new().force().target_dir(path)),
+    )
+    .into_diagnostic()
+    .wrap_err("checking out model tree")?;
+
+    write_partitions(&repo, &root_tree, path)
+}
+
+/// Fetches only the partitions to the models directory, for models fetched before they were
+/// included.
+pub fn fetch_partitions(path: &Path) -> Result<()> {
+    let repo = fetch_repository()?;
+    let root_tree = origin_main_tree(&repo)?;
+    write_partitions(&repo, &root_tree, path)
+}
+
+fn fetch_repository() -> Result<git2::Repository> {
     let repo = git2::Repository::init_opts(
         ".aws-sdk.git",
         git2::RepositoryInitOptions::new().bare(true),
@@ -63,28 +92,34 @@ pub fn fetch_models(path: &Path) -> Result<()> {
         )
         .into_diagnostic()
         .wrap_err("fetching remote")?;
+    Ok(repo)
+}
 
-    let origin_main = repo
-        .find_reference("refs/remotes/origin/main")
+fn origin_main_tree(repo: &git2::Repository) -> Result<git2::Tree<'_>> {
+    repo.find_reference("refs/remotes/origin/main")
         .into_diagnostic()
-        .wrap_err("finding origin/main reference")?;
-    let root_tree = origin_main
+        .wrap_err("finding origin/main reference")?
         .peel_to_tree()
         .into_diagnostic()
-        .wrap_err("peeling origin/main reference to tree")?;
-    let models_tree = root_tree
-        .get_path(Path::new("codegen/sdk-codegen/aws-models"))
-        .into_diagnostic()
-        .wrap_err("finding codegen models tree in origin/main root tree")?;
+        .wrap_err("peeling origin/main reference to tree")
+}
 
-    repo.checkout_tree(
-        &models_tree
-            .to_object(&repo)
-            .into_diagnostic()
-            .wrap_err("converting models tree to object")?,
-        Some(git2::build::CheckoutBuilder::new().force().target_dir(path)),
-    )
-    .into_diagnostic()
-    .wrap_err("checking out model tree")?;
+fn write_partitions(repo: &git2::Repository, root_tree: &git2::Tree, path: &Path) -> Result<()> {
+    // The partitions `aws.partition` looks regions up in, which the models don't include.
+    let partitions_blob = root_tree
+        .get_path(Path::new(
+            "packages/util-endpoints/src/lib/aws/partitions.json",
+        ))
+        .into_diagnostic()
+        .wrap_err("finding partitions.json in origin/main root tree")?
+        .to_object(repo)
+        .into_diagnostic()
+        .wrap_err("converting partitions.json entry to object")?
+        .peel_to_blob()
+        .into_diagnostic()
+        .wrap_err("peeling partitions.json to blob")?;
+    std::fs::write(path.join("partitions.json"), partitions_blob.content())
+        .into_diagnostic()
+        .wrap_err("writing partitions.json")?;
     Ok(())
 }
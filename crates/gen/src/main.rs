@@ -1,15 +1,23 @@
 #![allow(dead_code)]
 
 use clap::{Parser, Subcommand};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsString;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use indexmap::IndexMap;
 use miette::{IntoDiagnostic, Result, WrapErr};
 use rayon::prelude::*;
 
+use laws_endpoint_rules as endpoint_rules;
 use laws_schema as schema;
 
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_RESET: &str = "\x1b[0m";
+const TICK: &str = "\u{2713}";
+
 #[derive(Parser)]
 struct Args {
     #[clap(subcommand)]
@@ -19,12 +27,49 @@ struct Args {
 #[derive(Subcommand)]
 enum Command {
     FetchModels,
-    WriteTs,
+    WriteTs {
+        /// Only write these operations and the types they use, e.g. `s3:GetObject,s3:PutObject`.
+        #[clap(long, value_delimiter = ',')]
+        operations: Vec<String>,
+    },
+    /// Prints a service's endpoint rules, simplified with the parameter values that are known.
     DumpEndpointRules {
         #[clap(name = "SERVICE")]
         name: String,
-        #[clap(short, long)]
-        minimal: bool,
+        /// A known parameter value, e.g. `Region=us-east-1`, where `Endpoint=` means unset.
+        #[clap(long = "param", value_name = "NAME=VALUE")]
+        params: Vec<String>,
+        /// A JSON object of known parameter values, by name, where null means unset.
+        #[clap(long, value_name = "FILE")]
+        params_file: Option<PathBuf>,
+        /// Also optimize the rules, which folds constants and merges repeated conditions.
+        #[clap(long)]
+        optimize: bool,
+        /// Write the simplified rule set as JSON.
+        #[clap(long)]
+        json: bool,
+    },
+    /// Runs the endpoint test cases of one or all services against their endpoint rule sets.
+    TestEndpointRules {
+        #[clap(name = "SERVICE")]
+        name: Option<String>,
+    },
+    Validate {
+        #[clap(name = "SERVICE")]
+        name: Option<String>,
+        /// Treat traits the schema doesn't define as errors.
+        #[clap(long)]
+        strict: bool,
+    },
+    /// Lists the changes between two directories of models, matching models by file name.
+    Diff {
+        #[clap(name = "OLD_DIR")]
+        old: PathBuf,
+        #[clap(name = "NEW_DIR")]
+        new: PathBuf,
+        /// Write the changes as JSON.
+        #[clap(long)]
+        json: bool,
     },
 }
 
@@ -33,7 +78,8 @@ fn main() -> Result<()> {
     let ts_services_dir_path = Path::new("ts-client/src/services");
 
     let args = Args::parse();
-    if matches!(args.command, Some(Command::FetchModels)) || !models_path.exists() {
+    let uses_models = !matches!(args.command, Some(Command::Diff { .. }));
+    if matches!(args.command, Some(Command::FetchModels)) || uses_models && !models_path.exists() {
         println!("fetching models to {}", models_path.display());
         laws_fetch_models::fetch_models(models_path).wrap_err("fetching models")?;
     }
@@ -42,39 +88,291 @@ fn main() -> Result<()> {
         Some(Command::FetchModels) => {
             // already handled.
         }
-        Some(Command::DumpEndpointRules { name, minimal }) => {
+        Some(Command::DumpEndpointRules {
+            name,
+            params,
+            params_file,
+            optimize,
+            json,
+        }) => {
             let model_path = models_path.join(format!("{}.json", name));
-            let model = parse_model(&model_path)?;
-            dump_endpoint_rules(
-                &model,
-                if minimal {
-                    EndpointRulesFilter::minimal()
-                } else {
-                    EndpointRulesFilter::default()
-                },
-            )?;
+            let model = parse_model(&model_path, &schema::ParseOptions::default())?;
+            let partitions = load_partitions(models_path)?;
+            let mut evaluator = endpoint_rules::Evaluator::new();
+            if let Some(partitions) = &partitions {
+                evaluator.partitions(partitions);
+            }
+            let rule_set = service_endpoint_rule_set(&model)?;
+            let known = known_endpoint_params(rule_set, &params, params_file.as_deref())?;
+            let mut simplified = evaluator.partially_evaluate(rule_set, &known)?;
+            if optimize {
+                simplified = evaluator.optimize(&simplified)?;
+            }
+            if json {
+                let json = serde_json::to_string_pretty(&simplified).into_diagnostic()?;
+                println!("{json}");
+            } else {
+                dump_endpoint_rules(&simplified);
+            }
         }
-        None | Some(Command::WriteTs) => {
-            write_ts(models_path, ts_services_dir_path)?;
+        Some(Command::TestEndpointRules { name }) => {
+            let model_paths = match name {
+                Some(name) => vec![models_path.join(format!("{name}.json"))],
+                None => model_paths(models_path)?,
+            };
+            let partitions = load_partitions(models_path)?;
+            test_endpoint_rules(&model_paths, partitions.as_ref())?;
+        }
+        Some(Command::Validate { name, strict }) => {
+            let model_paths = match name {
+                Some(name) => vec![models_path.join(format!("{name}.json"))],
+                None => model_paths(models_path)?,
+            };
+            validate(&model_paths, &schema::ParseOptions { strict })?;
+        }
+        Some(Command::Diff { old, new, json }) => {
+            diff(&old, &new, json)?;
+        }
+        None => {
+            write_ts(models_path, ts_services_dir_path, &[])?;
+        }
+        Some(Command::WriteTs { operations }) => {
+            write_ts(models_path, ts_services_dir_path, &operations)?;
         }
     }
 
     Ok(())
 }
 
-fn parse_model(path: &Path) -> Result<schema::Model> {
+fn parse_model(path: &Path, options: &schema::ParseOptions) -> Result<schema::Model> {
     let source = fs::read_to_string(path)
         .into_diagnostic()
         .wrap_err_with(|| format!("reading {path:?}"))?;
 
-    let model: schema::Model = schema::parse_model(&source)
+    let name = path.display().to_string();
+    let model: schema::Model = if path.extension().is_some_and(|ext| ext == "smithy") {
+        schema::parse_idl(&name, &source).wrap_err_with(|| format!("parsing {path:?}"))?
+    } else {
+        let (model, warnings) = schema::parse_model_with_options(&name, &source, options)
+            .wrap_err_with(|| format!("parsing {path:?}"))?;
+        for warning in warnings {
+            render_report(&miette::Report::new(warning));
+        }
+        model
+    };
+
+    model
+        .flatten_mixins()
+        .wrap_err_with(|| format!("flattening mixins in {path:?}"))
+}
+
+/// Fetched alongside the models, but not a model.
+const PARTITIONS_FILE_NAME: &str = "partitions.json";
+
+/// Loads the partitions from the models directory, if they were fetched with the models.
+fn load_partitions(models_path: &Path) -> Result<Option<endpoint_rules::Partitions>> {
+    let path = models_path.join(PARTITIONS_FILE_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let source = fs::read_to_string(&path)
         .into_diagnostic()
+        .wrap_err_with(|| format!("reading {path:?}"))?;
+    let partitions = endpoint_rules::Partitions::from_json(&source)
         .wrap_err_with(|| format!("parsing {path:?}"))?;
+    Ok(Some(partitions))
+}
 
-    Ok(model)
+fn model_paths(models_path: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = fs::read_dir(models_path)
+        .into_diagnostic()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            if !entry.file_type().ok()?.is_file() {
+                return None;
+            }
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            if file_name == PARTITIONS_FILE_NAME {
+                None
+            } else if file_name.ends_with(".json") || file_name.ends_with(".smithy") {
+                Some(entry.path())
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    Ok(paths)
 }
 
-fn write_ts(models_path: &Path, ts_services_dir_path: &Path) -> Result<()> {
+fn validate(model_paths: &[PathBuf], options: &schema::ParseOptions) -> Result<()> {
+    let mut valid = 0;
+    for path in model_paths {
+        let model = match parse_model(path, options) {
+            Ok(model) => model,
+            Err(error) => {
+                render_report(&error);
+                continue;
+            }
+        };
+        let mut errors = schema::validate(&model)
+            .into_iter()
+            .map(miette::Report::new)
+            .collect::<Vec<_>>();
+        for (id, shape) in &model.shapes {
+            let schema::Shape::Service(service) = shape else {
+                continue;
+            };
+            let schema::EndpointRuleSetTrait::V1_0(rule_set) = &service.traits.endpoint_rule_set;
+            errors.extend(
+                endpoint_rules::check(rule_set).into_iter().map(|error| {
+                    miette::Report::new(error).wrap_err(format!("{id} endpoint rules"))
+                }),
+            );
+        }
+        if errors.is_empty() {
+            println!("  {ANSI_GREEN}{TICK}{ANSI_RESET} validated {path:?}");
+            valid += 1;
+            continue;
+        }
+        eprintln!("{path:?} has {} problem(s):", errors.len());
+        for error in errors {
+            render_report(&error);
+        }
+    }
+    println!("{valid} / {} models are valid", model_paths.len());
+    if valid < model_paths.len() {
+        miette::bail!("some models are invalid");
+    }
+    Ok(())
+}
+
+fn diff(old_dir: &Path, new_dir: &Path, json: bool) -> Result<()> {
+    let mut file_names = BTreeSet::new();
+    for dir in [old_dir, new_dir] {
+        let paths = model_paths(dir).wrap_err_with(|| format!("listing {dir:?}"))?;
+        file_names.extend(
+            paths
+                .into_iter()
+                .filter_map(|path| path.file_name().map(Into::into)),
+        );
+    }
+
+    // A model only in one directory is compared with an empty model.
+    let load = |dir: &Path, file_name: &OsString| -> Result<schema::Model> {
+        let path = dir.join(file_name);
+        if !path.exists() {
+            return Ok(schema::Model {
+                smithy: schema::SmithyVersion::_2_0,
+                metadata: Default::default(),
+                shapes: Default::default(),
+            });
+        }
+        parse_model(&path, &schema::ParseOptions::default())
+    };
+    let mut changes = vec![];
+    for file_name in &file_names {
+        let old = load(old_dir, file_name)?;
+        let new = load(new_dir, file_name)?;
+        let model_name = file_name.to_string_lossy().into_owned();
+        changes.extend(
+            schema::diff::diff(&old, &new)
+                .into_iter()
+                .map(|change| (model_name.clone(), change)),
+        );
+    }
+
+    if json {
+        let changes = changes
+            .iter()
+            .map(|(model_name, change)| {
+                let mut object = serde_json::Map::new();
+                object.insert("model".to_string(), model_name.clone().into());
+                if let serde_json::Value::Object(change) =
+                    serde_json::to_value(change).into_diagnostic()?
+                {
+                    object.extend(change);
+                }
+                Ok(serde_json::Value::Object(object))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&changes).into_diagnostic()?
+        );
+        return Ok(());
+    }
+
+    let mut current_model = None;
+    for (model_name, change) in &changes {
+        if current_model != Some(model_name) {
+            println!("{model_name}");
+            current_model = Some(model_name);
+        }
+        println!("  {:<9}  {change}", change.severity);
+    }
+    let count = |severity| {
+        changes
+            .iter()
+            .filter(|(_, change)| change.severity == severity)
+            .count()
+    };
+    println!(
+        "{} breaking, {} dangerous, {} safe changes",
+        count(schema::diff::Severity::Breaking),
+        count(schema::diff::Severity::Dangerous),
+        count(schema::diff::Severity::Safe),
+    );
+    Ok(())
+}
+
+fn write_ts(models_path: &Path, ts_services_dir_path: &Path, operations: &[String]) -> Result<()> {
+    // Operations are given as `service:Operation`, where the service is the model file name.
+    let mut service_operations = HashMap::<&str, Vec<&str>>::new();
+    for operation in operations {
+        let (service, name) = operation
+            .split_once(':')
+            .ok_or_else(|| miette::diagnostic!("expected service:Operation, got {operation:?}"))?;
+        service_operations.entry(service).or_default().push(name);
+    }
+    let mut model_paths = model_paths(models_path)?;
+    if !service_operations.is_empty() {
+        model_paths.retain(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| service_operations.contains_key(stem))
+        });
+        let found = model_paths
+            .iter()
+            .filter_map(|path| path.file_stem()?.to_str())
+            .collect::<BTreeSet<_>>();
+        let mut missing = service_operations
+            .keys()
+            .filter(|service| !found.contains(*service))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            missing.sort();
+            miette::bail!("no models found for the services {missing:?}");
+        }
+    }
+
+    // The endpoint resolvers look regions up in the partitions. Load them before removing
+    // anything, so failing to get them leaves the previously written services in place.
+    let partitions = match load_partitions(models_path)? {
+        Some(partitions) => partitions,
+        None => {
+            println!(
+                "fetching {PARTITIONS_FILE_NAME} to {}",
+                models_path.display()
+            );
+            laws_fetch_models::fetch_partitions(models_path).wrap_err("fetching partitions")?;
+            load_partitions(models_path)?.ok_or_else(|| {
+                miette::diagnostic!("no {PARTITIONS_FILE_NAME} in {models_path:?} after fetching")
+            })?
+        }
+    };
+
     println!("writing ts services to {ts_services_dir_path:?}");
     match fs::remove_dir_all(ts_services_dir_path) {
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
@@ -85,23 +383,17 @@ fn write_ts(models_path: &Path, ts_services_dir_path: &Path) -> Result<()> {
         }
     };
 
-    let results = fs::read_dir(models_path)
-        .into_diagnostic()?
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            if !entry.file_type().ok()?.is_file() {
-                return None;
-            }
-            if entry.file_name().to_string_lossy().ends_with(".json") {
-                Some(entry)
-            } else {
-                None
-            }
-        })
-        .par_bridge()
+    fs::create_dir_all(ts_services_dir_path)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("creating {ts_services_dir_path:?}"))?;
+    let partitions_path = ts_services_dir_path.join("partitions.ts");
+    laws_write_ts::write_partitions(&partitions, &partitions_path)
+        .wrap_err_with(|| format!("writing {partitions_path:?}"))?;
+
+    let results = model_paths
         .into_par_iter()
-        .map(|entry| -> bool {
-            let model = match parse_model(&entry.path()) {
+        .map(|model_path| -> bool {
+            let mut model = match parse_model(&model_path, &schema::ParseOptions::default()) {
                 Ok(model) => model,
                 Err(error) => {
                     render_report(&error);
@@ -109,12 +401,24 @@ fn write_ts(models_path: &Path, ts_services_dir_path: &Path) -> Result<()> {
                 }
             };
 
+            let service = model_path.file_stem().and_then(|stem| stem.to_str());
+            if let Some(names) = service.and_then(|service| service_operations.get(service)) {
+                match operation_ids(&model, names) {
+                    Ok(ids) => model = model.project(&ids),
+                    Err(error) => {
+                        render_report(&error.wrap_err(format!("projecting {model_path:?}")));
+                        return false;
+                    }
+                }
+            }
+
             if let Err(error) = fs::create_dir_all(ts_services_dir_path) {
                 eprintln!("  {error} while creating {ts_services_dir_path:?}");
                 return false;
             }
 
-            let mut ts_service_path = ts_services_dir_path.join(entry.file_name());
+            let mut ts_service_path =
+                ts_services_dir_path.join(model_path.file_name().expect("model paths are files"));
             ts_service_path.set_extension("ts");
             if let Err(error) = laws_write_ts::write_service(&model, &ts_service_path)
                 .wrap_err_with(|| format!("writing {ts_service_path:?}"))
@@ -125,9 +429,6 @@ fn write_ts(models_path: &Path, ts_services_dir_path: &Path) -> Result<()> {
                 }
                 false
             } else {
-                const ANSI_GREEN: &str = "\x1b[32m";
-                const ANSI_RESET: &str = "\x1b[0m";
-                const TICK: &str = "\u{2713}";
                 println!("  {ANSI_GREEN}{TICK}{ANSI_RESET} wrote {ts_service_path:?}");
                 true
             }
@@ -147,6 +448,22 @@ fn write_ts(models_path: &Path, ts_services_dir_path: &Path) -> Result<()> {
     Ok(())
 }
 
+fn operation_ids(model: &schema::Model, names: &[&str]) -> Result<Vec<schema::ShapeId>> {
+    names
+        .iter()
+        .map(|&name| {
+            model
+                .shapes
+                .iter()
+                .find_map(|(id, shape)| match shape {
+                    schema::Shape::Operation(_) if id.name == name => Some(id.clone()),
+                    _ => None,
+                })
+                .ok_or_else(|| miette::miette!("no operation named {name:?}"))
+        })
+        .collect()
+}
+
 fn render_report(report: &miette::Report) {
     // It's way too hard to get miette to render a graphical report
     let handler = miette::GraphicalReportHandler::new();
@@ -158,224 +475,236 @@ fn render_report(report: &miette::Report) {
     }
 }
 
-#[derive(Clone)]
-enum EndpointRuleValue {
-    Unknown,
-    Invalid,
-    Required,
-    ConstUnset,
-    ConstNull,
-    ConstBool(bool),
-    ConstString(String),
-    ConstObject(HashMap<String, EndpointRuleValue>),
-}
-
-impl EndpointRuleValue {
-    fn is_set(&self) -> EndpointRuleValue {
-        match self {
-            EndpointRuleValue::Unknown => EndpointRuleValue::Unknown,
-            EndpointRuleValue::Invalid => EndpointRuleValue::Invalid,
-            EndpointRuleValue::ConstUnset => EndpointRuleValue::ConstBool(false),
-            EndpointRuleValue::Required
-            | EndpointRuleValue::ConstNull
-            | EndpointRuleValue::ConstBool(_)
-            | EndpointRuleValue::ConstString(_)
-            | EndpointRuleValue::ConstObject(_) => EndpointRuleValue::ConstBool(true),
-        }
+fn test_endpoint_rules(
+    model_paths: &[PathBuf],
+    partitions: Option<&endpoint_rules::Partitions>,
+) -> Result<()> {
+    let mut evaluator = endpoint_rules::Evaluator::new();
+    if let Some(partitions) = partitions {
+        evaluator.partitions(partitions);
     }
-}
 
-#[derive(Clone, Default)]
-struct EndpointRulesFilter {
-    values: HashMap<String, EndpointRuleValue>,
-    no_arn_bucket: bool,
-}
+    let (mut passed, mut failed, mut unsupported) = (0, 0, 0);
+    for path in model_paths {
+        let model = match parse_model(path, &schema::ParseOptions::default()) {
+            Ok(model) => model,
+            Err(error) => {
+                render_report(&error);
+                println!("  {ANSI_RED}x{ANSI_RESET} {path:?}: not parsed");
+                failed += 1;
+                continue;
+            }
+        };
+        let services = model.shapes.values().filter_map(|shape| match shape {
+            schema::Shape::Service(service) => Some(service),
+            _ => None,
+        });
+        for service in services {
+            let schema::EndpointRuleSetTrait::V1_0(rule_set) = &service.traits.endpoint_rule_set;
+            let schema::EndpointTestsTrait::V1_0(tests) = &service.traits.endpoint_tests;
+            let optimized = match evaluator.optimize(rule_set) {
+                Ok(optimized) => optimized,
+                Err(error) => {
+                    render_report(&miette::Report::new(error));
+                    println!("  {ANSI_RED}x{ANSI_RESET} {path:?}: rules not optimized");
+                    failed += 1;
+                    continue;
+                }
+            };
+            let mut failures = vec![];
+            let mut service_unsupported = 0;
+            for (index, test_case) in tests.test_cases.iter().enumerate() {
+                // The optimized rules should resolve exactly as the original ones do, whether
+                // or not that passes the test.
+                let resolved = evaluator.resolve(rule_set, &test_case.params);
+                let resolved_optimized = evaluator.resolve(&optimized, &test_case.params);
+                let (resolved_debug, optimized_debug) =
+                    (format!("{resolved:?}"), format!("{resolved_optimized:?}"));
+                let outcome = if resolved_debug != optimized_debug {
+                    EndpointTestOutcome::Failed(format!(
+                        "optimized rules resolve to {optimized_debug} rather than {resolved_debug}"
+                    ))
+                } else {
+                    check_endpoint_test(test_case, resolved)
+                };
+                // Clients calling the operations should end up with the same endpoint.
+                let outcome = match outcome {
+                    EndpointTestOutcome::Passed => test_case
+                        .operation_inputs
+                        .iter()
+                        .flatten()
+                        .map(|input| {
+                            let params = match operation_input_params(&model, rule_set, input) {
+                                Ok(params) => params,
+                                Err(message) => return EndpointTestOutcome::Failed(message),
+                            };
+                            let resolved = evaluator.resolve(rule_set, &params);
+                            match check_endpoint_test(test_case, resolved) {
+                                EndpointTestOutcome::Failed(message) => {
+                                    let name = &input.operation_name;
+                                    EndpointTestOutcome::Failed(format!("{name}: {message}"))
+                                }
+                                outcome => outcome,
+                            }
+                        })
+                        .find(|outcome| !matches!(outcome, EndpointTestOutcome::Passed))
+                        .unwrap_or(EndpointTestOutcome::Passed),
+                    outcome => outcome,
+                };
+                match outcome {
+                    EndpointTestOutcome::Passed => passed += 1,
+                    EndpointTestOutcome::Failed(message) => {
+                        let name = test_case
+                            .documentation
+                            .clone()
+                            .unwrap_or_else(|| format!("test case {index}"));
+                        failures.push(format!("{name}: {message}"));
+                    }
+                    EndpointTestOutcome::Unsupported => service_unsupported += 1,
+                }
+            }
 
-impl EndpointRulesFilter {
-    fn minimal() -> Self {
-        let mut values = HashMap::new();
-        values.insert("Region".to_string(), EndpointRuleValue::Required);
-        values.insert("Bucket".to_string(), EndpointRuleValue::Required);
-        values.insert("UseFIPS".to_string(), EndpointRuleValue::ConstBool(false));
-        values.insert(
-            "UseDualStack".to_string(),
-            EndpointRuleValue::ConstBool(false),
-        );
-        values.insert(
-            "Accelerate".to_string(),
-            EndpointRuleValue::ConstBool(false),
-        );
-        values.insert(
-            "ForcePathStyle".to_string(),
-            EndpointRuleValue::ConstBool(false),
-        );
-        values.insert(
-            "UseArnRegion".to_string(),
-            EndpointRuleValue::ConstBool(false),
-        );
-        values.insert(
-            "UseGlobalEndpoint".to_string(),
-            EndpointRuleValue::ConstBool(false),
-        );
-        values.insert("Endpoint".to_string(), EndpointRuleValue::ConstUnset);
-        let no_arn_bucket = true;
-        Self {
-            values,
-            no_arn_bucket,
+            let service_failed = failures.len();
+            let service_passed = tests.test_cases.len() - service_failed - service_unsupported;
+            if failures.is_empty() {
+                print!("  {ANSI_GREEN}{TICK}{ANSI_RESET} ");
+            } else {
+                print!("  {ANSI_RED}x{ANSI_RESET} ");
+            }
+            println!(
+                "{path:?}: {service_passed} passed, {service_failed} failed, \
+                 {service_unsupported} unsupported"
+            );
+            for failure in failures {
+                println!("      {failure}");
+            }
+            failed += service_failed;
+            unsupported += service_unsupported;
         }
     }
+    println!("{passed} passed, {failed} failed, {unsupported} unsupported");
+    if failed > 0 {
+        miette::bail!("{failed} endpoint test case(s) or model(s) failed");
+    }
+    Ok(())
 }
 
-struct EndpointRuleEvaluation<'rule> {
-    always_false: bool,
-    unknown_conditions: Vec<&'rule schema::EndpointRuleCondition>,
-    child_filter: EndpointRulesFilter,
+enum EndpointTestOutcome {
+    Passed,
+    Failed(String),
+    /// The rules call `aws.partition`, but the partitions weren't fetched with the models.
+    Unsupported,
 }
 
-impl EndpointRulesFilter {
-    fn evaluate_rule<'rule>(
-        &self,
-        rule: &'rule schema::EndpointRuleItem,
-    ) -> EndpointRuleEvaluation<'rule> {
-        let mut result = EndpointRuleEvaluation {
-            always_false: false,
-            unknown_conditions: vec![],
-            child_filter: self.clone(),
-        };
-
-        for condition in &rule.conditions {
-            let value = self.condition_const(condition);
-
-            if let Some(name) = condition.assign.clone() {
-                result.child_filter.values.insert(name, value);
-                // assignments are not conditions
-                continue;
+fn check_endpoint_test(
+    test_case: &schema::EndpointTestCase,
+    resolved: Result<endpoint_rules::ResolvedEndpoint, endpoint_rules::ResolveError>,
+) -> EndpointTestOutcome {
+    let failed = |message: String| EndpointTestOutcome::Failed(message);
+    match (&test_case.expect, resolved) {
+        (_, Err(endpoint_rules::ResolveError::MissingPartitions)) => {
+            EndpointTestOutcome::Unsupported
+        }
+        (
+            schema::EndpointTestExpectation::Error(expected),
+            Err(endpoint_rules::ResolveError::Rule(actual)),
+        ) => {
+            if *expected == actual {
+                EndpointTestOutcome::Passed
+            } else {
+                failed(format!("expected error {expected:?}, got error {actual:?}"))
             }
-
-            match value {
-                EndpointRuleValue::ConstBool(true) => {}
-                EndpointRuleValue::ConstBool(false) => {
-                    result.always_false = true;
-                    return result;
-                }
-                _ => {
-                    result.unknown_conditions.push(condition);
-                }
+        }
+        (_, Err(error)) => failed(error.to_string()),
+        (schema::EndpointTestExpectation::Error(expected), Ok(endpoint)) => failed(format!(
+            "expected error {expected:?}, got endpoint {:?}",
+            endpoint.url
+        )),
+        (schema::EndpointTestExpectation::Endpoint(expected), Ok(endpoint)) => {
+            let expected_properties =
+                serde_json::to_value(&expected.properties).expect("properties should serialize");
+            let expected_headers =
+                serde_json::to_value(&expected.headers).expect("headers should serialize");
+            let properties = serde_json::Value::Object(endpoint.properties);
+            let headers =
+                serde_json::to_value(&endpoint.headers).expect("headers should serialize");
+            if expected.url != endpoint.url {
+                failed(format!(
+                    "expected url {:?}, got {:?}",
+                    expected.url, endpoint.url
+                ))
+            } else if !json_matches(&expected_properties, &properties) {
+                failed(format!(
+                    "expected properties {expected_properties}, got {properties}"
+                ))
+            } else if !json_matches(&expected_headers, &headers) {
+                failed(format!(
+                    "expected headers {expected_headers}, got {headers}"
+                ))
+            } else {
+                EndpointTestOutcome::Passed
             }
         }
-
-        result
     }
+}
 
-    // conditions that are always true can be removed
-    fn exclude_condition(&self, condition: &schema::EndpointRuleCondition) -> bool {
-        matches!(
-            self.condition_const(condition),
-            EndpointRuleValue::ConstBool(true)
-        )
+/// The parameters a client calling the operation resolves its endpoint with: the built-ins and
+/// client parameters it's configured with, overridden by the `smithy.rules#contextParam` members
+/// of the input and then the operation's `smithy.rules#staticContextParams`.
+fn operation_input_params(
+    model: &schema::Model,
+    rule_set: &schema::EndpointRuleSet,
+    input: &schema::EndpointTestOperationInput,
+) -> Result<IndexMap<String, serde_json::Value>, String> {
+    let mut params = IndexMap::new();
+    let built_in_params = input.built_in_params.as_ref();
+    for (name, param) in &rule_set.parameters {
+        let Some(built_in) = &param.built_in else {
+            continue;
+        };
+        let built_in = serde_json::to_value(built_in).expect("built-ins should serialize");
+        if let Some(value) = built_in
+            .as_str()
+            .and_then(|built_in| built_in_params?.get(built_in))
+        {
+            params.insert(name.clone(), value.clone());
+        }
     }
-
-    fn condition_const(&self, condition: &schema::EndpointRuleCondition) -> EndpointRuleValue {
-        match &condition.function {
-            schema::EndpointRuleConditionFn::Not(e) => match self.expr_const(e) {
-                EndpointRuleValue::Unknown => EndpointRuleValue::Unknown,
-                EndpointRuleValue::ConstBool(value) => EndpointRuleValue::ConstBool(!value),
-                _ => EndpointRuleValue::Invalid,
-            },
-            schema::EndpointRuleConditionFn::BooleanEquals(l, r) => {
-                match (self.expr_const(l), self.expr_const(r)) {
-                    (EndpointRuleValue::Unknown, _) => EndpointRuleValue::Unknown,
-                    (_, EndpointRuleValue::Unknown) => EndpointRuleValue::Unknown,
-                    (EndpointRuleValue::ConstBool(l), EndpointRuleValue::ConstBool(r)) => {
-                        EndpointRuleValue::ConstBool(l == r)
-                    }
-                    _ => EndpointRuleValue::Invalid,
-                }
-            }
-            schema::EndpointRuleConditionFn::StringEquals(l, r) => {
-                match (self.expr_const(l), self.expr_const(r)) {
-                    (EndpointRuleValue::Unknown, _) => EndpointRuleValue::Unknown,
-                    (_, EndpointRuleValue::Unknown) => EndpointRuleValue::Unknown,
-                    (EndpointRuleValue::ConstString(l), EndpointRuleValue::ConstString(r)) => {
-                        EndpointRuleValue::ConstBool(l == r)
-                    }
-                    _ => EndpointRuleValue::Invalid,
-                }
-            }
-            schema::EndpointRuleConditionFn::IsSet(e) => self.expr_const(e).is_set(),
-            schema::EndpointRuleConditionFn::Substring(expr, start, end, reverse) => {
-                match self.expr_const(expr) {
-                    EndpointRuleValue::Unknown => EndpointRuleValue::Unknown,
-                    EndpointRuleValue::ConstString(value) => {
-                        if !value.is_ascii() {
-                            return EndpointRuleValue::ConstNull;
-                        }
-                        if *end < *start {
-                            return EndpointRuleValue::Invalid;
-                        }
-
-                        let len = *end - *start;
-                        let start = if *reverse { value.len() - *end } else { *start };
-                        let end = start + len;
-                        if value.len() < end {
-                            EndpointRuleValue::ConstNull
-                        } else {
-                            EndpointRuleValue::ConstString(value[start..end].to_owned())
-                        }
-                    }
-                    _ => EndpointRuleValue::Invalid,
-                }
-            }
-            schema::EndpointRuleConditionFn::AwsParseArn(e) => {
-                if !self.no_arn_bucket {
-                    return EndpointRuleValue::Unknown;
-                }
-                match e {
-                    schema::EndpointRuleExpr::Reference { name } if name == "Bucket" => {
-                        EndpointRuleValue::ConstObject({
-                            let mut map = HashMap::new();
-                            map.insert(
-                                "resourceId[0]".to_string(),
-                                EndpointRuleValue::ConstString("".to_string()),
-                            );
-                            map
-                        })
-                    }
-                    _ => EndpointRuleValue::Unknown,
-                }
-            }
-            schema::EndpointRuleConditionFn::GetAttr(e, name) => match self.expr_const(e) {
-                EndpointRuleValue::Unknown => EndpointRuleValue::Unknown,
-                EndpointRuleValue::ConstObject(map) => {
-                    map.get(name).cloned().unwrap_or(EndpointRuleValue::Unknown)
-                }
-                _ => EndpointRuleValue::Invalid,
-            },
-            schema::EndpointRuleConditionFn::ParseURL(..)
-            | schema::EndpointRuleConditionFn::IsValidHostLabel(..)
-            | schema::EndpointRuleConditionFn::UriEncode(..)
-            | schema::EndpointRuleConditionFn::AwsPartition(..)
-            | schema::EndpointRuleConditionFn::AwsIsVirtualHostableS3Bucket(..) => {
-                EndpointRuleValue::Unknown
+    params.extend(input.client_params.clone().into_iter().flatten());
+
+    let operation = model.shapes.iter().find_map(|(id, shape)| match shape {
+        schema::Shape::Operation(operation) if id.name == input.operation_name => Some(operation),
+        _ => None,
+    });
+    let Some(operation) = operation else {
+        return Err(format!("no operation named {:?}", input.operation_name));
+    };
+    if let Some(schema::Shape::Structure(structure)) = model.shapes.get(&operation.input.target) {
+        for (name, member) in &structure.members {
+            let value = input
+                .operation_params
+                .as_ref()
+                .and_then(|operation_params| operation_params.get(name));
+            if let (Some(context_param), Some(value)) = (&member.traits.context_param, value) {
+                params.insert(context_param.name.clone(), value.clone());
             }
         }
     }
+    for (name, param) in operation.traits.static_context_params.iter().flatten() {
+        params.insert(name.clone(), param.value.clone());
+    }
+    Ok(params)
+}
 
-    fn expr_const(&self, expr: &schema::EndpointRuleExpr) -> EndpointRuleValue {
-        match expr {
-            schema::EndpointRuleExpr::Boolean(s) => EndpointRuleValue::ConstBool(*s),
-            schema::EndpointRuleExpr::String(s) => EndpointRuleValue::ConstString(s.clone()),
-            schema::EndpointRuleExpr::Condition(cond) => self.condition_const(cond),
-            schema::EndpointRuleExpr::Reference { name } => self
-                .values
-                .get(name)
-                .cloned()
-                .unwrap_or(EndpointRuleValue::Unknown),
-        }
+/// Compares expected and resolved values, where a missing expected value means an empty object.
+fn json_matches(expected: &serde_json::Value, actual: &serde_json::Value) -> bool {
+    match expected {
+        serde_json::Value::Null => actual.as_object().is_some_and(|object| object.is_empty()),
+        expected => expected == actual,
     }
 }
 
-fn dump_endpoint_rules(model: &schema::Model, filter: EndpointRulesFilter) -> Result<()> {
+fn service_endpoint_rule_set(model: &schema::Model) -> Result<&schema::EndpointRuleSet> {
     let service = model
         .shapes
         .values()
@@ -385,31 +714,63 @@ fn dump_endpoint_rules(model: &schema::Model, filter: EndpointRulesFilter) -> Re
         })
         .ok_or(miette::diagnostic!("no service shape found in model"))?;
     let schema::EndpointRuleSetTrait::V1_0(rule_set) = &service.traits.endpoint_rule_set;
+    Ok(rule_set)
+}
 
-    filtered_rules(0, &rule_set.rules, filter);
+/// Parses known endpoint parameter values from a JSON file and `NAME=VALUE` arguments, which
+/// take precedence.
+fn known_endpoint_params(
+    rule_set: &schema::EndpointRuleSet,
+    params: &[String],
+    params_file: Option<&Path>,
+) -> Result<IndexMap<String, serde_json::Value>> {
+    let mut known = match params_file {
+        Some(path) => {
+            let source = fs::read_to_string(path)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("reading {path:?}"))?;
+            serde_json::from_str::<IndexMap<String, serde_json::Value>>(&source)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("parsing {path:?}"))?
+        }
+        None => IndexMap::new(),
+    };
+    for param in params {
+        let Some((name, value)) = param.split_once('=') else {
+            miette::bail!("expected a parameter as NAME=VALUE, got {param:?}");
+        };
+        let Some(definition) = rule_set.parameters.get(name) else {
+            miette::bail!("{name} is not a parameter of the endpoint rule set");
+        };
+        let value = match (&definition.type_, value) {
+            (_, "") => serde_json::Value::Null,
+            (schema::EndpointRuleSetParamType::Boolean, "true") => serde_json::Value::Bool(true),
+            (schema::EndpointRuleSetParamType::Boolean, "false") => serde_json::Value::Bool(false),
+            (schema::EndpointRuleSetParamType::Boolean, value) => {
+                miette::bail!("{name} should be true or false, got {value:?}");
+            }
+            (schema::EndpointRuleSetParamType::String, value) => {
+                serde_json::Value::String(value.to_string())
+            }
+        };
+        known.insert(name.to_string(), value);
+    }
+    Ok(known)
+}
 
-    fn filtered_rules(
-        indent: usize,
-        rules: &[schema::EndpointRuleItem],
-        filter: EndpointRulesFilter,
-    ) {
+fn dump_endpoint_rules(rule_set: &schema::EndpointRuleSet) {
+    print_rules(0, &rule_set.rules);
+
+    fn print_rules(indent: usize, rules: &[schema::EndpointRuleItem]) {
         for rule in rules {
-            let evaluation = filter.evaluate_rule(rule);
-            if evaluation.always_false {
-                continue;
-            }
-            print_rule_item(indent + 2, rule, &evaluation);
+            print_rule_item(indent + 2, rule);
         }
     }
 
-    fn print_rule_item(
-        indent: usize,
-        item: &schema::EndpointRuleItem,
-        evaluation: &EndpointRuleEvaluation,
-    ) {
+    fn print_rule_item(indent: usize, item: &schema::EndpointRuleItem) {
         let mut has_condition = false;
         print!("{:indent$}", "");
-        for condition in &evaluation.unknown_conditions {
+        for condition in &item.conditions {
             if !has_condition {
                 has_condition = true;
                 print!("if ");
@@ -428,7 +789,7 @@ fn dump_endpoint_rules(model: &schema::Model, filter: EndpointRulesFilter) -> Re
             }
             schema::EndpointRule::Tree { rules } => {
                 println!("{{");
-                filtered_rules(indent + 2, rules, evaluation.child_filter.clone());
+                print_rules(indent + 2, rules);
                 print!("{:indent$}}}", "");
             }
             schema::EndpointRule::Endpoint { endpoint } => {
@@ -562,13 +923,14 @@ fn dump_endpoint_rules(model: &schema::Model, filter: EndpointRulesFilter) -> Re
                 print_expr(indent, expr);
                 print!(")");
             }
-            schema::EndpointRuleConditionFn::AwsIsVirtualHostableS3Bucket(expr, foo) => {
+            schema::EndpointRuleConditionFn::AwsIsVirtualHostableS3Bucket(
+                expr,
+                allow_subdomains,
+            ) => {
                 print!("aws_is_virtual_hostable_s3_bucket(");
                 print_expr(indent, expr);
-                print!(", {foo})");
+                print!(", {allow_subdomains})");
             }
         }
     }
-
-    Ok(())
 }
@@ -0,0 +1,328 @@
+//! Amazon Resource Names, and the `aws.api#arn` templates resources describe theirs with.
+//!
+//! See <https://smithy.io/2.0/aws/aws-core.html#aws-api-arn-trait>.
+
+use std::fmt;
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+
+use crate::ArnTrait;
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum ArnError {
+    #[error("invalid ARN {0:?}")]
+    #[diagnostic(
+        code(laws_schema::arn::invalid),
+        help("ARNs look like `arn:partition:service:region:account-id:resource`")
+    )]
+    Invalid(String),
+    #[error("invalid ARN template {template:?}: {message}")]
+    #[diagnostic(code(laws_schema::arn::template))]
+    Template { template: String, message: String },
+    #[error("no value for {label:?} in ARN template {template:?}")]
+    #[diagnostic(code(laws_schema::arn::missing_identifier))]
+    MissingIdentifier { template: String, label: String },
+}
+
+/// A parsed ARN, e.g. `arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Arn {
+    pub partition: String,
+    pub service: String,
+    /// Empty for global resources.
+    pub region: String,
+    /// Empty for resources not owned by an account, such as S3 buckets.
+    pub account_id: String,
+    pub resource: String,
+}
+
+impl Arn {
+    /// The resource split on `:` and `/`, as `aws.parseArn` returns it.
+    pub fn resource_id(&self) -> Vec<&str> {
+        self.resource.split([':', '/']).collect()
+    }
+}
+
+impl FromStr for Arn {
+    type Err = ArnError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parts = value.splitn(6, ':').collect::<Vec<_>>();
+        let ["arn", partition, service, region, account_id, resource] = parts[..] else {
+            return Err(ArnError::Invalid(value.to_string()));
+        };
+        if partition.is_empty() || service.is_empty() || resource.is_empty() {
+            return Err(ArnError::Invalid(value.to_string()));
+        }
+        Ok(Arn {
+            partition: partition.to_string(),
+            service: service.to_string(),
+            region: region.to_string(),
+            account_id: account_id.to_string(),
+            resource: resource.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Arn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "arn:{}:{}:{}:{}:{}",
+            self.partition, self.service, self.region, self.account_id, self.resource
+        )
+    }
+}
+
+/// The parts of an ARN that come from where a resource is rather than its identifiers.
+#[derive(Debug, Clone, Copy)]
+pub struct ArnContext<'a> {
+    pub partition: &'a str,
+    /// The service's `arnNamespace`, e.g. `s3`.
+    pub service: &'a str,
+    pub region: &'a str,
+    pub account_id: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArnTemplateSegment {
+    Literal(String),
+    /// `{Name}`, replaced by the resource identifier `Name`.
+    Label(String),
+}
+
+/// A parsed `aws.api#arn` template, which builds a resource's ARN from its identifiers and
+/// extracts the identifiers from an ARN.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArnTemplate {
+    pub template: String,
+    /// The template is the whole ARN, rather than the resource part of one.
+    pub absolute: bool,
+    pub no_region: bool,
+    pub no_account: bool,
+    pub segments: Vec<ArnTemplateSegment>,
+}
+
+impl ArnTemplate {
+    pub fn new(arn: &ArnTrait) -> Result<Self, ArnError> {
+        let template = &arn.template;
+        let error = |message: &str| ArnError::Template {
+            template: template.clone(),
+            message: message.to_string(),
+        };
+        if !arn.absolute && template.starts_with('/') {
+            return Err(error("relative templates cannot start with `/`"));
+        }
+
+        let mut segments = vec![];
+        let mut rest = template.as_str();
+        while !rest.is_empty() {
+            if let Some(label) = rest.strip_prefix('{') {
+                let end = label.find('}').ok_or_else(|| error("unclosed `{`"))?;
+                let label = &label[..end];
+                if label.is_empty() || label.contains('{') {
+                    return Err(error("labels must be a name inside `{}`"));
+                }
+                // Without something between them, there's no telling where one ends.
+                if let Some(ArnTemplateSegment::Label(_)) = segments.last() {
+                    return Err(error("labels must be separated by text"));
+                }
+                segments.push(ArnTemplateSegment::Label(label.to_string()));
+                rest = &rest[end + 2..];
+            } else {
+                let end = rest.find('{').unwrap_or(rest.len());
+                if rest[..end].contains('}') {
+                    return Err(error("unopened `}`"));
+                }
+                segments.push(ArnTemplateSegment::Literal(rest[..end].to_string()));
+                rest = &rest[end..];
+            }
+        }
+
+        Ok(ArnTemplate {
+            template: template.clone(),
+            absolute: arn.absolute,
+            no_region: arn.no_region,
+            no_account: arn.no_account,
+            segments,
+        })
+    }
+
+    /// The names of the identifiers the template uses.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|segment| match segment {
+            ArnTemplateSegment::Label(label) => Some(label.as_str()),
+            ArnTemplateSegment::Literal(_) => None,
+        })
+    }
+
+    /// Builds the ARN of the resource with these identifiers. The context is unused by
+    /// absolute templates, and the region and account are left out if the template says so.
+    pub fn build(
+        &self,
+        context: &ArnContext,
+        identifiers: &IndexMap<String, String>,
+    ) -> Result<String, ArnError> {
+        let mut expanded = String::new();
+        for segment in &self.segments {
+            match segment {
+                ArnTemplateSegment::Literal(literal) => expanded.push_str(literal),
+                ArnTemplateSegment::Label(label) => {
+                    let Some(value) = identifiers.get(label) else {
+                        return Err(ArnError::MissingIdentifier {
+                            template: self.template.clone(),
+                            label: label.clone(),
+                        });
+                    };
+                    expanded.push_str(value);
+                }
+            }
+        }
+        if self.absolute {
+            return Ok(expanded);
+        }
+        let region = if self.no_region { "" } else { context.region };
+        let account_id = if self.no_account {
+            ""
+        } else {
+            context.account_id
+        };
+        let arn = Arn {
+            partition: context.partition.to_string(),
+            service: context.service.to_string(),
+            region: region.to_string(),
+            account_id: account_id.to_string(),
+            resource: expanded,
+        };
+        Ok(arn.to_string())
+    }
+
+    /// Extracts the identifiers from an ARN of this template's resource, or returns `None` if it
+    /// doesn't match. Each label takes the shortest value up to the text following it, so only
+    /// the last can contain that text.
+    pub fn extract(&self, arn: &str) -> Option<IndexMap<String, String>> {
+        let mut rest = if self.absolute {
+            arn
+        } else {
+            let parsed = arn.parse::<Arn>().ok()?;
+            if parsed.region.is_empty() != self.no_region
+                || parsed.account_id.is_empty() != self.no_account
+            {
+                return None;
+            }
+            // The resource is the rest after the fifth `:`.
+            arn.splitn(6, ':').last()?
+        };
+
+        let mut identifiers = IndexMap::new();
+        let mut segments = self.segments.iter().peekable();
+        while let Some(segment) = segments.next() {
+            match segment {
+                ArnTemplateSegment::Literal(literal) => {
+                    rest = rest.strip_prefix(literal.as_str())?
+                }
+                ArnTemplateSegment::Label(label) => {
+                    let end = match segments.peek() {
+                        Some(ArnTemplateSegment::Literal(literal)) => {
+                            rest.find(literal.as_str())?
+                        }
+                        _ => rest.len(),
+                    };
+                    if end == 0 {
+                        return None;
+                    }
+                    identifiers.insert(label.clone(), rest[..end].to_string());
+                    rest = &rest[end..];
+                }
+            }
+        }
+        rest.is_empty().then_some(identifiers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_format() {
+        let arn = "arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap:object"
+            .parse::<Arn>()
+            .unwrap();
+        assert_eq!(arn.partition, "aws");
+        assert_eq!(arn.account_id, "123456789012");
+        assert_eq!(arn.resource_id(), ["accesspoint", "my-ap", "object"]);
+        assert_eq!(
+            arn.to_string(),
+            "arn:aws:s3:us-west-2:123456789012:accesspoint/my-ap:object"
+        );
+
+        assert!("arn:aws:s3:::".parse::<Arn>().is_err());
+        assert!("arn:aws:s3::".parse::<Arn>().is_err());
+        assert!("urn:aws:s3:::bucket".parse::<Arn>().is_err());
+    }
+
+    #[test]
+    fn templates() {
+        let template = |template: &str, absolute, no_region, no_account| {
+            ArnTemplate::new(&ArnTrait {
+                template: template.to_string(),
+                absolute,
+                no_account,
+                no_region,
+            })
+        };
+        let identifiers = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<IndexMap<_, _>>()
+        };
+        let context = ArnContext {
+            partition: "aws",
+            service: "s3",
+            region: "us-east-1",
+            account_id: "123456789012",
+        };
+
+        let object = template("{Bucket}/{Key}", false, true, true).unwrap();
+        assert_eq!(object.labels().collect::<Vec<_>>(), ["Bucket", "Key"]);
+        let ids = identifiers(&[("Bucket", "my-bucket"), ("Key", "a/b.txt")]);
+        let arn = object.build(&context, &ids).unwrap();
+        assert_eq!(arn, "arn:aws:s3:::my-bucket/a/b.txt");
+        assert_eq!(object.extract(&arn), Some(ids));
+        assert_eq!(object.extract("arn:aws:s3:us-east-1::my-bucket/key"), None);
+        assert_eq!(object.extract("arn:aws:s3:::my-bucket"), None);
+
+        let queue = template("queue/{QueueName}", false, false, false).unwrap();
+        let ids = identifiers(&[("QueueName", "jobs")]);
+        let arn = queue.build(&context, &ids).unwrap();
+        assert_eq!(arn, "arn:aws:s3:us-east-1:123456789012:queue/jobs");
+        assert_eq!(queue.extract(&arn), Some(ids));
+        assert_eq!(
+            queue.extract("arn:aws:s3:us-east-1:123456789012:topic/jobs"),
+            None
+        );
+        let error = queue.build(&context, &IndexMap::new()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "no value for \"QueueName\" in ARN template \"queue/{QueueName}\""
+        );
+
+        let absolute = template("{Arn}", true, false, false).unwrap();
+        let ids = identifiers(&[("Arn", "arn:aws:iam::123456789012:role/admin")]);
+        assert_eq!(
+            absolute.build(&context, &ids).unwrap(),
+            "arn:aws:iam::123456789012:role/admin"
+        );
+        assert_eq!(
+            absolute.extract("arn:aws:iam::123456789012:role/admin"),
+            Some(ids)
+        );
+
+        for invalid in ["/{Name}", "{Name", "{}", "{A}{B}", "a}b"] {
+            assert!(template(invalid, false, false, false).is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Assembles a single model from multiple model files, as Smithy does when building a project.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::{
+    parse_idl, parse_model_with_options, validate, IdlError, Metadata, MixinError, Model,
+    ParseError, ParseOptions, ParseWarning, ShapeId, SmithyVersion, ValidationError,
+};
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum AssemblyError {
+    #[error("reading {path:?}")]
+    #[diagnostic(code(laws_schema::assembler::io))]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Idl(#[from] IdlError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Mixin(#[from] MixinError),
+    #[error("shape {shape_id} is defined differently in {first} and {second}")]
+    #[diagnostic(code(laws_schema::assembler::shape_conflict))]
+    ShapeConflict {
+        shape_id: ShapeId,
+        first: String,
+        second: String,
+    },
+    #[error("metadata {key:?} is defined differently in {first} and {second}")]
+    #[diagnostic(code(laws_schema::assembler::metadata_conflict))]
+    MetadataConflict {
+        key: String,
+        first: String,
+        second: String,
+    },
+    #[error("assembled model has {} problem(s)", errors.len())]
+    #[diagnostic(code(laws_schema::assembler::invalid))]
+    Invalid {
+        #[related]
+        errors: Vec<ValidationError>,
+    },
+}
+
+/// Collects model files to merge into one validated [`Model`].
+///
+/// Shapes defined in more than one file must be identical. Metadata arrays, such as
+/// `suppressions`, are concatenated, and other metadata values must be identical.
+pub struct ModelAssembler {
+    options: ParseOptions,
+    sources: Vec<(String, String)>,
+}
+
+impl Default for ModelAssembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModelAssembler {
+    pub fn new() -> Self {
+        Self {
+            options: ParseOptions { strict: true },
+            sources: vec![],
+        }
+    }
+
+    /// Sets the options for parsing JSON AST files, which are strict by default.
+    pub fn options(&mut self, options: ParseOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Adds a model file, or every `.smithy` file and JSON AST file under a directory. JSON files
+    /// without a top-level `smithy` version, such as AWS partitions, are skipped.
+    pub fn add_path(&mut self, path: impl AsRef<Path>) -> Result<&mut Self, AssemblyError> {
+        let path = path.as_ref();
+        let io_error = |source| AssemblyError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        if !fs::metadata(path).map_err(io_error)?.is_dir() {
+            let source = fs::read_to_string(path).map_err(io_error)?;
+            return Ok(self.add_source(path.display().to_string(), source));
+        }
+
+        let mut entries = fs::read_dir(path)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(io_error)?;
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() {
+                self.add_path(&entry)?;
+                continue;
+            }
+            let Some(extension) = entry.extension() else {
+                continue;
+            };
+            if extension != "json" && extension != "smithy" {
+                continue;
+            }
+            let source = fs::read_to_string(&entry).map_err(|source| AssemblyError::Io {
+                path: entry.clone(),
+                source,
+            })?;
+            if extension == "json" && !is_json_ast(&source) {
+                continue;
+            }
+            self.add_source(entry.display().to_string(), source);
+        }
+        Ok(self)
+    }
+
+    /// Adds a model from memory. The name is used in errors, and names ending in `.smithy` are
+    /// parsed as IDL rather than JSON AST.
+    pub fn add_source(&mut self, name: impl Into<String>, source: impl Into<String>) -> &mut Self {
+        self.sources.push((name.into(), source.into()));
+        self
+    }
+
+    /// Parses and merges all the added models, flattens their mixins, then validates the result.
+    pub fn assemble(&self) -> Result<(Model, Vec<ParseWarning>), AssemblyError> {
+        let mut assembled = Model {
+            smithy: SmithyVersion::_2_0,
+            metadata: Metadata::default(),
+            shapes: IndexMap::new(),
+        };
+        let mut warnings = vec![];
+        let mut shape_sources = HashMap::new();
+        let mut metadata_sources = HashMap::new();
+
+        for (name, source) in &self.sources {
+            let model = if name.ends_with(".smithy") {
+                parse_idl(name, source)?
+            } else {
+                let (model, model_warnings) =
+                    parse_model_with_options(name, source, &self.options)?;
+                warnings.extend(model_warnings);
+                model
+            };
+
+            for (shape_id, shape) in model.shapes {
+                match assembled.shapes.get(&shape_id) {
+                    None => {
+                        shape_sources.insert(shape_id.clone(), name);
+                        assembled.shapes.insert(shape_id, shape);
+                    }
+                    Some(existing) if *existing == shape => {}
+                    Some(_) => {
+                        return Err(AssemblyError::ShapeConflict {
+                            first: shape_sources[&shape_id].clone(),
+                            second: name.clone(),
+                            shape_id,
+                        });
+                    }
+                }
+            }
+
+            assembled
+                .metadata
+                .suppressions
+                .extend(model.metadata.suppressions);
+            for (key, value) in model.metadata.other {
+                let Some(existing) = assembled.metadata.other.get_mut(&key) else {
+                    metadata_sources.insert(key.clone(), name);
+                    assembled.metadata.other.insert(key, value);
+                    continue;
+                };
+                match (existing, value) {
+                    (Value::Array(existing), Value::Array(values)) => existing.extend(values),
+                    (existing, value) if *existing == value => {}
+                    _ => {
+                        return Err(AssemblyError::MetadataConflict {
+                            first: metadata_sources[&key].clone(),
+                            second: name.clone(),
+                            key,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Mixins can be defined in any of the files, so they're only flattened once all are merged.
+        let assembled = assembled.flatten_mixins()?;
+        let errors = validate(&assembled);
+        if !errors.is_empty() {
+            return Err(AssemblyError::Invalid { errors });
+        }
+        Ok((assembled, warnings))
+    }
+}
+
+/// Whether a JSON file is a model, rather than some other JSON. Files that aren't valid JSON
+/// are treated as models so that their syntax errors are reported.
+fn is_json_ast(source: &str) -> bool {
+    #[derive(serde::Deserialize)]
+    struct Version {
+        smithy: Option<serde::de::IgnoredAny>,
+    }
+    serde_json::from_str::<Version>(source).map_or(true, |version| version.smithy.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn thing(name: &str) -> serde_json::Value {
+        json!({
+            "smithy": "2.0",
+            "metadata": {
+                "suppressions": [{ "id": name, "namespace": "*" }],
+                "tags": [name],
+            },
+            "shapes": {
+                "example#Name": { "type": "string" },
+                format!("example#{name}"): {
+                    "type": "structure",
+                    "members": { "Name": { "target": "example#Name" } },
+                },
+            },
+        })
+    }
+
+    #[test]
+    fn merges_models() {
+        let (model, warnings) = ModelAssembler::new()
+            .add_source("a.json", thing("A").to_string())
+            .add_source("b.json", thing("B").to_string())
+            .add_source(
+                "c.smithy",
+                "$version: \"2\"\nnamespace example\nstructure C { name: Name }\n",
+            )
+            .assemble()
+            .unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(
+            model
+                .shapes
+                .keys()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["example#Name", "example#A", "example#B", "example#C"]
+        );
+        assert_eq!(
+            model
+                .metadata
+                .suppressions
+                .iter()
+                .map(|suppression| suppression.id.as_str())
+                .collect::<Vec<_>>(),
+            ["A", "B"]
+        );
+        assert_eq!(model.metadata.other["tags"], json!(["A", "B"]));
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut changed = thing("B");
+        changed["shapes"]["example#Name"]["type"] = json!("blob");
+        let error = ModelAssembler::new()
+            .add_source("a.json", thing("A").to_string())
+            .add_source("b.json", changed.to_string())
+            .assemble()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "shape example#Name is defined differently in a.json and b.json"
+        );
+
+        let mut changed = thing("B");
+        changed["metadata"]["tags"] = json!("B");
+        let error = ModelAssembler::new()
+            .add_source("a.json", thing("A").to_string())
+            .add_source("b.json", changed.to_string())
+            .assemble()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"metadata "tags" is defined differently in a.json and b.json"#
+        );
+    }
+
+    #[test]
+    fn validates() {
+        let mut missing = thing("A");
+        missing["shapes"]
+            .as_object_mut()
+            .unwrap()
+            .remove("example#Name");
+        let error = ModelAssembler::new()
+            .add_source("a.json", missing.to_string())
+            .assemble()
+            .unwrap_err();
+        let AssemblyError::Invalid { errors } = error else {
+            panic!("expected the model to be invalid, got {error:?}");
+        };
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn loads_directories() {
+        let dir = std::env::temp_dir().join(format!("laws-assembler-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("a.json"), thing("A").to_string()).unwrap();
+        fs::write(dir.join("nested/b.json"), thing("B").to_string()).unwrap();
+        fs::write(dir.join("README.md"), "not a model").unwrap();
+        fs::write(dir.join("partitions.json"), r#"{ "version": "1.1" }"#).unwrap();
+
+        let result = ModelAssembler::new()
+            .add_path(&dir)
+            .and_then(|assembler| assembler.assemble());
+        fs::remove_dir_all(&dir).unwrap();
+        let (model, _) = result.unwrap();
+        assert_eq!(model.shapes.len(), 3);
+    }
+}
@@ -0,0 +1,892 @@
+//! Differences between two versions of a model, classified by how they affect clients.
+
+use std::{collections::HashSet, fmt};
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::{Model, Shape, ShapeGraph, ShapeId, ShapeRef};
+
+/// How a change can affect existing clients, from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// Existing clients are unaffected.
+    Safe,
+    /// Existing clients keep working, but may behave differently, e.g. a member they read is no
+    /// longer always set.
+    Dangerous,
+    /// Existing clients can stop compiling or working.
+    Breaking,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Severity::Safe => "safe",
+            Severity::Dangerous => "dangerous",
+            Severity::Breaking => "breaking",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Change {
+    pub severity: Severity,
+    #[serde(rename = "shape")]
+    pub shape_id: ShapeId,
+    /// The member changed, if the change isn't to the shape itself.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    #[serde(flatten)]
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ChangeKind {
+    AddedOperation,
+    RemovedOperation,
+    AddedShape,
+    RemovedShape,
+    AddedMember,
+    RemovedMember,
+    BecameRequired,
+    BecameOptional,
+    AddedEnumValue {
+        value: String,
+    },
+    RemovedEnumValue {
+        value: String,
+    },
+    ChangedType {
+        old: String,
+        new: String,
+    },
+    ChangedTarget {
+        old: ShapeId,
+        new: ShapeId,
+    },
+    /// A shape was bound to a property of an operation, service or resource, such as an error
+    /// of an operation or an identifier of a resource.
+    AddedBinding {
+        property: String,
+        target: ShapeId,
+    },
+    RemovedBinding {
+        property: String,
+        target: ShapeId,
+    },
+    ChangedBinding {
+        property: String,
+        old: ShapeId,
+        new: ShapeId,
+    },
+    AddedTrait {
+        trait_id: String,
+    },
+    RemovedTrait {
+        trait_id: String,
+    },
+    ChangedTrait {
+        trait_id: String,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = match &self.member {
+            Some(member) => format!("{}${member}", self.shape_id),
+            None => self.shape_id.to_string(),
+        };
+        match &self.kind {
+            ChangeKind::AddedOperation => write!(f, "added operation {location}"),
+            ChangeKind::RemovedOperation => write!(f, "removed operation {location}"),
+            ChangeKind::AddedShape => write!(f, "added shape {location}"),
+            ChangeKind::RemovedShape => write!(f, "removed shape {location}"),
+            ChangeKind::AddedMember => write!(f, "added member {location}"),
+            ChangeKind::RemovedMember => write!(f, "removed member {location}"),
+            ChangeKind::BecameRequired => write!(f, "{location} became required"),
+            ChangeKind::BecameOptional => write!(f, "{location} became optional"),
+            ChangeKind::AddedEnumValue { value } => {
+                write!(f, "added enum value {value:?} to {location}")
+            }
+            ChangeKind::RemovedEnumValue { value } => {
+                write!(f, "removed enum value {value:?} from {location}")
+            }
+            ChangeKind::ChangedType { old, new } => {
+                write!(f, "{location} changed type from {old} to {new}")
+            }
+            ChangeKind::ChangedTarget { old, new } => {
+                write!(f, "{location} changed target from {old} to {new}")
+            }
+            ChangeKind::AddedBinding { property, target } => {
+                write!(f, "added {target} to {property} of {location}")
+            }
+            ChangeKind::RemovedBinding { property, target } => {
+                write!(f, "removed {target} from {property} of {location}")
+            }
+            ChangeKind::ChangedBinding { property, old, new } => {
+                write!(f, "{property} of {location} changed from {old} to {new}")
+            }
+            ChangeKind::AddedTrait { trait_id } => write!(f, "added {trait_id} to {location}"),
+            ChangeKind::RemovedTrait { trait_id } => {
+                write!(f, "removed {trait_id} from {location}")
+            }
+            ChangeKind::ChangedTrait { trait_id, .. } => {
+                write!(f, "changed {trait_id} on {location}")
+            }
+        }
+    }
+}
+
+const REQUIRED: &str = "smithy.api#required";
+const DEFAULT: &str = "smithy.api#default";
+const ENUM: &str = "smithy.api#enum";
+const ENUM_VALUE: &str = "smithy.api#enumValue";
+
+/// How changing a trait affects clients. Traits that change what is sent or received on the wire
+/// break clients, traits that constrain values may reject requests that used to work, and
+/// everything else, such as documentation, is safe.
+fn trait_severity(trait_id: &str) -> Severity {
+    match trait_id {
+        "smithy.api#error"
+        | "smithy.api#enumValue"
+        | "smithy.api#eventPayload"
+        | "smithy.api#hostLabel"
+        | "smithy.api#http"
+        | "smithy.api#httpError"
+        | "smithy.api#httpHeader"
+        | "smithy.api#httpLabel"
+        | "smithy.api#httpPayload"
+        | "smithy.api#httpPrefixHeaders"
+        | "smithy.api#httpQuery"
+        | "smithy.api#httpQueryParams"
+        | "smithy.api#httpResponseCode"
+        | "smithy.api#jsonName"
+        | "smithy.api#streaming"
+        | "smithy.api#timestampFormat"
+        | "smithy.api#xmlAttribute"
+        | "smithy.api#xmlFlattened"
+        | "smithy.api#xmlName"
+        | "smithy.api#xmlNamespace"
+        | "aws.protocols#awsQueryError" => Severity::Breaking,
+        "smithy.api#default"
+        | "smithy.api#endpoint"
+        | "smithy.api#idempotencyToken"
+        | "smithy.api#length"
+        | "smithy.api#paginated"
+        | "smithy.api#pattern"
+        | "smithy.api#range"
+        | "smithy.api#sparse"
+        | "smithy.api#uniqueItems" => Severity::Dangerous,
+        _ => Severity::Safe,
+    }
+}
+
+/// Lists the changes from the old model to the new one.
+pub fn diff(old: &Model, new: &Model) -> Vec<Change> {
+    let mut usage = Usage::default();
+    usage.add(old);
+    usage.add(new);
+    let mut changes = Changes {
+        changes: vec![],
+        usage,
+    };
+    for (id, old_shape) in &old.shapes {
+        match new.shapes.get(id) {
+            Some(new_shape) => changes.shape(id, old_shape, new_shape),
+            None => {
+                let kind = match old_shape {
+                    Shape::Operation(_) => ChangeKind::RemovedOperation,
+                    _ => ChangeKind::RemovedShape,
+                };
+                changes.push(Severity::Breaking, id, None, kind);
+            }
+        }
+    }
+    for (id, new_shape) in &new.shapes {
+        if !old.shapes.contains_key(id) {
+            let kind = match new_shape {
+                Shape::Operation(_) => ChangeKind::AddedOperation,
+                _ => ChangeKind::AddedShape,
+            };
+            changes.push(Severity::Safe, id, None, kind);
+        }
+    }
+    changes.changes
+}
+
+/// The shapes that are part of the input or the output of an operation, in either model. Errors
+/// count as output.
+#[derive(Default)]
+struct Usage {
+    input: HashSet<ShapeId>,
+    output: HashSet<ShapeId>,
+}
+
+impl Usage {
+    fn add(&mut self, model: &Model) {
+        let graph = ShapeGraph::new(model);
+        let add = |shapes: &mut HashSet<ShapeId>, shape_ref: &ShapeRef| {
+            shapes.extend(graph.closure(&shape_ref.target).into_iter().cloned());
+        };
+        for shape in model.shapes.values() {
+            match shape {
+                Shape::Operation(operation) => {
+                    add(&mut self.input, &operation.input);
+                    add(&mut self.output, &operation.output);
+                    for error in &operation.errors {
+                        add(&mut self.output, error);
+                    }
+                }
+                Shape::Service(service) => {
+                    for error in &service.errors {
+                        add(&mut self.output, error);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether the shape is sent by clients and whether it is received by them. Shapes no
+    /// operation uses could be either.
+    fn of(&self, id: &ShapeId) -> (bool, bool) {
+        match (self.input.contains(id), self.output.contains(id)) {
+            (false, false) => (true, true),
+            usage => usage,
+        }
+    }
+}
+
+struct Changes {
+    changes: Vec<Change>,
+    usage: Usage,
+}
+
+impl Changes {
+    fn push(&mut self, severity: Severity, id: &ShapeId, member: Option<&str>, kind: ChangeKind) {
+        self.changes.push(Change {
+            severity,
+            shape_id: id.clone(),
+            member: member.map(str::to_string),
+            kind,
+        });
+    }
+
+    fn shape(&mut self, id: &ShapeId, old: &Shape, new: &Shape) {
+        if old.type_name() != new.type_name() {
+            // Enums are strings with a fixed set of values, so clients still send the same thing.
+            let severity = match (old.type_name(), new.type_name()) {
+                ("string", "enum") | ("enum", "string") => Severity::Dangerous,
+                _ => Severity::Breaking,
+            };
+            let kind = ChangeKind::ChangedType {
+                old: old.type_name().to_string(),
+                new: new.type_name().to_string(),
+            };
+            self.push(severity, id, None, kind);
+            return;
+        }
+        self.bindings(id, old, new);
+
+        // Traits and members are compared in their JSON AST form, so every trait is covered,
+        // including unknown traits.
+        let old = serde_json::to_value(old).expect("shapes should serialize");
+        let new = serde_json::to_value(new).expect("shapes should serialize");
+        let old_traits = traits(&old);
+        let new_traits = traits(&new);
+        self.traits(id, None, &old_traits, &new_traits);
+        self.string_enum(id, &old_traits, &new_traits);
+
+        let is_enum = old["type"] == "enum";
+        let is_union = old["type"] == "union";
+        let (is_input, _) = self.usage.of(id);
+        let old_members = members(&old);
+        let new_members = members(&new);
+        for (&name, old_member) in &old_members {
+            let Some(new_member) = new_members.get(name) else {
+                let kind = if is_enum {
+                    ChangeKind::RemovedEnumValue {
+                        value: enum_value(name, old_member),
+                    }
+                } else {
+                    ChangeKind::RemovedMember
+                };
+                self.push(Severity::Breaking, id, Some(name), kind);
+                continue;
+            };
+            self.member(id, name, old_member, new_member);
+        }
+        for (&name, new_member) in &new_members {
+            if old_members.contains_key(name) {
+                continue;
+            }
+            let traits = traits(new_member);
+            let (severity, kind) = if is_enum {
+                let value = enum_value(name, new_member);
+                (Severity::Safe, ChangeKind::AddedEnumValue { value })
+            } else if is_union {
+                // Clients handling every variant won't handle this one.
+                (Severity::Dangerous, ChangeKind::AddedMember)
+            } else if is_input && traits.contains_key(REQUIRED) && !traits.contains_key(DEFAULT) {
+                // Clients don't send it yet.
+                (Severity::Breaking, ChangeKind::AddedMember)
+            } else {
+                (Severity::Safe, ChangeKind::AddedMember)
+            };
+            self.push(severity, id, Some(name), kind);
+        }
+    }
+
+    fn member(&mut self, id: &ShapeId, name: &str, old: &Value, new: &Value) {
+        if old["target"] != new["target"] {
+            if let (Ok(old), Ok(new)) = (
+                serde_json::from_value(old["target"].clone()),
+                serde_json::from_value(new["target"].clone()),
+            ) {
+                let kind = ChangeKind::ChangedTarget { old, new };
+                self.push(Severity::Breaking, id, Some(name), kind);
+            }
+        }
+
+        // Clients must start sending members that become required, while members that become
+        // optional may no longer be sent to them.
+        let (is_input, is_output) = self.usage.of(id);
+        let old_traits = traits(old);
+        let new_traits = traits(new);
+        match (
+            old_traits.contains_key(REQUIRED),
+            new_traits.contains_key(REQUIRED),
+        ) {
+            (false, true) => {
+                let severity = if is_input {
+                    Severity::Breaking
+                } else {
+                    Severity::Safe
+                };
+                self.push(severity, id, Some(name), ChangeKind::BecameRequired);
+            }
+            (true, false) => {
+                let severity = if is_output {
+                    Severity::Dangerous
+                } else {
+                    Severity::Safe
+                };
+                self.push(severity, id, Some(name), ChangeKind::BecameOptional);
+            }
+            _ => {}
+        }
+        self.traits(id, Some(name), &old_traits, &new_traits);
+    }
+
+    /// Compares the shapes bound to operations, services and resources.
+    fn bindings(&mut self, id: &ShapeId, old: &Shape, new: &Shape) {
+        use Severity::{Breaking, Dangerous, Safe};
+        match (old, new) {
+            (Shape::Operation(old), Shape::Operation(new)) => {
+                self.binding(id, "input", Some(&old.input), Some(&new.input), Breaking);
+                self.binding(id, "output", Some(&old.output), Some(&new.output), Breaking);
+                // Clients may not handle new errors, and may rely on removed ones.
+                self.binding_list(id, "errors", &old.errors, &new.errors, Dangerous, Dangerous);
+            }
+            (Shape::Service(old), Shape::Service(new)) => {
+                let lists = [
+                    (
+                        "operations",
+                        &old.operations,
+                        &new.operations,
+                        Safe,
+                        Breaking,
+                    ),
+                    ("resources", &old.resources, &new.resources, Safe, Breaking),
+                    ("errors", &old.errors, &new.errors, Dangerous, Dangerous),
+                ];
+                for (property, old, new, added, removed) in lists {
+                    self.binding_list(id, property, old, new, added, removed);
+                }
+            }
+            (Shape::Resource(old), Shape::Resource(new)) => {
+                // Identifiers are members of the input of every instance operation.
+                let empty = IndexMap::new();
+                let old_identifiers = old.identifiers.as_ref().unwrap_or(&empty);
+                let new_identifiers = new.identifiers.as_ref().unwrap_or(&empty);
+                self.binding_map(
+                    id,
+                    "identifiers",
+                    old_identifiers,
+                    new_identifiers,
+                    Breaking,
+                );
+                self.binding_map(id, "properties", &old.properties, &new.properties, Safe);
+                let lifecycle = [
+                    ("put", &old.put, &new.put),
+                    ("create", &old.create, &new.create),
+                    ("read", &old.read, &new.read),
+                    ("update", &old.update, &new.update),
+                    ("delete", &old.delete, &new.delete),
+                    ("list", &old.list, &new.list),
+                ];
+                for (property, old, new) in lifecycle {
+                    self.binding(id, property, old.as_ref(), new.as_ref(), Safe);
+                }
+                let lists = [
+                    ("operations", &old.operations, &new.operations),
+                    (
+                        "collectionOperations",
+                        &old.collection_operations,
+                        &new.collection_operations,
+                    ),
+                    ("resources", &old.resources, &new.resources),
+                ];
+                for (property, old, new) in lists {
+                    self.binding_list(id, property, old, new, Safe, Breaking);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Compares a property binding at most one shape. Removing or changing the shape breaks
+    /// clients.
+    fn binding(
+        &mut self,
+        id: &ShapeId,
+        property: &str,
+        old: Option<&ShapeRef>,
+        new: Option<&ShapeRef>,
+        added: Severity,
+    ) {
+        let property = property.to_string();
+        match (old, new) {
+            (None, Some(new)) => {
+                let target = new.target.clone();
+                self.push(
+                    added,
+                    id,
+                    None,
+                    ChangeKind::AddedBinding { property, target },
+                );
+            }
+            (Some(old), None) => {
+                let target = old.target.clone();
+                let kind = ChangeKind::RemovedBinding { property, target };
+                self.push(Severity::Breaking, id, None, kind);
+            }
+            (Some(old), Some(new)) if old.target != new.target => {
+                let kind = ChangeKind::ChangedBinding {
+                    property,
+                    old: old.target.clone(),
+                    new: new.target.clone(),
+                };
+                self.push(Severity::Breaking, id, None, kind);
+            }
+            _ => {}
+        }
+    }
+
+    /// Compares a property binding names to shapes, such as the identifiers of a resource.
+    fn binding_map(
+        &mut self,
+        id: &ShapeId,
+        property: &str,
+        old: &IndexMap<String, ShapeRef>,
+        new: &IndexMap<String, ShapeRef>,
+        added: Severity,
+    ) {
+        let added_names = new.keys().filter(|name| !old.contains_key(*name));
+        for name in old.keys().chain(added_names) {
+            let property = format!("{property}.{name}");
+            self.binding(id, &property, old.get(name), new.get(name), added);
+        }
+    }
+
+    /// Compares a property binding a list of shapes.
+    fn binding_list(
+        &mut self,
+        id: &ShapeId,
+        property: &str,
+        old: &[ShapeRef],
+        new: &[ShapeRef],
+        added: Severity,
+        removed: Severity,
+    ) {
+        for shape_ref in old {
+            if !new.contains(shape_ref) {
+                let kind = ChangeKind::RemovedBinding {
+                    property: property.to_string(),
+                    target: shape_ref.target.clone(),
+                };
+                self.push(removed, id, None, kind);
+            }
+        }
+        for shape_ref in new {
+            if !old.contains(shape_ref) {
+                let kind = ChangeKind::AddedBinding {
+                    property: property.to_string(),
+                    target: shape_ref.target.clone(),
+                };
+                self.push(added, id, None, kind);
+            }
+        }
+    }
+
+    fn traits(
+        &mut self,
+        id: &ShapeId,
+        member: Option<&str>,
+        old: &IndexMap<&str, &Value>,
+        new: &IndexMap<&str, &Value>,
+    ) {
+        // These are reported as their own kinds of change.
+        let skip = [REQUIRED, ENUM];
+        for (&trait_id, &old_value) in old {
+            if skip.contains(&trait_id) {
+                continue;
+            }
+            let severity = trait_severity(trait_id);
+            let trait_id = trait_id.to_string();
+            match new.get(trait_id.as_str()) {
+                None => self.push(severity, id, member, ChangeKind::RemovedTrait { trait_id }),
+                Some(&new_value) if new_value != old_value => {
+                    let kind = ChangeKind::ChangedTrait {
+                        trait_id,
+                        old: old_value.clone(),
+                        new: new_value.clone(),
+                    };
+                    self.push(severity, id, member, kind);
+                }
+                Some(_) => {}
+            }
+        }
+        for &trait_id in new.keys() {
+            if skip.contains(&trait_id) || old.contains_key(trait_id) {
+                continue;
+            }
+            let severity = trait_severity(trait_id);
+            let trait_id = trait_id.to_string();
+            self.push(severity, id, member, ChangeKind::AddedTrait { trait_id });
+        }
+    }
+
+    /// Compares the values of the deprecated `smithy.api#enum` trait on strings.
+    fn string_enum(
+        &mut self,
+        id: &ShapeId,
+        old: &IndexMap<&str, &Value>,
+        new: &IndexMap<&str, &Value>,
+    ) {
+        let values = |traits: &IndexMap<&str, &Value>| -> Vec<String> {
+            traits
+                .get(ENUM)
+                .and_then(|value| value.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|item| item["value"].as_str())
+                .map(str::to_string)
+                .collect()
+        };
+        let old = values(old);
+        let new = values(new);
+        for value in &old {
+            if !new.contains(value) {
+                let kind = ChangeKind::RemovedEnumValue {
+                    value: value.clone(),
+                };
+                self.push(Severity::Breaking, id, None, kind);
+            }
+        }
+        for value in new {
+            if !old.contains(&value) {
+                self.push(
+                    Severity::Safe,
+                    id,
+                    None,
+                    ChangeKind::AddedEnumValue { value },
+                );
+            }
+        }
+    }
+}
+
+fn traits(value: &Value) -> IndexMap<&str, &Value> {
+    value["traits"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(id, value)| (id.as_str(), value))
+        .collect()
+}
+
+/// The members of aggregate shapes, including the `member` of lists and `key` and `value` of maps.
+fn members(shape: &Value) -> IndexMap<&str, &Value> {
+    let mut members = shape["members"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, member)| (name.as_str(), member))
+        .collect::<IndexMap<_, _>>();
+    for name in ["member", "key", "value"] {
+        if let Some(member) = shape.get(name) {
+            members.insert(name, member);
+        }
+    }
+    members
+}
+
+fn enum_value(name: &str, member: &Value) -> String {
+    member["traits"][ENUM_VALUE]
+        .as_str()
+        .unwrap_or(name)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn diff_models() {
+        let old = serde_json::from_value::<Model>(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#GetThing": {
+                    "type": "operation",
+                    "input": { "target": "example#GetThingInput" },
+                    "output": { "target": "smithy.api#Unit" },
+                },
+                "example#DeleteThing": {
+                    "type": "operation",
+                    "input": { "target": "smithy.api#Unit" },
+                    "output": { "target": "smithy.api#Unit" },
+                },
+                "example#GetThingInput": {
+                    "type": "structure",
+                    "members": {
+                        "Name": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#required": {}, "smithy.api#httpLabel": {} },
+                        },
+                        "Color": { "target": "example#Color" },
+                        "Size": { "target": "smithy.api#Integer" },
+                        "Legacy": { "target": "smithy.api#String" },
+                    },
+                    "traits": { "smithy.api#documentation": "Old docs" },
+                },
+                "example#Color": {
+                    "type": "enum",
+                    "members": {
+                        "RED": { "target": "smithy.api#Unit", "traits": { "smithy.api#enumValue": "red" } },
+                        "BLUE": { "target": "smithy.api#Unit", "traits": { "smithy.api#enumValue": "blue" } },
+                    },
+                },
+                "example#Id": { "type": "string" },
+            },
+        }))
+        .unwrap();
+        let new = serde_json::from_value::<Model>(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#GetThing": {
+                    "type": "operation",
+                    "input": { "target": "example#GetThingInput" },
+                    "output": { "target": "smithy.api#Unit" },
+                },
+                "example#PutThing": {
+                    "type": "operation",
+                    "input": { "target": "smithy.api#Unit" },
+                    "output": { "target": "smithy.api#Unit" },
+                },
+                "example#GetThingInput": {
+                    "type": "structure",
+                    "members": {
+                        "Name": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#httpQuery": "name" },
+                        },
+                        "Color": {
+                            "target": "example#Color",
+                            "traits": { "smithy.api#required": {} },
+                        },
+                        "Size": { "target": "smithy.api#Long" },
+                        "Owner": { "target": "smithy.api#String" },
+                    },
+                    "traits": { "smithy.api#documentation": "New docs" },
+                },
+                "example#Color": {
+                    "type": "enum",
+                    "members": {
+                        "RED": { "target": "smithy.api#Unit", "traits": { "smithy.api#enumValue": "red" } },
+                        "GREEN": { "target": "smithy.api#Unit", "traits": { "smithy.api#enumValue": "green" } },
+                    },
+                },
+                "example#Id": { "type": "blob" },
+            },
+        }))
+        .unwrap();
+
+        let changes = diff(&old, &new)
+            .iter()
+            .map(|change| format!("{}: {change}", change.severity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                "breaking: removed operation example#DeleteThing",
+                "safe: changed smithy.api#documentation on example#GetThingInput",
+                "safe: example#GetThingInput$Name became optional",
+                "breaking: removed smithy.api#httpLabel from example#GetThingInput$Name",
+                "breaking: added smithy.api#httpQuery to example#GetThingInput$Name",
+                "breaking: example#GetThingInput$Color became required",
+                "breaking: example#GetThingInput$Size changed target from smithy.api#Integer to smithy.api#Long",
+                "breaking: removed member example#GetThingInput$Legacy",
+                "safe: added member example#GetThingInput$Owner",
+                "breaking: removed enum value \"blue\" from example#Color$BLUE",
+                "safe: added enum value \"green\" to example#Color$GREEN",
+                "breaking: example#Id changed type from string to blob",
+                "safe: added operation example#PutThing",
+            ]
+        );
+
+        let json = serde_json::to_value(&diff(&old, &new)[0]).unwrap();
+        assert_eq!(
+            json,
+            json!({ "severity": "breaking", "shape": "example#DeleteThing", "kind": "removedOperation" })
+        );
+    }
+
+    #[test]
+    fn diff_bindings() {
+        let old = serde_json::from_value::<Model>(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#Thing": {
+                    "type": "resource",
+                    "identifiers": { "thingId": { "target": "smithy.api#String" } },
+                    "read": { "target": "example#GetThing" },
+                    "operations": [{ "target": "example#DeleteThing" }],
+                },
+                "example#GetThing": {
+                    "type": "operation",
+                    "input": { "target": "example#GetThingInput" },
+                    "output": { "target": "example#GetThingOutput" },
+                    "errors": [{ "target": "example#NotFound" }],
+                },
+                "example#GetThingInput": { "type": "structure", "members": {} },
+                "example#GetThingOutput": {
+                    "type": "structure",
+                    "members": {
+                        "Name": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#required": {} },
+                        },
+                        "Size": { "target": "smithy.api#Integer" },
+                    },
+                },
+                "example#NotFound": {
+                    "type": "structure",
+                    "members": {},
+                    "traits": { "smithy.api#error": "client" },
+                },
+                "example#Throttled": {
+                    "type": "structure",
+                    "members": {},
+                    "traits": { "smithy.api#error": "client" },
+                },
+            },
+        }))
+        .unwrap();
+        let new = serde_json::from_value::<Model>(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#Thing": {
+                    "type": "resource",
+                    "identifiers": { "id": { "target": "smithy.api#String" } },
+                    "list": { "target": "example#ListThings" },
+                    "operations": [{ "target": "example#UpdateThing" }],
+                },
+                "example#GetThing": {
+                    "type": "operation",
+                    "input": { "target": "example#GetThingInput" },
+                    "output": { "target": "example#GetThingOutput" },
+                    "errors": [{ "target": "example#Throttled" }],
+                },
+                "example#ListThings": {
+                    "type": "operation",
+                    "input": { "target": "smithy.api#Unit" },
+                    "output": { "target": "example#GetThingOutput" },
+                },
+                "example#GetThingInput": { "type": "structure", "members": {} },
+                "example#GetThingOutput": {
+                    "type": "structure",
+                    "members": {
+                        "Name": { "target": "smithy.api#String" },
+                        "Size": {
+                            "target": "smithy.api#Integer",
+                            "traits": { "smithy.api#required": {} },
+                        },
+                        "Color": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#required": {} },
+                        },
+                    },
+                },
+                "example#NotFound": {
+                    "type": "structure",
+                    "members": {},
+                    "traits": { "smithy.api#error": "client" },
+                },
+                "example#Throttled": {
+                    "type": "structure",
+                    "members": {},
+                    "traits": { "smithy.api#error": "client" },
+                },
+            },
+        }))
+        .unwrap();
+
+        let changes = diff(&old, &new)
+            .iter()
+            .map(|change| format!("{}: {change}", change.severity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                "breaking: removed smithy.api#String from identifiers.thingId of example#Thing",
+                "breaking: added smithy.api#String to identifiers.id of example#Thing",
+                "breaking: removed example#GetThing from read of example#Thing",
+                "safe: added example#ListThings to list of example#Thing",
+                "breaking: removed example#DeleteThing from operations of example#Thing",
+                "safe: added example#UpdateThing to operations of example#Thing",
+                "dangerous: removed example#NotFound from errors of example#GetThing",
+                "dangerous: added example#Throttled to errors of example#GetThing",
+                "dangerous: example#GetThingOutput$Name became optional",
+                "safe: example#GetThingOutput$Size became required",
+                "safe: added member example#GetThingOutput$Color",
+                "safe: added operation example#ListThings",
+            ]
+        );
+
+        let mut changed = new.clone();
+        let Some(Shape::Operation(operation)) = changed
+            .shapes
+            .get_mut(&"example#GetThing".parse::<ShapeId>().unwrap())
+        else {
+            panic!("expected GetThing operation");
+        };
+        operation.output.target = "example#GetThingInput".parse().unwrap();
+        let changes = diff(&new, &changed)
+            .iter()
+            .map(|change| format!("{}: {change}", change.severity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            ["breaking: output of example#GetThing changed from example#GetThingOutput to example#GetThingInput"]
+        );
+    }
+}
@@ -1,26 +1,26 @@
 use indexmap::IndexMap;
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct EndpointRuleSet {
     pub parameters: IndexMap<String, EndpointRuleSetParam>,
     pub rules: Vec<EndpointRuleItem>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct EndpointRuleSetParam {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub built_in: Option<EndpointRuleSetParamBuiltIn>,
     pub required: bool,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub default: Option<serde_json::Value>,
     pub documentation: String,
     #[serde(rename = "type")]
     pub type_: EndpointRuleSetParamType,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum EndpointRuleSetParamBuiltIn {
     #[serde(rename = "AWS::Region")]
     Region,
@@ -46,13 +46,13 @@ pub enum EndpointRuleSetParamBuiltIn {
     SdkEndpoint,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum EndpointRuleSetParamType {
     Boolean,
     String,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EndpointRuleItem {
     pub conditions: Vec<EndpointRuleCondition>,
@@ -60,16 +60,16 @@ pub struct EndpointRuleItem {
     pub rule: EndpointRule,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct EndpointRuleCondition {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub assign: Option<String>,
     #[serde(flatten)]
     pub function: EndpointRuleConditionFn,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(
     tag = "fn",
     content = "argv",
@@ -77,24 +77,67 @@ pub struct EndpointRuleCondition {
     deny_unknown_fields
 )]
 pub enum EndpointRuleConditionFn {
-    Not(#[serde(deserialize_with = "single_tuple::deserialize")] EndpointRuleExpr),
+    Not(#[serde(with = "single_tuple")] EndpointRuleExpr),
     BooleanEquals(EndpointRuleExpr, EndpointRuleExpr),
     StringEquals(EndpointRuleExpr, EndpointRuleExpr),
     GetAttr(EndpointRuleExpr, String),
-    IsSet(#[serde(deserialize_with = "single_tuple::deserialize")] EndpointRuleExpr),
-    ParseURL(#[serde(deserialize_with = "single_tuple::deserialize")] EndpointRuleExpr),
+    IsSet(#[serde(with = "single_tuple")] EndpointRuleExpr),
+    ParseURL(#[serde(with = "single_tuple")] EndpointRuleExpr),
     IsValidHostLabel(EndpointRuleExpr, bool),
     Substring(EndpointRuleExpr, usize, usize, bool),
-    UriEncode(#[serde(deserialize_with = "single_tuple::deserialize")] EndpointRuleExpr),
+    UriEncode(#[serde(with = "single_tuple")] EndpointRuleExpr),
     #[serde(rename = "aws.partition")]
-    AwsPartition(#[serde(deserialize_with = "single_tuple::deserialize")] EndpointRuleExpr),
+    AwsPartition(#[serde(with = "single_tuple")] EndpointRuleExpr),
     #[serde(rename = "aws.parseArn")]
-    AwsParseArn(#[serde(deserialize_with = "single_tuple::deserialize")] EndpointRuleExpr),
+    AwsParseArn(#[serde(with = "single_tuple")] EndpointRuleExpr),
     #[serde(rename = "aws.isVirtualHostableS3Bucket")]
     AwsIsVirtualHostableS3Bucket(EndpointRuleExpr, bool),
 }
 
+impl EndpointRuleConditionFn {
+    /// The expressions the function is called with, in order, without its literal options.
+    pub fn args(&self) -> Vec<&EndpointRuleExpr> {
+        match self {
+            Self::BooleanEquals(lhs, rhs) | Self::StringEquals(lhs, rhs) => vec![lhs, rhs],
+            Self::Not(expr)
+            | Self::GetAttr(expr, _)
+            | Self::IsSet(expr)
+            | Self::ParseURL(expr)
+            | Self::IsValidHostLabel(expr, _)
+            | Self::Substring(expr, ..)
+            | Self::UriEncode(expr)
+            | Self::AwsPartition(expr)
+            | Self::AwsParseArn(expr)
+            | Self::AwsIsVirtualHostableS3Bucket(expr, _) => vec![expr],
+        }
+    }
+
+    pub fn args_mut(&mut self) -> Vec<&mut EndpointRuleExpr> {
+        match self {
+            Self::BooleanEquals(lhs, rhs) | Self::StringEquals(lhs, rhs) => vec![lhs, rhs],
+            Self::Not(expr)
+            | Self::GetAttr(expr, _)
+            | Self::IsSet(expr)
+            | Self::ParseURL(expr)
+            | Self::IsValidHostLabel(expr, _)
+            | Self::Substring(expr, ..)
+            | Self::UriEncode(expr)
+            | Self::AwsPartition(expr)
+            | Self::AwsParseArn(expr)
+            | Self::AwsIsVirtualHostableS3Bucket(expr, _) => vec![expr],
+        }
+    }
+}
+
 mod single_tuple {
+    pub fn serialize<S, V>(value: &V, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+        V: serde::Serialize,
+    {
+        serde::Serialize::serialize(&(value,), serializer)
+    }
+
     pub fn deserialize<'de, D, V>(deserializer: D) -> Result<V, D::Error>
     where
         D: serde::Deserializer<'de>,
@@ -130,7 +173,7 @@ mod single_tuple {
     }
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(untagged, deny_unknown_fields)]
 pub enum EndpointRuleExpr {
     Condition(Box<EndpointRuleCondition>),
@@ -142,7 +185,7 @@ pub enum EndpointRuleExpr {
     Boolean(bool),
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
 pub enum EndpointRule {
     Tree { rules: Vec<EndpointRuleItem> },
@@ -150,7 +193,7 @@ pub enum EndpointRule {
     Endpoint { endpoint: Endpoint },
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct Endpoint {
     pub url: EndpointRuleExpr,
@@ -158,41 +201,91 @@ pub struct Endpoint {
     pub headers: IndexMap<String, Vec<String>>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct EndpointPropertyMap {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub backend: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub auth_schemes: Vec<EndpointAuthScheme>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(tag = "name", rename_all = "kebab-case", deny_unknown_fields)]
 pub enum EndpointAuthScheme {
     #[serde(rename_all = "camelCase")]
     Sigv4 {
         signing_name: String,
         signing_region: String,
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
         disable_double_encoding: bool,
     },
     #[serde(rename_all = "camelCase")]
     Sigv4a {
         signing_name: String,
         signing_region_set: Vec<String>,
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
         disable_double_encoding: bool,
     },
     #[serde(rename = "sigv4-s3express", rename_all = "camelCase")]
     Sigv4S3Express {
         signing_name: String,
         signing_region: String,
-        #[serde(default)]
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
         disable_double_encoding: bool,
     },
 }
 
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EndpointTests {
+    pub test_cases: Vec<EndpointTestCase>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EndpointTestCase {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+    /// The rule set parameters, by name.
+    #[serde(default)]
+    pub params: IndexMap<String, serde_json::Value>,
+    pub expect: EndpointTestExpectation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_inputs: Option<Vec<EndpointTestOperationInput>>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub enum EndpointTestExpectation {
+    Endpoint(EndpointTestEndpoint),
+    Error(String),
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EndpointTestEndpoint {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<IndexMap<String, serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<IndexMap<String, Vec<String>>>,
+}
+
+/// How a client calling an operation ends up with the test case's parameters.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EndpointTestOperationInput {
+    pub operation_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation_params: Option<IndexMap<String, serde_json::Value>>,
+    /// Values for built-ins such as `AWS::Region`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub built_in_params: Option<IndexMap<String, serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_params: Option<IndexMap<String, serde_json::Value>>,
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -292,11 +385,16 @@ mod tests {
             "endpoint": {
                 "url": { "ref": "https://{service}.{region}.amazonaws.com" },
                 "properties": {
-                    "service": "service",
-                    "region": "region",
+                    "authSchemes": [
+                        {
+                            "name": "sigv4",
+                            "signingName": "service",
+                            "signingRegion": "region",
+                        },
+                    ],
                 },
                 "headers": {
-                    "X-Foo": "bar",
+                    "X-Foo": ["bar"],
                 },
             },
             "type": "endpoint",
@@ -370,4 +468,40 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_cases() -> serde_json::Result<()> {
+        let value = serde_json::from_value::<EndpointTests>(json!({
+            "testCases": [
+                {
+                    "documentation": "FIPS in us-east-1",
+                    "expect": {
+                        "endpoint": { "url": "https://example-fips.us-east-1.amazonaws.com" },
+                    },
+                    "params": { "Region": "us-east-1", "UseFIPS": true },
+                    "operationInputs": [{
+                        "operationName": "GetThing",
+                        "builtInParams": { "AWS::Region": "us-east-1", "AWS::UseFIPS": true },
+                    }],
+                },
+                {
+                    "expect": { "error": "Invalid Configuration: Missing Region" },
+                    "params": {},
+                },
+            ],
+        }))?;
+
+        assert_matches!(
+            value.test_cases.as_slice() => [first, second],
+            &first.expect => EndpointTestExpectation::Endpoint(EndpointTestEndpoint { url, headers: None, .. }),
+            url.as_str() => "https://example-fips.us-east-1.amazonaws.com",
+            first.params.get("UseFIPS") => Some(serde_json::Value::Bool(true)),
+            first.operation_inputs.as_deref() => Some([input]),
+            input.operation_name.as_str() => "GetThing",
+            &second.expect => EndpointTestExpectation::Error(error),
+            error.as_str() => "Invalid Configuration: Missing Region",
+        );
+
+        Ok(())
+    }
 }
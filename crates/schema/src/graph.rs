@@ -0,0 +1,308 @@
+//! An index of the references between the shapes of a model.
+
+use std::collections::HashMap;
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::{Model, Shape, ShapeId};
+
+/// The shapes each shape refers to, and is referred to by, through members, operation inputs,
+/// outputs and errors, and service and resource bindings.
+///
+/// References to shapes outside the model, such as the prelude, are included, but only shapes in
+/// the model have references of their own.
+pub struct ShapeGraph<'model> {
+    references: IndexMap<&'model ShapeId, Vec<&'model ShapeId>>,
+    referenced_by: HashMap<&'model ShapeId, Vec<&'model ShapeId>>,
+}
+
+impl<'model> ShapeGraph<'model> {
+    pub fn new(model: &'model Model) -> Self {
+        let mut references = IndexMap::new();
+        let mut referenced_by = HashMap::<_, Vec<_>>::new();
+        for (id, shape) in &model.shapes {
+            let targets = shape_references(shape);
+            for &target in &targets {
+                let sources = referenced_by.entry(target).or_default();
+                if !sources.contains(&id) {
+                    sources.push(id);
+                }
+            }
+            references.insert(id, targets);
+        }
+        Self {
+            references,
+            referenced_by,
+        }
+    }
+
+    /// The shapes this shape refers to directly.
+    pub fn references(&self, id: &ShapeId) -> &[&'model ShapeId] {
+        self.references.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The shapes that refer to this shape directly.
+    pub fn referenced_by(&self, id: &ShapeId) -> &[&'model ShapeId] {
+        self.referenced_by.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The shape and every shape it reaches, transitively. For a service, this is every shape
+    /// needed by its operations and resources.
+    pub fn closure(&self, id: &'model ShapeId) -> IndexSet<&'model ShapeId> {
+        let mut closure = IndexSet::new();
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            if closure.insert(id) {
+                pending.extend(self.references(id).iter().rev());
+            }
+        }
+        closure
+    }
+
+    /// The groups of shapes that refer to each other, directly or indirectly, such as recursive
+    /// structures. A shape referring to itself is a group of one.
+    pub fn cycles(&self) -> Vec<Vec<&'model ShapeId>> {
+        self.strongly_connected()
+            .into_iter()
+            .filter(|component| match component.as_slice() {
+                [id] => self.references(id).contains(id),
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// Whether the shape can reach itself.
+    pub fn is_recursive(&self, id: &ShapeId) -> bool {
+        self.references(id)
+            .iter()
+            .any(|&target| target == id || self.closure(target).contains(id))
+    }
+
+    /// The shapes in the model ordered so that shapes come after the shapes they refer to, apart
+    /// from shapes in a cycle, which are ordered arbitrarily among themselves.
+    pub fn topological_order(&self) -> Vec<&'model ShapeId> {
+        self.strongly_connected().into_iter().flatten().collect()
+    }
+
+    /// Tarjan's algorithm, which finds each component only after all the components it refers
+    /// to, so the result is already in topological order.
+    fn strongly_connected(&self) -> Vec<Vec<&'model ShapeId>> {
+        struct State<'model> {
+            index: HashMap<&'model ShapeId, usize>,
+            low_link: HashMap<&'model ShapeId, usize>,
+            stack: Vec<&'model ShapeId>,
+            on_stack: IndexSet<&'model ShapeId>,
+            components: Vec<Vec<&'model ShapeId>>,
+        }
+
+        fn visit<'model>(
+            graph: &ShapeGraph<'model>,
+            state: &mut State<'model>,
+            id: &'model ShapeId,
+        ) {
+            let index = state.index.len();
+            state.index.insert(id, index);
+            state.low_link.insert(id, index);
+            state.stack.push(id);
+            state.on_stack.insert(id);
+
+            for &target in graph.references(id) {
+                if !graph.references.contains_key(target) {
+                    continue;
+                }
+                if !state.index.contains_key(target) {
+                    visit(graph, state, target);
+                    let low_link = state.low_link[id].min(state.low_link[target]);
+                    state.low_link.insert(id, low_link);
+                } else if state.on_stack.contains(target) {
+                    let low_link = state.low_link[id].min(state.index[target]);
+                    state.low_link.insert(id, low_link);
+                }
+            }
+
+            if state.low_link[id] == index {
+                let mut component = vec![];
+                while let Some(member) = state.stack.pop() {
+                    state.on_stack.swap_remove(member);
+                    component.push(member);
+                    if member == id {
+                        break;
+                    }
+                }
+                component.reverse();
+                state.components.push(component);
+            }
+        }
+
+        let mut state = State {
+            index: HashMap::new(),
+            low_link: HashMap::new(),
+            stack: vec![],
+            on_stack: IndexSet::new(),
+            components: vec![],
+        };
+        for &id in self.references.keys() {
+            if !state.index.contains_key(id) {
+                visit(self, &mut state, id);
+            }
+        }
+        state.components
+    }
+}
+
+fn shape_references(shape: &Shape) -> Vec<&ShapeId> {
+    let mut targets = vec![];
+    match shape {
+        Shape::Service(service) => {
+            targets.extend(service.operations.iter().map(|r| &r.target));
+            targets.extend(service.resources.iter().map(|r| &r.target));
+            targets.extend(service.errors.iter().map(|r| &r.target));
+        }
+        Shape::Operation(operation) => {
+            targets.push(&operation.input.target);
+            targets.push(&operation.output.target);
+            targets.extend(operation.errors.iter().map(|r| &r.target));
+        }
+        Shape::Resource(resource) => {
+            targets.extend(
+                resource
+                    .identifiers
+                    .iter()
+                    .flatten()
+                    .map(|(_, r)| &r.target),
+            );
+            targets.extend(resource.properties.values().map(|r| &r.target));
+            let lifecycle = [
+                &resource.put,
+                &resource.create,
+                &resource.read,
+                &resource.update,
+                &resource.delete,
+                &resource.list,
+            ];
+            targets.extend(lifecycle.into_iter().flatten().map(|r| &r.target));
+            targets.extend(resource.collection_operations.iter().map(|r| &r.target));
+            targets.extend(resource.operations.iter().map(|r| &r.target));
+            targets.extend(resource.resources.iter().map(|r| &r.target));
+        }
+        Shape::Structure(structure) => {
+            targets.extend(structure.members.values().map(|member| &member.target));
+        }
+        Shape::Union(union) => {
+            targets.extend(union.members.values().map(|member| &member.target));
+        }
+        Shape::Enum(enum_) => {
+            targets.extend(enum_.members.values().map(|member| &member.target));
+        }
+        Shape::List(list) => targets.push(&list.member.target),
+        Shape::Map(map) => {
+            targets.push(&map.key.target);
+            targets.push(&map.value.target);
+        }
+        Shape::Boolean(_)
+        | Shape::Byte(_)
+        | Shape::Short(_)
+        | Shape::Integer(_)
+        | Shape::Long(_)
+        | Shape::Float(_)
+        | Shape::Double(_)
+        | Shape::BigInteger(_)
+        | Shape::BigDecimal(_)
+        | Shape::String(_)
+        | Shape::Blob(_)
+        | Shape::Timestamp(_)
+        | Shape::Document(_) => {}
+    }
+    let mut unique = IndexSet::new();
+    targets.retain(|target| unique.insert(*target));
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn names<'a>(ids: impl IntoIterator<Item = &'a &'a ShapeId>) -> Vec<&'a str> {
+        ids.into_iter().map(|id| id.name.as_str()).collect()
+    }
+
+    #[test]
+    fn shape_graph() {
+        let model = serde_json::from_value::<Model>(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#Forest": {
+                    "type": "resource",
+                    "operations": [{ "target": "example#GetTree" }],
+                },
+                "example#GetTree": {
+                    "type": "operation",
+                    "input": { "target": "example#GetTreeInput" },
+                    "output": { "target": "example#Tree" },
+                    "errors": [{ "target": "example#NotFound" }],
+                },
+                "example#GetTreeInput": {
+                    "type": "structure",
+                    "members": { "Name": { "target": "smithy.api#String" } },
+                },
+                "example#Tree": {
+                    "type": "structure",
+                    "members": {
+                        "Name": { "target": "smithy.api#String" },
+                        "Children": { "target": "example#Trees" },
+                    },
+                },
+                "example#Trees": { "type": "list", "member": { "target": "example#Tree" } },
+                "example#NotFound": {
+                    "type": "structure",
+                    "members": {},
+                    "traits": { "smithy.api#error": "client" },
+                },
+                "example#Unused": { "type": "string" },
+            },
+        }))
+        .unwrap();
+        let graph = ShapeGraph::new(&model);
+        let id = |name: &str| {
+            model
+                .shapes
+                .keys()
+                .find(|id| id.name == name)
+                .expect("shape should exist")
+        };
+
+        assert_eq!(
+            names(graph.references(id("GetTree"))),
+            ["GetTreeInput", "Tree", "NotFound"]
+        );
+        assert_eq!(names(graph.referenced_by(id("Tree"))), ["GetTree", "Trees"]);
+        assert_eq!(
+            names(&graph.closure(id("Forest"))),
+            [
+                "Forest",
+                "GetTree",
+                "GetTreeInput",
+                "String",
+                "Tree",
+                "Trees",
+                "NotFound"
+            ]
+        );
+        assert!(!graph.closure(id("Forest")).contains(id("Unused")));
+
+        let cycles = graph.cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(names(&cycles[0]), ["Tree", "Trees"]);
+        assert!(graph.is_recursive(id("Trees")));
+        assert!(!graph.is_recursive(id("GetTree")));
+
+        let order = graph.topological_order();
+        let position = |name: &str| order.iter().position(|id| id.name == name).unwrap();
+        assert_eq!(order.len(), model.shapes.len());
+        assert!(position("GetTreeInput") < position("GetTree"));
+        assert!(position("Tree") < position("GetTree"));
+        assert!(position("GetTree") < position("Forest"));
+    }
+}
@@ -0,0 +1,1463 @@
+//! Parser for the Smithy IDL 2.0 text format.
+//!
+//! The IDL is lowered to the equivalent JSON AST, which is then deserialized with the same
+//! definitions as [`crate::parse_model`], so both formats produce identical [`Model`]s.
+
+use std::collections::{HashMap, HashSet};
+
+use indexmap::IndexMap;
+use miette::{NamedSource, SourceSpan};
+use serde_json::{Map, Value};
+
+use crate::{prelude, upgrade_json_ast, Model, ShapeId};
+
+const UNIT: &str = "smithy.api#Unit";
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum IdlError {
+    #[error("{message}")]
+    #[diagnostic(code(laws_schema::idl::syntax))]
+    Syntax {
+        message: String,
+        #[source_code]
+        source_code: NamedSource,
+        #[label("here")]
+        span: SourceSpan,
+    },
+    #[error("lowering IDL to a model")]
+    #[diagnostic(code(laws_schema::idl::model))]
+    Model(#[source] serde_json::Error),
+}
+
+pub fn parse_idl(name: &str, source: &str) -> Result<Model, IdlError> {
+    let mut ast = idl_to_json_ast(name, source)?;
+    upgrade_json_ast(&mut ast);
+    serde_json::from_value(ast).map_err(IdlError::Model)
+}
+
+/// Converts Smithy IDL source to the equivalent JSON AST value.
+pub fn idl_to_json_ast(name: &str, source: &str) -> Result<Value, IdlError> {
+    // Relative shape ids can refer to shapes defined later in the file, so the first pass only
+    // collects the definitions, which are then available to resolve ids in the second pass.
+    let (_, defs) = Parser::new(name, source, None).parse()?;
+    let (ast, _) = Parser::new(name, source, Some(&defs)).parse()?;
+    Ok(ast)
+}
+
+#[derive(Default)]
+struct Definitions {
+    /// Names of shapes defined in the file's namespace.
+    names: HashSet<String>,
+    /// Member names and unresolved targets that elided (`$name`) members can refer to, by
+    /// absolute shape id: resource identifiers and properties, and structure members for mixins.
+    elidable: HashMap<String, IndexMap<String, String>>,
+}
+
+struct Parser<'a> {
+    name: &'a str,
+    source: &'a str,
+    pos: usize,
+    docs: Vec<String>,
+    /// `None` on the first pass, when shape ids are left unresolved.
+    defined: Option<&'a Definitions>,
+    defs: Definitions,
+
+    version: String,
+    input_suffix: String,
+    output_suffix: String,
+    namespace: Option<String>,
+    uses: Vec<ShapeId>,
+    metadata: Map<String, Value>,
+    shapes: Map<String, Value>,
+}
+
+type Result<T, E = IdlError> = std::result::Result<T, E>;
+
+impl<'a> Parser<'a> {
+    fn new(name: &'a str, source: &'a str, defined: Option<&'a Definitions>) -> Self {
+        Self {
+            name,
+            source,
+            pos: 0,
+            docs: vec![],
+            defined,
+            defs: Definitions::default(),
+            version: "1.0".to_string(),
+            input_suffix: "Input".to_string(),
+            output_suffix: "Output".to_string(),
+            namespace: None,
+            uses: Vec::new(),
+            metadata: Map::new(),
+            shapes: Map::new(),
+        }
+    }
+
+    fn parse(mut self) -> Result<(Value, Definitions)> {
+        self.ws();
+        while self.peek() == Some('$') {
+            self.control_statement()?;
+            self.ws();
+        }
+        while self.eat_keyword("metadata") {
+            self.metadata_statement()?;
+            self.ws();
+        }
+        if self.eat_keyword("namespace") {
+            self.ws();
+            let namespace = self.namespace()?;
+            self.namespace = Some(namespace);
+            self.ws();
+            while self.eat_keyword("use") {
+                self.use_statement()?;
+                self.ws();
+            }
+            let mut applies = vec![];
+            while self.peek().is_some() {
+                if self.eat_keyword("apply") {
+                    applies.push(self.apply_statement()?);
+                } else {
+                    self.shape_statement()?;
+                }
+                self.ws();
+            }
+            // Apply targets are only resolved on the second pass.
+            if self.defined.is_some() {
+                for apply in applies {
+                    self.apply(apply)?;
+                }
+            }
+        }
+        if self.peek().is_some() {
+            return Err(self.error_here("expected a control, metadata or namespace statement"));
+        }
+
+        let mut ast = Map::new();
+        ast.insert("smithy".to_string(), Value::String(self.version));
+        if !self.metadata.is_empty() {
+            ast.insert("metadata".to_string(), Value::Object(self.metadata));
+        }
+        ast.insert("shapes".to_string(), Value::Object(self.shapes));
+        Ok((Value::Object(ast), self.defs))
+    }
+
+    fn control_statement(&mut self) -> Result<()> {
+        self.take_docs();
+        self.expect('$')?;
+        let start = self.pos;
+        let key = self.node_object_key()?;
+        self.ws();
+        self.expect(':')?;
+        self.ws();
+        let value_start = self.pos;
+        let value = self.node_value()?;
+        let Value::String(value) = value else {
+            return Err(self.error(value_start, self.pos, "expected a string"));
+        };
+        match key.as_str() {
+            "version" => {
+                self.version = match value.as_str() {
+                    "1" | "1.0" => "1.0".to_string(),
+                    "2" | "2.0" => "2.0".to_string(),
+                    _ => return Err(self.error(value_start, self.pos, "unsupported IDL version")),
+                };
+            }
+            "operationInputSuffix" => self.input_suffix = value,
+            "operationOutputSuffix" => self.output_suffix = value,
+            _ => return Err(self.error(start, start + key.len(), "unknown control statement")),
+        }
+        Ok(())
+    }
+
+    fn metadata_statement(&mut self) -> Result<()> {
+        self.take_docs();
+        self.ws();
+        let key = self.node_object_key()?;
+        self.ws();
+        self.expect('=')?;
+        self.ws();
+        let value = self.node_value()?;
+        match (self.metadata.get_mut(&key), value) {
+            // Metadata lists are concatenated, anything else is a conflict.
+            (Some(Value::Array(existing)), Value::Array(value)) => existing.extend(value),
+            (Some(existing), value) if *existing == value => {}
+            (Some(_), _) => {
+                return Err(self.error_here(format!("conflicting metadata for {key:?}")));
+            }
+            (None, value) => {
+                self.metadata.insert(key, value);
+            }
+        }
+        Ok(())
+    }
+
+    fn use_statement(&mut self) -> Result<()> {
+        self.take_docs();
+        self.ws();
+        let start = self.pos;
+        let id = self.shape_id()?;
+        let id = match id.parse::<ShapeId>() {
+            Ok(id) if id.member.is_none() => id,
+            _ => return Err(self.error(start, self.pos, "expected an absolute shape id")),
+        };
+        if self.uses.iter().any(|used| used.name == id.name) {
+            let message = format!("conflicting use of {:?}", id.name);
+            return Err(self.error(start, self.pos, message));
+        }
+        self.uses.push(id);
+        Ok(())
+    }
+
+    fn apply_statement(&mut self) -> Result<Apply> {
+        self.take_docs();
+        self.ws();
+        let start = self.pos;
+        let id = self.shape_id()?;
+        let span = (start, self.pos);
+        let id = self.resolve(&id);
+        self.ws();
+        let traits = if self.eat('{') {
+            let traits = self.trait_statements()?;
+            self.ws();
+            self.expect('}')?;
+            traits
+        } else {
+            let mut traits = Map::new();
+            if !self.trait_statement(&mut traits)? {
+                return Err(self.error_here("expected a trait or '{'"));
+            }
+            traits
+        };
+        Ok(Apply { id, span, traits })
+    }
+
+    fn apply(&mut self, apply: Apply) -> Result<()> {
+        let (start, end) = apply.span;
+        let (shape_id, member_name) = match apply.id.split_once('$') {
+            Some((shape_id, member_name)) => (shape_id, Some(member_name)),
+            None => (apply.id.as_str(), None),
+        };
+        if let Some(member_name) = member_name {
+            self.redefine_mixin_member(shape_id, member_name);
+        }
+        let Some(Value::Object(shape)) = self.shapes.get_mut(shape_id) else {
+            return Err(self.error(start, end, "apply target is not defined in this file"));
+        };
+        let target = match member_name {
+            None => Some(shape),
+            Some(member_name) => match shape.get("members") {
+                Some(_) => shape
+                    .get_mut("members")
+                    .and_then(|members| members.get_mut(member_name))
+                    .and_then(Value::as_object_mut),
+                None => shape.get_mut(member_name).and_then(Value::as_object_mut),
+            },
+        };
+        let Some(target) = target else {
+            return Err(self.error(start, end, "apply target member is not defined"));
+        };
+        let Value::Object(traits) = target
+            .entry("traits")
+            .or_insert_with(|| Value::Object(Map::new()))
+        else {
+            unreachable!("traits are always objects");
+        };
+        for (id, value) in apply.traits {
+            match (traits.get_mut(&id), value) {
+                (Some(Value::Array(existing)), Value::Array(value)) => existing.extend(value),
+                (Some(existing), value) if *existing == value => {}
+                (Some(_), _) => {
+                    return Err(self.error(start, end, format!("conflicting trait {id}")));
+                }
+                (None, value) => {
+                    traits.insert(id, value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn shape_statement(&mut self) -> Result<()> {
+        let traits = self.documented_trait_statements()?;
+        let type_start = self.pos;
+        let type_ = self.identifier()?;
+        let type_end = self.pos;
+        self.ws();
+        let name_start = self.pos;
+        let name = self.identifier()?;
+        let id = self.absolute_id(name);
+        if self.shapes.contains_key(&id) {
+            return Err(self.error(name_start, self.pos, format!("duplicate shape {id}")));
+        }
+        self.defs.names.insert(name.to_string());
+
+        let mut shape = Map::new();
+        shape.insert("type".to_string(), Value::String(type_.to_string()));
+        match type_ {
+            "blob" | "boolean" | "document" | "string" | "byte" | "short" | "integer" | "long"
+            | "float" | "double" | "bigInteger" | "bigDecimal" | "timestamp" => {
+                self.mixins(&mut shape)?;
+            }
+            "enum" | "intEnum" => {
+                self.mixins(&mut shape)?;
+                let members = self.enum_members(type_ == "intEnum")?;
+                shape.insert("members".to_string(), Value::Object(members));
+            }
+            "list" | "set" | "map" | "structure" | "union" => {
+                let resource = self.for_resource()?;
+                let mixins = self.mixins(&mut shape)?;
+                let members = self.shape_members(resource.as_deref(), &mixins)?;
+                self.insert_members(&id, type_, &mut shape, members)?;
+            }
+            "service" | "resource" => {
+                self.mixins(&mut shape)?;
+                self.ws();
+                self.entity_body(&id, type_, &mut shape)?;
+            }
+            "operation" => {
+                self.mixins(&mut shape)?;
+                self.ws();
+                self.operation_body(name, &mut shape)?;
+            }
+            _ => {
+                return Err(self.error(
+                    type_start,
+                    type_end,
+                    format!("unknown shape type {type_:?}"),
+                ));
+            }
+        }
+        if !traits.is_empty() {
+            shape.insert("traits".to_string(), Value::Object(traits));
+        }
+        self.shapes.insert(id, Value::Object(shape));
+        Ok(())
+    }
+
+    /// Redefines a member the shape gets from a mixin, if it doesn't define the member itself, so
+    /// traits can be applied to it.
+    fn redefine_mixin_member(&mut self, shape_id: &str, member_name: &str) {
+        let Some(Value::Object(shape)) = self.shapes.get(shape_id) else {
+            return;
+        };
+        let members = shape.get("members").and_then(Value::as_object);
+        if members.is_none_or(|members| members.contains_key(member_name)) {
+            return;
+        }
+        let mixins = shape
+            .get("mixins")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|mixin| Some(mixin["target"].as_str()?.to_string()))
+            .collect::<Vec<_>>();
+        let Some(target) = self.elided_target(member_name, None, &mixins) else {
+            return;
+        };
+        let Some(Value::Object(members)) = self
+            .shapes
+            .get_mut(shape_id)
+            .and_then(|shape| shape.get_mut("members"))
+        else {
+            return;
+        };
+        members.insert(member_name.to_string(), shape_ref(target));
+    }
+
+    fn for_resource(&mut self) -> Result<Option<String>> {
+        self.ws();
+        if !self.eat_keyword("for") {
+            return Ok(None);
+        }
+        self.ws();
+        let id = self.shape_id()?;
+        Ok(Some(self.resolve(&id)))
+    }
+
+    fn mixins(&mut self, shape: &mut Map<String, Value>) -> Result<Vec<String>> {
+        self.ws();
+        if !self.eat_keyword("with") {
+            return Ok(vec![]);
+        }
+        self.ws();
+        self.expect('[')?;
+        let mut mixins = vec![];
+        loop {
+            self.ws();
+            if self.eat(']') {
+                break;
+            }
+            let id = self.shape_id()?;
+            mixins.push(self.resolve(&id));
+        }
+        shape.insert(
+            "mixins".to_string(),
+            Value::Array(mixins.iter().map(|id| shape_ref(id.clone())).collect()),
+        );
+        Ok(mixins)
+    }
+
+    fn shape_members(
+        &mut self,
+        resource: Option<&str>,
+        mixins: &[String],
+    ) -> Result<Vec<ParsedMember>> {
+        self.ws();
+        self.expect('{')?;
+        let mut members: Vec<ParsedMember> = vec![];
+        loop {
+            self.ws();
+            if self.eat('}') {
+                self.docs.clear();
+                break;
+            }
+            let mut traits = self.documented_trait_statements()?;
+            let start = self.pos;
+            let elided = self.eat('$');
+            let name = self.identifier()?.to_string();
+            let span = (start, self.pos);
+            let target = if elided {
+                let target = self.elided_target(&name, resource, mixins);
+                target.ok_or_else(|| {
+                    self.error(
+                        start,
+                        span.1,
+                        format!("cannot resolve elided member {name:?}"),
+                    )
+                })?
+            } else {
+                self.ws();
+                self.expect(':')?;
+                self.ws();
+                let id = self.shape_id()?;
+                self.resolve(&id)
+            };
+            self.ws();
+            if self.eat('=') {
+                self.ws();
+                let value = self.node_value()?;
+                traits.insert("smithy.api#default".to_string(), value);
+            }
+            let mut member = Map::new();
+            member.insert("target".to_string(), Value::String(target));
+            if !traits.is_empty() {
+                member.insert("traits".to_string(), Value::Object(traits));
+            }
+            if members.iter().any(|existing| existing.name == name) {
+                return Err(self.error(span.0, span.1, format!("duplicate member {name:?}")));
+            }
+            members.push(ParsedMember { name, span, member });
+        }
+        Ok(members)
+    }
+
+    fn elided_target(
+        &self,
+        name: &str,
+        resource: Option<&str>,
+        mixins: &[String],
+    ) -> Option<String> {
+        // On the first pass, the resource and mixins might not be defined yet.
+        let Some(defined) = self.defined else {
+            return Some(String::new());
+        };
+        resource
+            .into_iter()
+            .chain(mixins.iter().map(String::as_str))
+            .find_map(|id| defined.elidable.get(id)?.get(name))
+            .map(|target| self.resolve(target))
+    }
+
+    fn insert_members(
+        &mut self,
+        id: &str,
+        type_: &str,
+        shape: &mut Map<String, Value>,
+        members: Vec<ParsedMember>,
+    ) -> Result<()> {
+        let allowed: &[&str] = match type_ {
+            "list" | "set" => &["member"],
+            "map" => &["key", "value"],
+            _ => {
+                let targets = members
+                    .iter()
+                    .map(|ParsedMember { name, member, .. }| {
+                        let target = member["target"].as_str().unwrap_or_default();
+                        (name.clone(), target.to_string())
+                    })
+                    .collect();
+                self.defs.elidable.insert(id.to_string(), targets);
+                let members = members
+                    .into_iter()
+                    .map(|ParsedMember { name, member, .. }| (name, Value::Object(member)))
+                    .collect();
+                shape.insert("members".to_string(), Value::Object(members));
+                return Ok(());
+            }
+        };
+        for ParsedMember {
+            name,
+            span: (start, end),
+            member,
+        } in members
+        {
+            if !allowed.contains(&name.as_str()) {
+                let message = format!("{type_} shapes can only have {allowed:?} members");
+                return Err(self.error(start, end, message));
+            }
+            shape.insert(name, Value::Object(member));
+        }
+        for name in allowed {
+            if !shape.contains_key(*name) {
+                return Err(self.error_here(format!("{type_} shape is missing member {name:?}")));
+            }
+        }
+        Ok(())
+    }
+
+    fn enum_members(&mut self, int_enum: bool) -> Result<Map<String, Value>> {
+        self.ws();
+        self.expect('{')?;
+        let mut members = Map::new();
+        loop {
+            self.ws();
+            if self.eat('}') {
+                self.docs.clear();
+                break;
+            }
+            let mut traits = self.documented_trait_statements()?;
+            let start = self.pos;
+            let name = self.identifier()?.to_string();
+            let end = self.pos;
+            self.ws();
+            let value = if self.eat('=') {
+                self.ws();
+                let value_start = self.pos;
+                let value = self.node_value()?;
+                let valid = if int_enum {
+                    value.is_i64()
+                } else {
+                    value.is_string()
+                };
+                if !valid {
+                    let expected = if int_enum { "an integer" } else { "a string" };
+                    return Err(self.error(value_start, self.pos, format!("expected {expected}")));
+                }
+                value
+            } else if int_enum {
+                return Err(self.error_here("expected '=' and an integer value"));
+            } else {
+                Value::String(name.clone())
+            };
+            traits.insert("smithy.api#enumValue".to_string(), value);
+            let mut member = Map::new();
+            member.insert("target".to_string(), Value::String(UNIT.to_string()));
+            member.insert("traits".to_string(), Value::Object(traits));
+            if members
+                .insert(name.clone(), Value::Object(member))
+                .is_some()
+            {
+                return Err(self.error(start, end, format!("duplicate member {name:?}")));
+            }
+        }
+        Ok(members)
+    }
+
+    fn entity_body(&mut self, id: &str, type_: &str, shape: &mut Map<String, Value>) -> Result<()> {
+        self.expect('{')?;
+        let mut elidable = IndexMap::new();
+        loop {
+            self.ws();
+            if self.eat('}') {
+                break;
+            }
+            let key_start = self.pos;
+            let key = self.node_object_key()?;
+            let key_end = self.pos;
+            self.ws();
+            self.expect(':')?;
+            self.ws();
+            let value_start = self.pos;
+            let value = self.node_value()?;
+            let value_span = (value_start, self.pos);
+            let value = match (type_, key.as_str()) {
+                ("service", "version") => match value {
+                    Value::String(_) => value,
+                    _ => return Err(self.error(value_start, self.pos, "expected a string")),
+                },
+                ("service", "rename") => value,
+                ("service", "operations" | "resources" | "errors")
+                | ("resource", "operations" | "collectionOperations" | "resources") => {
+                    self.shape_ref_list(value, value_span)?
+                }
+                ("resource", "identifiers" | "properties") => {
+                    let Value::Object(map) = value else {
+                        return Err(self.error(value_start, self.pos, "expected an object"));
+                    };
+                    let mut refs = Map::new();
+                    for (name, target) in map {
+                        let Value::String(target) = target else {
+                            return Err(self.error(value_start, self.pos, "expected shape ids"));
+                        };
+                        elidable.insert(name.clone(), target.clone());
+                        refs.insert(name, shape_ref(target));
+                    }
+                    Value::Object(refs)
+                }
+                ("resource", "create" | "put" | "read" | "update" | "delete" | "list") => {
+                    let Value::String(target) = value else {
+                        return Err(self.error(value_start, self.pos, "expected a shape id"));
+                    };
+                    shape_ref(target)
+                }
+                _ => {
+                    let message = format!("unknown {type_} property {key:?}");
+                    return Err(self.error(key_start, key_end, message));
+                }
+            };
+            if shape.insert(key.clone(), value).is_some() {
+                return Err(self.error(key_start, key_end, format!("duplicate property {key:?}")));
+            }
+        }
+        if type_ == "resource" {
+            self.defs.elidable.insert(id.to_string(), elidable);
+        }
+        Ok(())
+    }
+
+    fn shape_ref_list(&self, value: Value, (start, end): (usize, usize)) -> Result<Value> {
+        let Value::Array(items) = value else {
+            return Err(self.error(start, end, "expected a list of shape ids"));
+        };
+        items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(target) => Ok(shape_ref(target)),
+                _ => Err(self.error(start, end, "expected a list of shape ids")),
+            })
+            .collect::<Result<_>>()
+            .map(Value::Array)
+    }
+
+    fn operation_body(&mut self, name: &str, shape: &mut Map<String, Value>) -> Result<()> {
+        self.expect('{')?;
+        loop {
+            self.ws();
+            if self.eat('}') {
+                break;
+            }
+            let key_start = self.pos;
+            let key = self.identifier()?;
+            let key_end = self.pos;
+            self.ws();
+            let value = match key {
+                "input" | "output" if self.peek_str(":=") => {
+                    self.pos += ":=".len();
+                    let (suffix, marker) = if key == "input" {
+                        (self.input_suffix.clone(), "smithy.api#input")
+                    } else {
+                        (self.output_suffix.clone(), "smithy.api#output")
+                    };
+                    let id = self.absolute_id(&format!("{name}{suffix}"));
+                    self.inline_structure(&id, marker)?;
+                    shape_ref(id)
+                }
+                "input" | "output" => {
+                    self.expect(':')?;
+                    self.ws();
+                    let id = self.shape_id()?;
+                    shape_ref(self.resolve(&id))
+                }
+                "errors" => {
+                    self.expect(':')?;
+                    self.ws();
+                    let start = self.pos;
+                    let value = self.node_value()?;
+                    self.shape_ref_list(value, (start, self.pos))?
+                }
+                _ => {
+                    let message = format!("unknown operation property {key:?}");
+                    return Err(self.error(key_start, key_end, message));
+                }
+            };
+            if shape.insert(key.to_string(), value).is_some() {
+                return Err(self.error(key_start, key_end, format!("duplicate property {key:?}")));
+            }
+        }
+        for key in ["input", "output"] {
+            shape
+                .entry(key)
+                .or_insert_with(|| shape_ref(UNIT.to_string()));
+        }
+        Ok(())
+    }
+
+    fn inline_structure(&mut self, id: &str, marker: &str) -> Result<()> {
+        let start = self.pos;
+        let mut traits = self.trait_statements()?;
+        traits.insert(marker.to_string(), Value::Object(Map::new()));
+        let resource = self.for_resource()?;
+        let mut shape = Map::new();
+        shape.insert("type".to_string(), Value::String("structure".to_string()));
+        let mixins = self.mixins(&mut shape)?;
+        let members = self.shape_members(resource.as_deref(), &mixins)?;
+        self.insert_members(id, "structure", &mut shape, members)?;
+        shape.insert("traits".to_string(), Value::Object(traits));
+        if self.shapes.contains_key(id) {
+            return Err(self.error(start, self.pos, format!("duplicate shape {id}")));
+        }
+        if let Some((_, name)) = id.split_once('#') {
+            self.defs.names.insert(name.to_string());
+        }
+        self.shapes.insert(id.to_string(), Value::Object(shape));
+        Ok(())
+    }
+
+    /// Parses any traits, adding preceding documentation comments as the documentation trait.
+    fn documented_trait_statements(&mut self) -> Result<Map<String, Value>> {
+        let mut traits = self.trait_statements()?;
+        self.ws();
+        if let Some(docs) = self.take_docs() {
+            traits
+                .entry("smithy.api#documentation")
+                .or_insert(Value::String(docs));
+        }
+        Ok(traits)
+    }
+
+    fn trait_statements(&mut self) -> Result<Map<String, Value>> {
+        let mut traits = Map::new();
+        while self.trait_statement(&mut traits)? {}
+        Ok(traits)
+    }
+
+    /// Parses a single trait into `traits`, returning whether there was one.
+    fn trait_statement(&mut self, traits: &mut Map<String, Value>) -> Result<bool> {
+        self.ws();
+        if !self.eat('@') {
+            return Ok(false);
+        }
+        let start = self.pos;
+        let id = self.shape_id()?;
+        let end = self.pos;
+        let value = if self.peek() == Some('(') {
+            self.trait_body()?
+        } else {
+            Value::Object(Map::new())
+        };
+        let id = self.resolve_trait(&id);
+        if traits.insert(id.clone(), value).is_some() {
+            return Err(self.error(start, end, format!("duplicate trait {id}")));
+        }
+        Ok(true)
+    }
+
+    fn trait_body(&mut self) -> Result<Value> {
+        self.expect('(')?;
+        self.ws();
+        if self.eat(')') {
+            return Ok(Value::Object(Map::new()));
+        }
+        let value = if self.at_structure_key() {
+            let mut object = Map::new();
+            loop {
+                self.ws();
+                if self.peek() == Some(')') {
+                    break;
+                }
+                self.object_entry(&mut object)?;
+            }
+            Value::Object(object)
+        } else {
+            self.node_value()?
+        };
+        self.ws();
+        self.expect(')')?;
+        Ok(value)
+    }
+
+    /// Whether the input is at `key:`, used to distinguish trait structures from values.
+    fn at_structure_key(&mut self) -> bool {
+        let (pos, docs) = (self.pos, self.docs.len());
+        let result = self.node_object_key().is_ok() && {
+            self.ws();
+            self.peek() == Some(':')
+        };
+        self.pos = pos;
+        self.docs.truncate(docs);
+        result
+    }
+
+    fn node_value(&mut self) -> Result<Value> {
+        match self.peek() {
+            Some('[') => {
+                self.pos += 1;
+                let mut items = vec![];
+                loop {
+                    self.ws();
+                    if self.eat(']') {
+                        break;
+                    }
+                    items.push(self.node_value()?);
+                }
+                Ok(Value::Array(items))
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut object = Map::new();
+                loop {
+                    self.ws();
+                    if self.eat('}') {
+                        break;
+                    }
+                    self.object_entry(&mut object)?;
+                }
+                Ok(Value::Object(object))
+            }
+            Some('"') => self.string().map(Value::String),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) if is_identifier_start(c) => {
+                let id = self.shape_id()?;
+                Ok(match id.as_str() {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Null,
+                    _ => Value::String(self.resolve(&id)),
+                })
+            }
+            _ => Err(self.error_here("expected a value")),
+        }
+    }
+
+    fn object_entry(&mut self, object: &mut Map<String, Value>) -> Result<()> {
+        let start = self.pos;
+        let key = self.node_object_key()?;
+        let end = self.pos;
+        self.ws();
+        self.expect(':')?;
+        self.ws();
+        let value = self.node_value()?;
+        if object.insert(key.clone(), value).is_some() {
+            return Err(self.error(start, end, format!("duplicate key {key:?}")));
+        }
+        Ok(())
+    }
+
+    fn node_object_key(&mut self) -> Result<String> {
+        if self.peek() == Some('"') {
+            self.string()
+        } else {
+            self.identifier().map(str::to_string)
+        }
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let start = self.pos;
+        let bytes = self.source.as_bytes();
+        let digits = |pos: &mut usize| {
+            let start = *pos;
+            while bytes.get(*pos).is_some_and(u8::is_ascii_digit) {
+                *pos += 1;
+            }
+            *pos > start
+        };
+        let mut pos = self.pos;
+        if bytes[pos] == b'-' {
+            pos += 1;
+        }
+        let mut valid = digits(&mut pos);
+        let mut integer = true;
+        if bytes.get(pos) == Some(&b'.') {
+            pos += 1;
+            integer = false;
+            valid &= digits(&mut pos);
+        }
+        if matches!(bytes.get(pos), Some(b'e' | b'E')) {
+            pos += 1;
+            integer = false;
+            if matches!(bytes.get(pos), Some(b'+' | b'-')) {
+                pos += 1;
+            }
+            valid &= digits(&mut pos);
+        }
+        self.pos = pos;
+        let text = &self.source[start..pos];
+        let value = if !valid {
+            None
+        } else if integer {
+            // Like the JSON AST, integers too large for i64 become u64 or f64.
+            text.parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| serde_json::from_str(text))
+                .ok()
+        } else {
+            text.parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+        };
+        value.ok_or_else(|| self.error(start, pos, "invalid number"))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.pos;
+        if self.eat_str("\"\"\"") {
+            return self.text_block(start);
+        }
+        self.expect('"')?;
+        let content_start = self.pos;
+        let content_end = self.skip_string_content(start, "\"")?;
+        unescape(&self.source[content_start..content_end])
+            .map_err(|message| self.error(start, self.pos, message))
+    }
+
+    fn text_block(&mut self, start: usize) -> Result<String> {
+        let line_end = self.source[self.pos..].find('\n').map(|i| self.pos + i);
+        if line_end.is_none_or(|end| !self.source[self.pos..end].trim().is_empty()) {
+            return Err(self.error_here("expected a new line after text block opening"));
+        }
+        self.pos = line_end.unwrap_or(self.pos) + 1;
+        let content_start = self.pos;
+        let content_end = self.skip_string_content(start, "\"\"\"")?;
+        let content = &self.source[content_start..content_end];
+
+        // Strip incidental whitespace: the smallest indentation of all non-blank lines and the
+        // line with the closing delimiter.
+        let lines = content.split('\n').collect::<Vec<_>>();
+        let indent = lines
+            .iter()
+            .enumerate()
+            .filter(|(i, line)| *i == lines.len() - 1 || !line.trim().is_empty())
+            .map(|(_, line)| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let text = lines
+            .iter()
+            .map(|line| line.get(indent..).unwrap_or("").trim_end())
+            .collect::<Vec<_>>()
+            .join("\n");
+        unescape(&text).map_err(|message| self.error(start, self.pos, message))
+    }
+
+    /// Skips to after the closing delimiter, returning the end offset of the content.
+    fn skip_string_content(&mut self, start: usize, delimiter: &str) -> Result<usize> {
+        loop {
+            let rest = &self.source[self.pos..];
+            if rest.starts_with(delimiter) {
+                let end = self.pos;
+                self.pos += delimiter.len();
+                return Ok(end);
+            }
+            let mut chars = rest.chars();
+            match chars.next() {
+                None => return Err(self.error(start, self.pos, "unterminated string")),
+                Some('\\') => {
+                    self.pos += 1;
+                    self.pos += chars.next().map_or(0, char::len_utf8);
+                }
+                Some(c) => self.pos += c.len_utf8(),
+            }
+        }
+    }
+
+    fn namespace(&mut self) -> Result<String> {
+        let mut namespace = self.identifier()?.to_string();
+        while self.peek() == Some('.') {
+            self.pos += 1;
+            namespace.push('.');
+            namespace.push_str(self.identifier()?);
+        }
+        Ok(namespace)
+    }
+
+    /// Parses an absolute or relative shape id, possibly with a member, without resolving it.
+    fn shape_id(&mut self) -> Result<String> {
+        let mut id = self.namespace()?;
+        if self.eat('#') {
+            id.push('#');
+            id.push_str(self.identifier()?);
+        }
+        if self.eat('$') {
+            id.push('$');
+            id.push_str(self.identifier()?);
+        }
+        Ok(id)
+    }
+
+    fn identifier(&mut self) -> Result<&'a str> {
+        let source = self.source;
+        let rest = &source[self.pos..];
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| {
+                if i == 0 {
+                    !is_identifier_start(c)
+                } else {
+                    !(c.is_ascii_alphanumeric() || c == '_')
+                }
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        // identifiers can't be only underscores
+        if rest[..len].bytes().all(|b| b == b'_') {
+            return Err(self.error_here("expected an identifier"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn absolute_id(&self, name: &str) -> String {
+        format!("{}#{name}", self.namespace.as_deref().unwrap_or_default())
+    }
+
+    fn resolve(&self, id: &str) -> String {
+        self.resolve_with(id, prelude::contains_name)
+    }
+
+    fn resolve_trait(&self, id: &str) -> String {
+        // Trait definitions in the prelude aren't tracked, so any trait that isn't imported or
+        // defined in this file is assumed to be a prelude trait.
+        self.resolve_with(id, |_| true)
+    }
+
+    fn resolve_with(&self, id: &str, is_prelude: impl Fn(&str) -> bool) -> String {
+        let Some(defined) = self.defined else {
+            return id.to_string();
+        };
+        let namespace = self.namespace.as_deref().unwrap_or_default();
+        let is_prelude = |name: &str| !defined.names.contains(name) && is_prelude(name);
+        // Invalid ids are left as they are, to be reported when the model is loaded.
+        match ShapeId::resolve(id, namespace, &self.uses, is_prelude) {
+            Ok(id) => id.to_string(),
+            Err(_) => id.to_string(),
+        }
+    }
+
+    /// Skips whitespace, commas and comments, collecting documentation comments.
+    fn ws(&mut self) {
+        loop {
+            let rest = &self.source[self.pos..];
+            let trimmed = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+            self.pos += rest.len() - trimmed.len();
+            if !trimmed.starts_with("//") {
+                break;
+            }
+            let line = trimmed.split('\n').next().unwrap_or_default();
+            self.pos += line.len();
+            if let Some(doc) = line.strip_prefix("///") {
+                let doc = doc.trim_end_matches('\r');
+                self.docs
+                    .push(doc.strip_prefix(' ').unwrap_or(doc).to_string());
+            }
+        }
+    }
+
+    fn take_docs(&mut self) -> Option<String> {
+        if self.docs.is_empty() {
+            return None;
+        }
+        let docs = self.docs.join("\n");
+        self.docs.clear();
+        Some(docs)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let result = self.peek() == Some(c);
+        if result {
+            self.pos += c.len_utf8();
+        }
+        result
+    }
+
+    fn peek_str(&self, s: &str) -> bool {
+        self.source[self.pos..].starts_with(s)
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let result = self.peek_str(s);
+        if result {
+            self.pos += s.len();
+        }
+        result
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let rest = &self.source[self.pos..];
+        let result = rest.starts_with(keyword)
+            && !rest[keyword.len()..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '#');
+        if result {
+            self.pos += keyword.len();
+        }
+        result
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error_here(format!("expected {c:?}")))
+        }
+    }
+
+    fn error_here(&self, message: impl Into<String>) -> IdlError {
+        let len = self.peek().map_or(0, char::len_utf8);
+        self.error(self.pos, self.pos + len, message)
+    }
+
+    fn error(&self, start: usize, end: usize, message: impl Into<String>) -> IdlError {
+        IdlError::Syntax {
+            message: message.into(),
+            source_code: NamedSource::new(self.name, self.source.to_string()),
+            span: (start, end - start).into(),
+        }
+    }
+}
+
+struct ParsedMember {
+    name: String,
+    span: (usize, usize),
+    member: Map<String, Value>,
+}
+
+struct Apply {
+    id: String,
+    span: (usize, usize),
+    traits: Map<String, Value>,
+}
+
+fn shape_ref(target: String) -> Value {
+    let mut value = Map::new();
+    value.insert("target".to_string(), Value::String(target));
+    Value::Object(value)
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => result.push('"'),
+            Some('\\') => result.push('\\'),
+            Some('/') => result.push('/'),
+            Some('b') => result.push('\u{8}'),
+            Some('f') => result.push('\u{c}'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            // escaped line break
+            Some('\n') => {}
+            Some('u') => {
+                let hex = chars.by_ref().take(4).collect::<String>();
+                let c = u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 4)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid unicode escape \\u{hex}"))?;
+                result.push(c);
+            }
+            Some(c) => return Err(format!("invalid escape \\{c}")),
+            None => return Err("unterminated escape".to_string()),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn lowers_shapes() -> Result<(), IdlError> {
+        let ast = idl_to_json_ast(
+            "test.smithy",
+            r#"
+$version: "2"
+$operationOutputSuffix: "Response"
+
+metadata suppressions = [{ id: "Foo", namespace: "*" }]
+
+namespace example.weather
+
+use aws.api#service
+
+/// Provides weather forecasts.
+@service(sdkId: "Weather")
+@title("Weather Service")
+service Weather {
+    version: "2006-03-01"
+    operations: [GetForecast]
+}
+
+@readonly
+operation GetForecast {
+    input := {
+        @required
+        city: CityId
+
+        days: Integer = 1
+    }
+    output := {
+        /// The forecast.
+        forecast: Forecast
+    }
+    errors: [NoSuchCity]
+}
+
+@pattern("^[A-Za-z0-9 ]+$")
+string CityId
+
+list Forecast {
+    member: Conditions
+}
+
+enum Conditions {
+    SUNNY
+    RAINY = "rain"
+}
+
+@error("client")
+structure NoSuchCity {
+    message: String
+}
+
+apply NoSuchCity$message @documentation("""
+    The error message.
+      Indented.
+    """)
+"#,
+        )?;
+
+        assert_eq!(ast["smithy"], json!("2.0"));
+        assert_eq!(
+            ast["metadata"],
+            json!({ "suppressions": [{ "id": "Foo", "namespace": "*" }] }),
+        );
+        let shapes = &ast["shapes"];
+        assert_eq!(
+            shapes["example.weather#Weather"],
+            json!({
+                "type": "service",
+                "version": "2006-03-01",
+                "operations": [{ "target": "example.weather#GetForecast" }],
+                "traits": {
+                    "aws.api#service": { "sdkId": "Weather" },
+                    "smithy.api#title": "Weather Service",
+                    "smithy.api#documentation": "Provides weather forecasts.",
+                },
+            }),
+        );
+        assert_eq!(
+            shapes["example.weather#GetForecast"],
+            json!({
+                "type": "operation",
+                "input": { "target": "example.weather#GetForecastInput" },
+                "output": { "target": "example.weather#GetForecastResponse" },
+                "errors": [{ "target": "example.weather#NoSuchCity" }],
+                "traits": { "smithy.api#readonly": {} },
+            }),
+        );
+        assert_eq!(
+            shapes["example.weather#GetForecastInput"],
+            json!({
+                "type": "structure",
+                "members": {
+                    "city": {
+                        "target": "example.weather#CityId",
+                        "traits": { "smithy.api#required": {} },
+                    },
+                    "days": {
+                        "target": "smithy.api#Integer",
+                        "traits": { "smithy.api#default": 1 },
+                    },
+                },
+                "traits": { "smithy.api#input": {} },
+            }),
+        );
+        assert_eq!(
+            shapes["example.weather#GetForecastResponse"]["members"]["forecast"],
+            json!({
+                "target": "example.weather#Forecast",
+                "traits": { "smithy.api#documentation": "The forecast." },
+            }),
+        );
+        assert_eq!(
+            shapes["example.weather#Forecast"],
+            json!({ "type": "list", "member": { "target": "example.weather#Conditions" } }),
+        );
+        assert_eq!(
+            shapes["example.weather#Conditions"]["members"]["RAINY"],
+            json!({
+                "target": "smithy.api#Unit",
+                "traits": { "smithy.api#enumValue": "rain" },
+            }),
+        );
+        assert_eq!(
+            shapes["example.weather#NoSuchCity"]["members"]["message"]["traits"],
+            json!({ "smithy.api#documentation": "The error message.\n  Indented.\n" }),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_elided_members() -> Result<(), IdlError> {
+        let ast = idl_to_json_ast(
+            "test.smithy",
+            r#"
+$version: "2.0"
+namespace example
+
+structure GetCityInput for City {
+    $cityId
+}
+
+resource City {
+    identifiers: { cityId: CityId }
+}
+
+string CityId
+
+@mixin
+structure CityMixin {
+    cityId: CityId
+    name: String
+}
+
+structure UpdateCityInput with [CityMixin] {
+    $cityId
+}
+
+apply UpdateCityInput$name @required
+"#,
+        )?;
+        let shapes = &ast["shapes"];
+        assert_eq!(
+            shapes["example#GetCityInput"]["members"]["cityId"],
+            json!({ "target": "example#CityId" }),
+        );
+        assert_eq!(
+            shapes["example#UpdateCityInput"]["members"],
+            json!({
+                "cityId": { "target": "example#CityId" },
+                "name": {
+                    "target": "smithy.api#String",
+                    "traits": { "smithy.api#required": {} },
+                },
+            }),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parses_model() -> Result<(), IdlError> {
+        let model = parse_idl(
+            "test.smithy",
+            r#"
+$version: "2"
+namespace example
+
+operation Ping {
+    output := {
+        @required
+        time: Timestamp
+    }
+}
+"#,
+        )?;
+        let shape_id = |name: &str| ShapeId {
+            namespace: "example".to_string(),
+            name: name.to_string(),
+            member: None,
+        };
+        let Some(crate::Shape::Operation(operation)) = model.shapes.get(&shape_id("Ping")) else {
+            panic!("expected Ping operation");
+        };
+        assert_eq!(operation.input.target.to_string(), "smithy.api#Unit");
+        assert_eq!(operation.output.target, shape_id("PingOutput"));
+        let Some(crate::Shape::Structure(output)) = model.shapes.get(&shape_id("PingOutput"))
+        else {
+            panic!("expected PingOutput structure");
+        };
+        assert!(output.traits.output.is_some());
+        assert!(output.members["time"].traits.required.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn apply_without_braces_takes_one_trait() -> Result<(), IdlError> {
+        let ast = idl_to_json_ast(
+            "test.smithy",
+            r#"
+$version: "2"
+namespace example
+
+string Foo
+
+apply Foo @documentation("x")
+
+@sensitive
+string Bar
+"#,
+        )?;
+        let shapes = &ast["shapes"];
+        assert_eq!(
+            shapes["example#Foo"]["traits"],
+            json!({ "smithy.api#documentation": "x" }),
+        );
+        assert_eq!(
+            shapes["example#Bar"]["traits"],
+            json!({ "smithy.api#sensitive": {} }),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parses_large_numbers() -> Result<(), IdlError> {
+        let ast = idl_to_json_ast(
+            "test.smithy",
+            r#"
+$version: "2"
+namespace example
+
+@range(min: -9223372036854775808, max: 18446744073709551615)
+bigInteger Huge
+
+structure Thing {
+    huge: Huge = 100000000000000000000
+}
+"#,
+        )?;
+        let shapes = &ast["shapes"];
+        let range = &shapes["example#Huge"]["traits"]["smithy.api#range"];
+        assert_eq!(range["min"], json!(i64::MIN));
+        assert_eq!(range["max"], json!(u64::MAX));
+        let huge = &shapes["example#Thing"]["members"]["huge"];
+        assert_eq!(huge["traits"]["smithy.api#default"], json!(1e20));
+        Ok(())
+    }
+
+    #[test]
+    fn syntax_error_span() {
+        let source = "$version: \"2\"\nnamespace example\n\nstructure Foo {\n    bar String\n}\n";
+        let Err(IdlError::Syntax { message, span, .. }) = idl_to_json_ast("test.smithy", source)
+        else {
+            panic!("expected a syntax error");
+        };
+        assert_eq!(message, "expected ':'");
+        assert_eq!(span.offset(), source.find("String").unwrap());
+    }
+}
@@ -0,0 +1,1537 @@
+//! JMESPath expressions, as used by waiter matchers and paginator paths.
+//!
+//! See <https://jmespath.org/specification.html>.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use indexmap::IndexMap;
+use miette::SourceSpan;
+use serde_json::{Map, Value};
+
+use crate::{Model, Shape, ShapeId};
+
+/// A parsed JMESPath expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// `@`, and the implicit right hand side of a projection.
+    Identity,
+    /// `foo` or `"foo"`.
+    Field(String),
+    /// The `[0]` in `foo[0]`, which may be negative to count from the end.
+    Index(i64),
+    /// The `[start:stop:step]` in `foo[start:stop:step]`, which is always projected.
+    Slice {
+        start: Option<i64>,
+        stop: Option<i64>,
+        step: Option<i64>,
+    },
+    /// `lhs.rhs`, and `lhs[0]`.
+    Subexpression(Box<Expr>, Box<Expr>),
+    /// `lhs[*].rhs`, `lhs[].rhs` and `lhs[1:].rhs`: `rhs` for each element of the array.
+    ListProjection(Box<Expr>, Box<Expr>),
+    /// `lhs.*.rhs`: `rhs` for each value of the object.
+    ValueProjection(Box<Expr>, Box<Expr>),
+    /// `lhs[?condition].rhs`: `rhs` for each element of the array that matches the condition.
+    FilterProjection {
+        lhs: Box<Expr>,
+        condition: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    /// The `[]` in `lhs[]`, which flattens nested arrays by one level.
+    Flatten(Box<Expr>),
+    /// `[a, b]`.
+    MultiSelectList(Vec<Expr>),
+    /// `{a: a, b: b}`.
+    MultiSelectHash(Vec<(String, Expr)>),
+    /// `` `{"a": 1}` `` or `'raw string'`.
+    Literal(Value),
+    Comparison(Comparator, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    /// `lhs | rhs`, which stops any projection in `lhs`.
+    Pipe(Box<Expr>, Box<Expr>),
+    /// `name(args)`.
+    Function(String, Vec<Expr>),
+    /// `&expr`, which can only be passed to functions.
+    Expref(Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("{message}")]
+#[diagnostic(code(laws_schema::jmespath::syntax))]
+pub struct SyntaxError {
+    pub message: String,
+    #[source_code]
+    expression: String,
+    #[label]
+    span: SourceSpan,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("{message}")]
+#[diagnostic(code(laws_schema::jmespath::evaluation))]
+pub struct EvaluationError {
+    pub message: String,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("{message}")]
+#[diagnostic(code(laws_schema::jmespath::type_check))]
+pub struct TypeError {
+    pub message: String,
+}
+
+/// Parses a JMESPath expression.
+pub fn parse(expression: &str) -> Result<Expr, SyntaxError> {
+    let mut parser = Parser {
+        expression,
+        tokens: tokenize(expression)?,
+        position: 0,
+    };
+    let expr = parser.expression(0)?;
+    match parser.peek(0) {
+        Token::Eof => Ok(expr),
+        token => Err(parser.error_here(format!("unexpected {token}"))),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dot,
+    Star,
+    Flatten,
+    Filter,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+    Current,
+    Expref,
+    And,
+    Or,
+    Pipe,
+    Not,
+    Comparator(Comparator),
+    Number(i64),
+    UnquotedIdentifier(String),
+    QuotedIdentifier(String),
+    Literal(Value),
+    Eof,
+}
+
+impl Token {
+    /// How tightly the token binds to the expression on its left, from the specification's
+    /// reference implementation.
+    fn binding_power(&self) -> u8 {
+        match self {
+            Token::Pipe => 1,
+            Token::Or => 2,
+            Token::And => 3,
+            Token::Comparator(_) => 5,
+            Token::Flatten => 9,
+            Token::Star => 20,
+            Token::Filter => 21,
+            Token::Dot => 40,
+            Token::Not => 45,
+            Token::LBrace => 50,
+            Token::LBracket => 55,
+            Token::LParen => 60,
+            _ => 0,
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Token::Dot => "`.`",
+            Token::Star => "`*`",
+            Token::Flatten => "`[]`",
+            Token::Filter => "`[?`",
+            Token::LBracket => "`[`",
+            Token::RBracket => "`]`",
+            Token::LBrace => "`{`",
+            Token::RBrace => "`}`",
+            Token::LParen => "`(`",
+            Token::RParen => "`)`",
+            Token::Comma => "`,`",
+            Token::Colon => "`:`",
+            Token::Current => "`@`",
+            Token::Expref => "`&`",
+            Token::And => "`&&`",
+            Token::Or => "`||`",
+            Token::Pipe => "`|`",
+            Token::Not => "`!`",
+            Token::Comparator(_) => "comparison",
+            Token::Number(number) => return write!(f, "number {number}"),
+            Token::UnquotedIdentifier(name) | Token::QuotedIdentifier(name) => {
+                return write!(f, "identifier {name:?}")
+            }
+            Token::Literal(_) => "literal",
+            Token::Eof => "end of expression",
+        };
+        f.write_str(symbol)
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<(Token, SourceSpan)>, SyntaxError> {
+    let error = |message: String, start: usize, end: usize| SyntaxError {
+        message,
+        expression: expression.to_string(),
+        span: (start, end - start).into(),
+    };
+    let mut tokens = vec![];
+    let mut chars = expression.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            ' ' | '\t' | '\n' | '\r' => continue,
+            '.' => Token::Dot,
+            '*' => Token::Star,
+            ']' => Token::RBracket,
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            '@' => Token::Current,
+            '[' if next_is(&mut chars, ']') => Token::Flatten,
+            '[' if next_is(&mut chars, '?') => Token::Filter,
+            '[' => Token::LBracket,
+            '&' if next_is(&mut chars, '&') => Token::And,
+            '&' => Token::Expref,
+            '|' if next_is(&mut chars, '|') => Token::Or,
+            '|' => Token::Pipe,
+            '!' if next_is(&mut chars, '=') => Token::Comparator(Comparator::Ne),
+            '!' => Token::Not,
+            '=' if next_is(&mut chars, '=') => Token::Comparator(Comparator::Eq),
+            '<' if next_is(&mut chars, '=') => Token::Comparator(Comparator::Le),
+            '<' => Token::Comparator(Comparator::Lt),
+            '>' if next_is(&mut chars, '=') => Token::Comparator(Comparator::Ge),
+            '>' => Token::Comparator(Comparator::Gt),
+            '-' | '0'..='9' => {
+                while chars.next_if(|(_, c)| c.is_ascii_digit()).is_some() {}
+                let end = chars.peek().map_or(expression.len(), |&(end, _)| end);
+                let number = expression[start..end]
+                    .parse()
+                    .map_err(|_| error("invalid number".to_string(), start, end))?;
+                Token::Number(number)
+            }
+            'a'..='z' | 'A'..='Z' | '_' => {
+                while chars
+                    .next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                    .is_some()
+                {}
+                let end = chars.peek().map_or(expression.len(), |&(end, _)| end);
+                Token::UnquotedIdentifier(expression[start..end].to_string())
+            }
+            '"' | '\'' | '`' => {
+                let Some(end) = closing(&mut chars, c) else {
+                    let message = format!("unclosed {c}");
+                    return Err(error(message, start, expression.len()));
+                };
+                let content = &expression[start + 1..end];
+                match c {
+                    '"' => serde_json::from_str(&expression[start..=end])
+                        .map(Token::QuotedIdentifier)
+                        .map_err(|e| error(format!("invalid identifier: {e}"), start, end + 1))?,
+                    '\'' => Token::Literal(Value::String(content.replace("\\'", "'"))),
+                    _ => serde_json::from_str(&content.replace("\\`", "`"))
+                        .map(Token::Literal)
+                        .map_err(|e| error(format!("invalid literal: {e}"), start, end + 1))?,
+                }
+            }
+            _ => {
+                let end = start + c.len_utf8();
+                return Err(error(format!("unexpected character {c:?}"), start, end));
+            }
+        };
+        let end = chars.peek().map_or(expression.len(), |&(end, _)| end);
+        tokens.push((token, (start, end - start).into()));
+    }
+    tokens.push((Token::Eof, (expression.len(), 0).into()));
+    Ok(tokens)
+}
+
+fn next_is(chars: &mut Peekable<CharIndices>, expected: char) -> bool {
+    chars.next_if(|&(_, c)| c == expected).is_some()
+}
+
+/// Finds the closing delimiter, skipping escaped characters.
+fn closing(chars: &mut Peekable<CharIndices>, delimiter: char) -> Option<usize> {
+    while let Some((index, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == delimiter {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// A Pratt parser, following the reference implementation's binding powers.
+struct Parser<'a> {
+    expression: &'a str,
+    tokens: Vec<(Token, SourceSpan)>,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self, offset: usize) -> &Token {
+        let index = (self.position + offset).min(self.tokens.len() - 1);
+        &self.tokens[index].0
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].0.clone();
+        if self.position < self.tokens.len() - 1 {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn error_at(&self, index: usize, message: String) -> SyntaxError {
+        SyntaxError {
+            message,
+            expression: self.expression.to_string(),
+            span: self.tokens[index].1,
+        }
+    }
+
+    fn error_here(&self, message: String) -> SyntaxError {
+        self.error_at(self.position, message)
+    }
+
+    /// An error for the token that was just advanced past.
+    fn error_previous(&self, message: String) -> SyntaxError {
+        self.error_at(self.position.saturating_sub(1), message)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), SyntaxError> {
+        if *self.peek(0) != expected {
+            let message = format!("expected {expected}, found {}", self.peek(0));
+            return Err(self.error_here(message));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn expression(&mut self, binding_power: u8) -> Result<Expr, SyntaxError> {
+        let token = self.advance();
+        let mut left = self.nud(token)?;
+        while binding_power < self.peek(0).binding_power() {
+            let token = self.advance();
+            left = self.led(token, left)?;
+        }
+        Ok(left)
+    }
+
+    /// Parses a token that starts an expression.
+    fn nud(&mut self, token: Token) -> Result<Expr, SyntaxError> {
+        Ok(match token {
+            Token::Literal(value) => Expr::Literal(value),
+            Token::UnquotedIdentifier(name) => Expr::Field(name),
+            Token::QuotedIdentifier(name) => {
+                if *self.peek(0) == Token::LParen {
+                    let message = "a quoted identifier can't be a function name".to_string();
+                    return Err(self.error_previous(message));
+                }
+                Expr::Field(name)
+            }
+            Token::Star => {
+                let rhs = self.projection_rhs(Token::Star.binding_power())?;
+                Expr::ValueProjection(Box::new(Expr::Identity), Box::new(rhs))
+            }
+            Token::Filter => self.filter(Expr::Identity)?,
+            Token::Flatten => {
+                let rhs = self.projection_rhs(Token::Flatten.binding_power())?;
+                let lhs = Expr::Flatten(Box::new(Expr::Identity));
+                Expr::ListProjection(Box::new(lhs), Box::new(rhs))
+            }
+            Token::LBracket => match (self.peek(0), self.peek(1)) {
+                (Token::Number(_) | Token::Colon, _) => {
+                    let index = self.index()?;
+                    self.project_if_slice(Expr::Identity, index)?
+                }
+                (Token::Star, Token::RBracket) => {
+                    self.advance();
+                    self.advance();
+                    let rhs = self.projection_rhs(Token::Star.binding_power())?;
+                    Expr::ListProjection(Box::new(Expr::Identity), Box::new(rhs))
+                }
+                _ => self.multi_select_list()?,
+            },
+            Token::LBrace => self.multi_select_hash()?,
+            Token::Current => Expr::Identity,
+            Token::Expref => Expr::Expref(Box::new(self.expression(0)?)),
+            Token::Not => Expr::Not(Box::new(self.expression(Token::Not.binding_power())?)),
+            Token::LParen => {
+                let expr = self.expression(0)?;
+                self.expect(Token::RParen)?;
+                expr
+            }
+            token => return Err(self.error_previous(format!("unexpected {token}"))),
+        })
+    }
+
+    /// Parses a token that continues the expression on its left.
+    fn led(&mut self, token: Token, left: Expr) -> Result<Expr, SyntaxError> {
+        let binding_power = token.binding_power();
+        let left = Box::new(left);
+        Ok(match token {
+            Token::Dot if *self.peek(0) == Token::Star => {
+                self.advance();
+                Expr::ValueProjection(left, Box::new(self.projection_rhs(binding_power)?))
+            }
+            Token::Dot => Expr::Subexpression(left, Box::new(self.dot_rhs(binding_power)?)),
+            Token::Pipe => Expr::Pipe(left, Box::new(self.expression(binding_power)?)),
+            Token::Or => Expr::Or(left, Box::new(self.expression(binding_power)?)),
+            Token::And => Expr::And(left, Box::new(self.expression(binding_power)?)),
+            Token::Comparator(comparator) => {
+                Expr::Comparison(comparator, left, Box::new(self.expression(binding_power)?))
+            }
+            Token::LParen => {
+                let Expr::Field(name) = *left else {
+                    let message = "only a name can be called as a function".to_string();
+                    return Err(self.error_previous(message));
+                };
+                Expr::Function(name, self.arguments()?)
+            }
+            Token::Filter => self.filter(*left)?,
+            Token::Flatten => {
+                let rhs = self.projection_rhs(binding_power)?;
+                Expr::ListProjection(Box::new(Expr::Flatten(left)), Box::new(rhs))
+            }
+            Token::LBracket if matches!(self.peek(0), Token::Number(_) | Token::Colon) => {
+                let index = self.index()?;
+                self.project_if_slice(*left, index)?
+            }
+            Token::LBracket => {
+                self.expect(Token::Star)?;
+                self.expect(Token::RBracket)?;
+                let rhs = self.projection_rhs(Token::Star.binding_power())?;
+                Expr::ListProjection(left, Box::new(rhs))
+            }
+            token => return Err(self.error_previous(format!("unexpected {token}"))),
+        })
+    }
+
+    /// Parses an index or slice, after its `[`.
+    fn index(&mut self) -> Result<Expr, SyntaxError> {
+        if *self.peek(0) == Token::Colon || *self.peek(1) == Token::Colon {
+            return self.slice();
+        }
+        let Token::Number(index) = self.advance() else {
+            return Err(self.error_previous("expected an index".to_string()));
+        };
+        self.expect(Token::RBracket)?;
+        Ok(Expr::Index(index))
+    }
+
+    fn slice(&mut self) -> Result<Expr, SyntaxError> {
+        let mut parts = [None; 3];
+        let mut part = 0;
+        loop {
+            match self.advance() {
+                Token::RBracket => break,
+                Token::Colon if part < 2 => part += 1,
+                Token::Number(number) if parts[part].is_none() => parts[part] = Some(number),
+                token => return Err(self.error_previous(format!("unexpected {token} in slice"))),
+            }
+        }
+        let [start, stop, step] = parts;
+        if step == Some(0) {
+            return Err(self.error_previous("slice step can't be 0".to_string()));
+        }
+        Ok(Expr::Slice { start, stop, step })
+    }
+
+    fn project_if_slice(&mut self, left: Expr, index: Expr) -> Result<Expr, SyntaxError> {
+        let is_slice = matches!(index, Expr::Slice { .. });
+        let expr = Expr::Subexpression(Box::new(left), Box::new(index));
+        if !is_slice {
+            return Ok(expr);
+        }
+        let rhs = self.projection_rhs(Token::Star.binding_power())?;
+        Ok(Expr::ListProjection(Box::new(expr), Box::new(rhs)))
+    }
+
+    /// Parses what a projection applies to each element, which continues until a token that
+    /// stops projections, such as `|`.
+    fn projection_rhs(&mut self, binding_power: u8) -> Result<Expr, SyntaxError> {
+        match self.peek(0) {
+            token if token.binding_power() < 10 => Ok(Expr::Identity),
+            Token::LBracket | Token::Filter => self.expression(binding_power),
+            Token::Dot => {
+                self.advance();
+                self.dot_rhs(binding_power)
+            }
+            token => Err(self.error_here(format!("unexpected {token} after projection"))),
+        }
+    }
+
+    fn dot_rhs(&mut self, binding_power: u8) -> Result<Expr, SyntaxError> {
+        match self.peek(0) {
+            Token::UnquotedIdentifier(_) | Token::QuotedIdentifier(_) | Token::Star => {
+                self.expression(binding_power)
+            }
+            Token::LBracket => {
+                self.advance();
+                self.multi_select_list()
+            }
+            Token::LBrace => {
+                self.advance();
+                self.multi_select_hash()
+            }
+            token => Err(self.error_here(format!("unexpected {token} after `.`"))),
+        }
+    }
+
+    /// Parses a filter projection, after its `[?`.
+    fn filter(&mut self, lhs: Expr) -> Result<Expr, SyntaxError> {
+        let condition = self.expression(0)?;
+        self.expect(Token::RBracket)?;
+        let rhs = if *self.peek(0) == Token::Flatten {
+            Expr::Identity
+        } else {
+            self.projection_rhs(Token::Filter.binding_power())?
+        };
+        Ok(Expr::FilterProjection {
+            lhs: Box::new(lhs),
+            condition: Box::new(condition),
+            rhs: Box::new(rhs),
+        })
+    }
+
+    /// Parses a multi-select list, after its `[`.
+    fn multi_select_list(&mut self) -> Result<Expr, SyntaxError> {
+        let mut items = vec![];
+        loop {
+            items.push(self.expression(0)?);
+            match self.advance() {
+                Token::Comma => {}
+                Token::RBracket => return Ok(Expr::MultiSelectList(items)),
+                token => return Err(self.error_previous(format!("unexpected {token} in list"))),
+            }
+        }
+    }
+
+    /// Parses a multi-select hash, after its `{`.
+    fn multi_select_hash(&mut self) -> Result<Expr, SyntaxError> {
+        let mut pairs = vec![];
+        loop {
+            let (Token::UnquotedIdentifier(key) | Token::QuotedIdentifier(key)) = self.advance()
+            else {
+                return Err(self.error_previous("expected a key".to_string()));
+            };
+            self.expect(Token::Colon)?;
+            pairs.push((key, self.expression(0)?));
+            match self.advance() {
+                Token::Comma => {}
+                Token::RBrace => return Ok(Expr::MultiSelectHash(pairs)),
+                token => return Err(self.error_previous(format!("unexpected {token} in hash"))),
+            }
+        }
+    }
+
+    /// Parses function arguments, after the `(`.
+    fn arguments(&mut self) -> Result<Vec<Expr>, SyntaxError> {
+        let mut arguments = vec![];
+        if *self.peek(0) == Token::RParen {
+            self.advance();
+            return Ok(arguments);
+        }
+        loop {
+            arguments.push(self.expression(0)?);
+            match self.advance() {
+                Token::Comma => {}
+                Token::RParen => return Ok(arguments),
+                token => {
+                    return Err(self.error_previous(format!("unexpected {token} in arguments")))
+                }
+            }
+        }
+    }
+}
+
+impl Expr {
+    /// Evaluates the expression against a JSON value.
+    pub fn evaluate(&self, value: &Value) -> Result<Value, EvaluationError> {
+        Ok(match self {
+            Expr::Identity => value.clone(),
+            Expr::Field(name) => value.get(name).cloned().unwrap_or(Value::Null),
+            Expr::Index(index) => {
+                let Value::Array(items) = value else {
+                    return Ok(Value::Null);
+                };
+                let index = if *index < 0 {
+                    items.len() as i64 + index
+                } else {
+                    *index
+                };
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|index| items.get(index))
+                    .cloned()
+                    .unwrap_or(Value::Null)
+            }
+            Expr::Slice { start, stop, step } => match value {
+                Value::Array(items) => Value::Array(slice(items, *start, *stop, *step)),
+                _ => Value::Null,
+            },
+            Expr::Subexpression(lhs, rhs) | Expr::Pipe(lhs, rhs) => {
+                rhs.evaluate(&lhs.evaluate(value)?)?
+            }
+            Expr::ListProjection(lhs, rhs) => match lhs.evaluate(value)? {
+                Value::Array(items) => project(&items, rhs)?,
+                _ => Value::Null,
+            },
+            Expr::ValueProjection(lhs, rhs) => match lhs.evaluate(value)? {
+                Value::Object(object) => project(object.values(), rhs)?,
+                _ => Value::Null,
+            },
+            Expr::FilterProjection {
+                lhs,
+                condition,
+                rhs,
+            } => {
+                let Value::Array(items) = lhs.evaluate(value)? else {
+                    return Ok(Value::Null);
+                };
+                let mut matching = vec![];
+                for item in &items {
+                    if is_truthy(&condition.evaluate(item)?) {
+                        matching.push(item);
+                    }
+                }
+                project(matching, rhs)?
+            }
+            Expr::Flatten(expr) => match expr.evaluate(value)? {
+                Value::Array(items) => Value::Array(
+                    items
+                        .into_iter()
+                        .flat_map(|item| match item {
+                            Value::Array(items) => items,
+                            item => vec![item],
+                        })
+                        .collect(),
+                ),
+                _ => Value::Null,
+            },
+            Expr::MultiSelectList(_) | Expr::MultiSelectHash(_) if value.is_null() => Value::Null,
+            Expr::MultiSelectList(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| item.evaluate(value))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Expr::MultiSelectHash(pairs) => Value::Object(
+                pairs
+                    .iter()
+                    .map(|(key, expr)| Ok((key.clone(), expr.evaluate(value)?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Expr::Literal(literal) => literal.clone(),
+            Expr::Comparison(comparator, lhs, rhs) => {
+                compare(*comparator, &lhs.evaluate(value)?, &rhs.evaluate(value)?)
+            }
+            Expr::And(lhs, rhs) => {
+                let lhs = lhs.evaluate(value)?;
+                if is_truthy(&lhs) {
+                    rhs.evaluate(value)?
+                } else {
+                    lhs
+                }
+            }
+            Expr::Or(lhs, rhs) => {
+                let lhs = lhs.evaluate(value)?;
+                if is_truthy(&lhs) {
+                    lhs
+                } else {
+                    rhs.evaluate(value)?
+                }
+            }
+            Expr::Not(expr) => Value::Bool(!is_truthy(&expr.evaluate(value)?)),
+            Expr::Function(name, arguments) => call(name, arguments, value)?,
+            Expr::Expref(_) => {
+                return Err(EvaluationError {
+                    message: "an expression reference can only be a function argument".to_string(),
+                })
+            }
+        })
+    }
+
+    /// Checks that the expression can be evaluated against a value of the shape, returning the
+    /// type of the result.
+    pub fn type_check(&self, model: &Model, shape: &ShapeId) -> Result<Type, TypeError> {
+        self.type_check_value(model, &Type::of_shape(model, shape)?)
+    }
+
+    /// Checks that the expression can be evaluated against a value of the type, returning the
+    /// type of the result.
+    pub fn type_check_value(&self, model: &Model, current: &Type) -> Result<Type, TypeError> {
+        let check = |expr: &Expr, current: &Type| expr.type_check_value(model, current);
+        Ok(match self {
+            Expr::Identity => current.clone(),
+            Expr::Field(name) => current.field(model, name)?,
+            Expr::Index(_) => match current {
+                Type::Array(element) => (**element).clone(),
+                Type::Any | Type::Null => current.clone(),
+                _ => return Err(TypeError::new(format!("can't index into {current}"))),
+            },
+            Expr::Slice { .. } => match current {
+                Type::Array(_) | Type::Any | Type::Null => current.clone(),
+                _ => return Err(TypeError::new(format!("can't slice {current}"))),
+            },
+            Expr::Subexpression(lhs, rhs) | Expr::Pipe(lhs, rhs) => {
+                check(rhs, &check(lhs, current)?)?
+            }
+            Expr::ListProjection(lhs, rhs) => match check(lhs, current)? {
+                Type::Array(element) => Type::Array(Box::new(check(rhs, &element)?)),
+                Type::Any => Type::Array(Box::new(check(rhs, &Type::Any)?)),
+                Type::Null => Type::Null,
+                other => return Err(TypeError::new(format!("can't project over {other}"))),
+            },
+            Expr::ValueProjection(lhs, rhs) => match check(lhs, current)? {
+                Type::Object(value) => Type::Array(Box::new(check(rhs, &value)?)),
+                Type::Any | Type::Shape(_) | Type::Record(_) => {
+                    Type::Array(Box::new(check(rhs, &Type::Any)?))
+                }
+                Type::Null => Type::Null,
+                other => return Err(TypeError::new(format!("can't project over {other}"))),
+            },
+            Expr::FilterProjection {
+                lhs,
+                condition,
+                rhs,
+            } => {
+                let element = match check(lhs, current)? {
+                    Type::Array(element) => *element,
+                    Type::Any => Type::Any,
+                    Type::Null => return Ok(Type::Null),
+                    other => return Err(TypeError::new(format!("can't filter {other}"))),
+                };
+                check(condition, &element)?;
+                Type::Array(Box::new(check(rhs, &element)?))
+            }
+            Expr::Flatten(expr) => match check(expr, current)? {
+                Type::Array(element) => match *element {
+                    Type::Array(element) => Type::Array(element),
+                    Type::Any => Type::Array(Box::new(Type::Any)),
+                    element => Type::Array(Box::new(element)),
+                },
+                Type::Any => Type::Array(Box::new(Type::Any)),
+                Type::Null => Type::Null,
+                other => return Err(TypeError::new(format!("can't flatten {other}"))),
+            },
+            Expr::MultiSelectList(items) => {
+                let types = items
+                    .iter()
+                    .map(|item| check(item, current))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Type::Array(Box::new(Type::common(types)))
+            }
+            Expr::MultiSelectHash(pairs) => Type::Record(
+                pairs
+                    .iter()
+                    .map(|(key, expr)| Ok((key.clone(), check(expr, current)?)))
+                    .collect::<Result<_, TypeError>>()?,
+            ),
+            Expr::Literal(value) => Type::of_value(value),
+            Expr::Comparison(comparator, lhs, rhs) => {
+                let types = [check(lhs, current)?, check(rhs, current)?];
+                if !matches!(comparator, Comparator::Eq | Comparator::Ne) {
+                    if let Some(other) = types
+                        .iter()
+                        .find(|t| !matches!(t, Type::Number | Type::Any))
+                    {
+                        return Err(TypeError::new(format!("can't order {other}")));
+                    }
+                }
+                Type::Boolean
+            }
+            Expr::And(lhs, rhs) | Expr::Or(lhs, rhs) => {
+                Type::common([check(lhs, current)?, check(rhs, current)?])
+            }
+            Expr::Not(expr) => {
+                check(expr, current)?;
+                Type::Boolean
+            }
+            Expr::Function(name, arguments) => {
+                let function = Function::find(name, arguments).map_err(TypeError::new)?;
+                let types = arguments
+                    .iter()
+                    .map(|argument| match argument {
+                        Expr::Expref(_) => Ok(Type::Any),
+                        argument => check(argument, current),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let element = |index: usize| match &types[index] {
+                    Type::Array(element) => (**element).clone(),
+                    _ => Type::Any,
+                };
+                let expref_type = |element: &Type| match function.expref {
+                    Some(index) => {
+                        let Expr::Expref(expr) = &arguments[index] else {
+                            unreachable!("function arguments are checked by Function::find")
+                        };
+                        check(expr, element)
+                    }
+                    None => Ok(Type::Any),
+                };
+                match function.name {
+                    "abs" | "avg" | "ceil" | "floor" | "length" | "sum" | "to_number" => {
+                        Type::Number
+                    }
+                    "contains" | "ends_with" | "starts_with" => Type::Boolean,
+                    "join" | "to_string" | "type" => Type::String,
+                    "keys" => Type::Array(Box::new(Type::String)),
+                    "values" => match &types[0] {
+                        Type::Object(value) => Type::Array(value.clone()),
+                        _ => Type::Array(Box::new(Type::Any)),
+                    },
+                    "map" => Type::Array(Box::new(expref_type(&element(1))?)),
+                    "max" | "min" => element(0),
+                    "max_by" | "min_by" => {
+                        expref_type(&element(0))?;
+                        element(0)
+                    }
+                    "sort_by" => {
+                        expref_type(&element(0))?;
+                        types[0].clone()
+                    }
+                    "reverse" | "sort" => types[0].clone(),
+                    "to_array" => match &types[0] {
+                        Type::Array(_) | Type::Any => types[0].clone(),
+                        other => Type::Array(Box::new(other.clone())),
+                    },
+                    _ => Type::Any,
+                }
+            }
+            Expr::Expref(_) => {
+                return Err(TypeError::new(
+                    "an expression reference can only be a function argument".to_string(),
+                ))
+            }
+        })
+    }
+}
+
+fn slice(items: &[Value], start: Option<i64>, stop: Option<i64>, step: Option<i64>) -> Vec<Value> {
+    let length = items.len() as i64;
+    let step = step.unwrap_or(1);
+    // Like Python, negative indices count from the end and indices out of range are clamped.
+    let clamp = |index: i64| {
+        if index < 0 {
+            (length + index).max(if step < 0 { -1 } else { 0 })
+        } else {
+            index.min(if step < 0 { length - 1 } else { length })
+        }
+    };
+    let (mut index, stop) = if step > 0 {
+        (start.map_or(0, clamp), stop.map_or(length, clamp))
+    } else {
+        (start.map_or(length - 1, clamp), stop.map_or(-1, clamp))
+    };
+    let mut sliced = vec![];
+    while if step > 0 { index < stop } else { index > stop } {
+        sliced.push(items[index as usize].clone());
+        index += step;
+    }
+    sliced
+}
+
+fn project<'a>(
+    items: impl IntoIterator<Item = &'a Value>,
+    rhs: &Expr,
+) -> Result<Value, EvaluationError> {
+    let mut projected = vec![];
+    for item in items {
+        let value = rhs.evaluate(item)?;
+        if !value.is_null() {
+            projected.push(value);
+        }
+    }
+    Ok(Value::Array(projected))
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(_) => true,
+        Value::String(value) => !value.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(object) => !object.is_empty(),
+    }
+}
+
+/// Equality, with numbers compared by value so that `1` equals `1.0`.
+fn equals(lhs: &Value, rhs: &Value) -> bool {
+    match (lhs, rhs) {
+        (Value::Number(lhs), Value::Number(rhs)) => lhs.as_f64() == rhs.as_f64(),
+        (Value::Array(lhs), Value::Array(rhs)) => {
+            lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(lhs, rhs)| equals(lhs, rhs))
+        }
+        (Value::Object(lhs), Value::Object(rhs)) => {
+            lhs.len() == rhs.len()
+                && lhs
+                    .iter()
+                    .all(|(key, lhs)| rhs.get(key).is_some_and(|rhs| equals(lhs, rhs)))
+        }
+        _ => lhs == rhs,
+    }
+}
+
+fn compare(comparator: Comparator, lhs: &Value, rhs: &Value) -> Value {
+    let (lhs, rhs) = match comparator {
+        Comparator::Eq => return Value::Bool(equals(lhs, rhs)),
+        Comparator::Ne => return Value::Bool(!equals(lhs, rhs)),
+        _ => match (lhs.as_f64(), rhs.as_f64()) {
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+            // Only numbers can be ordered.
+            _ => return Value::Null,
+        },
+    };
+    Value::Bool(match comparator {
+        Comparator::Lt => lhs < rhs,
+        Comparator::Le => lhs <= rhs,
+        Comparator::Gt => lhs > rhs,
+        Comparator::Ge => lhs >= rhs,
+        Comparator::Eq | Comparator::Ne => unreachable!(),
+    })
+}
+
+/// Orders two numbers or two strings, which are the only values functions like `sort` accept.
+fn order(lhs: &Value, rhs: &Value) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Value::Number(lhs), Value::Number(rhs)) => lhs.as_f64()?.partial_cmp(&rhs.as_f64()?),
+        (Value::String(lhs), Value::String(rhs)) => Some(lhs.cmp(rhs)),
+        _ => None,
+    }
+}
+
+/// Whether every value is a number or every value is a string.
+fn is_orderable(values: &[Value]) -> bool {
+    values.iter().all(Value::is_number) || values.iter().all(Value::is_string)
+}
+
+/// A number as a JSON value, as an integer if it is one.
+fn number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < 2f64.powi(53) {
+        Value::from(value as i64)
+    } else {
+        serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// A built in function.
+struct Function {
+    name: &'static str,
+    min_arguments: usize,
+    max_arguments: usize,
+    /// The argument that must be an expression reference, if any.
+    expref: Option<usize>,
+}
+
+impl Function {
+    const fn new(name: &'static str, arguments: usize) -> Self {
+        Self {
+            name,
+            min_arguments: arguments,
+            max_arguments: arguments,
+            expref: None,
+        }
+    }
+
+    const fn variadic(self) -> Self {
+        Self {
+            max_arguments: usize::MAX,
+            ..self
+        }
+    }
+
+    const fn expref(self, index: usize) -> Self {
+        Self {
+            expref: Some(index),
+            ..self
+        }
+    }
+
+    /// Looks up a function, checking the number of arguments and where expression references
+    /// are passed.
+    fn find(name: &str, arguments: &[Expr]) -> Result<&'static Function, String> {
+        let function = FUNCTIONS
+            .iter()
+            .find(|function| function.name == name)
+            .ok_or_else(|| format!("unknown function {name}()"))?;
+        if !(function.min_arguments..=function.max_arguments).contains(&arguments.len()) {
+            let expected = if function.max_arguments == usize::MAX {
+                format!("at least {}", function.min_arguments)
+            } else {
+                function.min_arguments.to_string()
+            };
+            return Err(format!(
+                "{name}() takes {expected} argument(s), got {}",
+                arguments.len()
+            ));
+        }
+        for (index, argument) in arguments.iter().enumerate() {
+            let is_expref = matches!(argument, Expr::Expref(_));
+            if is_expref != (function.expref == Some(index)) {
+                let expected = if is_expref {
+                    "a value"
+                } else {
+                    "an expression reference"
+                };
+                return Err(format!(
+                    "argument {} of {name}() should be {expected}",
+                    index + 1
+                ));
+            }
+        }
+        Ok(function)
+    }
+}
+
+const FUNCTIONS: &[Function] = &[
+    Function::new("abs", 1),
+    Function::new("avg", 1),
+    Function::new("ceil", 1),
+    Function::new("contains", 2),
+    Function::new("ends_with", 2),
+    Function::new("floor", 1),
+    Function::new("join", 2),
+    Function::new("keys", 1),
+    Function::new("length", 1),
+    Function::new("map", 2).expref(0),
+    Function::new("max", 1),
+    Function::new("max_by", 2).expref(1),
+    Function::new("merge", 1).variadic(),
+    Function::new("min", 1),
+    Function::new("min_by", 2).expref(1),
+    Function::new("not_null", 1).variadic(),
+    Function::new("reverse", 1),
+    Function::new("sort", 1),
+    Function::new("sort_by", 2).expref(1),
+    Function::new("starts_with", 2),
+    Function::new("sum", 1),
+    Function::new("to_array", 1),
+    Function::new("to_number", 1),
+    Function::new("to_string", 1),
+    Function::new("type", 1),
+    Function::new("values", 1),
+];
+
+fn call(name: &str, arguments: &[Expr], current: &Value) -> Result<Value, EvaluationError> {
+    let function =
+        Function::find(name, arguments).map_err(|message| EvaluationError { message })?;
+    // Expression references are evaluated by the functions that take them.
+    let values = arguments
+        .iter()
+        .map(|argument| match argument {
+            Expr::Expref(_) => Ok(Value::Null),
+            argument => argument.evaluate(current),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let invalid = |index: usize, expected: &str| EvaluationError {
+        message: format!(
+            "argument {} of {name}() should be {expected}, got {}",
+            index + 1,
+            type_name(&values[index])
+        ),
+    };
+    let number_argument = |index: usize| {
+        values[index]
+            .as_f64()
+            .ok_or_else(|| invalid(index, "a number"))
+    };
+    let string = |index: usize| {
+        values[index]
+            .as_str()
+            .ok_or_else(|| invalid(index, "a string"))
+    };
+    let array = |index: usize| {
+        values[index]
+            .as_array()
+            .ok_or_else(|| invalid(index, "an array"))
+    };
+    let object = |index: usize| {
+        values[index]
+            .as_object()
+            .ok_or_else(|| invalid(index, "an object"))
+    };
+    let numbers = |index: usize| {
+        array(index)?
+            .iter()
+            .map(|value| {
+                value
+                    .as_f64()
+                    .ok_or_else(|| invalid(index, "an array of numbers"))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let orderable = |index: usize| {
+        let items = array(index)?;
+        if !is_orderable(items) {
+            return Err(invalid(index, "an array of numbers or strings"));
+        }
+        Ok(items)
+    };
+    // The values of the expression reference for each item of the array argument.
+    let keys = |items: &[Value]| {
+        let Some(Expr::Expref(expr)) = function.expref.map(|index| &arguments[index]) else {
+            unreachable!("function arguments are checked by Function::find")
+        };
+        let keys = items
+            .iter()
+            .map(|item| expr.evaluate(item))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !is_orderable(&keys) {
+            return Err(EvaluationError {
+                message: format!(
+                    "the expression passed to {name}() should give numbers or strings"
+                ),
+            });
+        }
+        Ok(keys)
+    };
+    let by = |ordering: Ordering| -> Result<Value, EvaluationError> {
+        let items = array(0)?;
+        let keys = keys(items)?;
+        let best = (0..items.len()).reduce(|best, index| {
+            if order(&keys[index], &keys[best]) == Some(ordering) {
+                index
+            } else {
+                best
+            }
+        });
+        Ok(best.map_or(Value::Null, |index| items[index].clone()))
+    };
+
+    Ok(match function.name {
+        "abs" => number(number_argument(0)?.abs()),
+        "avg" => {
+            let numbers = numbers(0)?;
+            if numbers.is_empty() {
+                Value::Null
+            } else {
+                number(numbers.iter().sum::<f64>() / numbers.len() as f64)
+            }
+        }
+        "ceil" => number(number_argument(0)?.ceil()),
+        "contains" => match &values[0] {
+            Value::Array(items) => Value::Bool(items.iter().any(|item| equals(item, &values[1]))),
+            Value::String(string) => Value::Bool(
+                values[1]
+                    .as_str()
+                    .is_some_and(|search| string.contains(search)),
+            ),
+            _ => return Err(invalid(0, "an array or string")),
+        },
+        "ends_with" => Value::Bool(string(0)?.ends_with(string(1)?)),
+        "floor" => number(number_argument(0)?.floor()),
+        "join" => {
+            let separator = string(0)?;
+            let parts = array(1)?
+                .iter()
+                .map(|part| {
+                    part.as_str()
+                        .ok_or_else(|| invalid(1, "an array of strings"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Value::String(parts.join(separator))
+        }
+        "keys" => Value::Array(object(0)?.keys().cloned().map(Value::String).collect()),
+        "length" => match &values[0] {
+            Value::String(string) => Value::from(string.chars().count()),
+            Value::Array(items) => Value::from(items.len()),
+            Value::Object(object) => Value::from(object.len()),
+            _ => return Err(invalid(0, "a string, array or object")),
+        },
+        "map" => {
+            let Expr::Expref(expr) = &arguments[0] else {
+                unreachable!("function arguments are checked by Function::find")
+            };
+            Value::Array(
+                array(1)?
+                    .iter()
+                    .map(|item| expr.evaluate(item))
+                    .collect::<Result<_, _>>()?,
+            )
+        }
+        "max" => orderable(0)?
+            .iter()
+            .max_by(|lhs, rhs| order(lhs, rhs).unwrap_or(Ordering::Equal))
+            .cloned()
+            .unwrap_or(Value::Null),
+        "max_by" => by(Ordering::Greater)?,
+        "merge" => {
+            let mut merged = Map::new();
+            for index in 0..values.len() {
+                merged.extend(object(index)?.clone());
+            }
+            Value::Object(merged)
+        }
+        "min" => orderable(0)?
+            .iter()
+            .min_by(|lhs, rhs| order(lhs, rhs).unwrap_or(Ordering::Equal))
+            .cloned()
+            .unwrap_or(Value::Null),
+        "min_by" => by(Ordering::Less)?,
+        "not_null" => values
+            .iter()
+            .find(|value| !value.is_null())
+            .cloned()
+            .unwrap_or(Value::Null),
+        "reverse" => match &values[0] {
+            Value::String(string) => Value::String(string.chars().rev().collect()),
+            Value::Array(items) => Value::Array(items.iter().rev().cloned().collect()),
+            _ => return Err(invalid(0, "an array or string")),
+        },
+        "sort" => {
+            let mut items = orderable(0)?.clone();
+            items.sort_by(|lhs, rhs| order(lhs, rhs).unwrap_or(Ordering::Equal));
+            Value::Array(items)
+        }
+        "sort_by" => {
+            let items = array(0)?;
+            let keys = keys(items)?;
+            let mut indices = (0..items.len()).collect::<Vec<_>>();
+            indices.sort_by(|&lhs, &rhs| order(&keys[lhs], &keys[rhs]).unwrap_or(Ordering::Equal));
+            Value::Array(
+                indices
+                    .into_iter()
+                    .map(|index| items[index].clone())
+                    .collect(),
+            )
+        }
+        "starts_with" => Value::Bool(string(0)?.starts_with(string(1)?)),
+        "sum" => number(numbers(0)?.iter().sum()),
+        "to_array" => match &values[0] {
+            Value::Array(_) => values[0].clone(),
+            value => Value::Array(vec![value.clone()]),
+        },
+        "to_number" => match &values[0] {
+            Value::Number(_) => values[0].clone(),
+            Value::String(string) => string.parse().map_or(Value::Null, number),
+            _ => Value::Null,
+        },
+        "to_string" => match &values[0] {
+            Value::String(_) => values[0].clone(),
+            value => Value::String(value.to_string()),
+        },
+        "type" => Value::from(type_name(&values[0])),
+        "values" => Value::Array(object(0)?.values().cloned().collect()),
+        name => unreachable!("no implementation of {name}()"),
+    })
+}
+
+/// The type of the value an expression evaluates to, as far as the model tells.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    /// Any value, such as a document.
+    Any,
+    Null,
+    Boolean,
+    Number,
+    String,
+    Array(Box<Type>),
+    /// An object with any keys and values of one type, such as a map shape.
+    Object(Box<Type>),
+    /// An object with known keys, such as from a multi-select hash.
+    Record(IndexMap<String, Type>),
+    /// A structure or union shape.
+    Shape(ShapeId),
+}
+
+impl Type {
+    /// The type of values of the shape.
+    pub fn of_shape(model: &Model, id: &ShapeId) -> Result<Type, TypeError> {
+        let shape = model
+            .resolve(id)
+            .ok_or_else(|| TypeError::new(format!("shape {id} is not in the model")))?;
+        Ok(match shape {
+            Shape::Boolean(_) => Type::Boolean,
+            Shape::Byte(_)
+            | Shape::Short(_)
+            | Shape::Integer(_)
+            | Shape::Long(_)
+            | Shape::Float(_)
+            | Shape::Double(_)
+            | Shape::BigInteger(_)
+            | Shape::BigDecimal(_) => Type::Number,
+            Shape::String(_) | Shape::Enum(_) | Shape::Blob(_) => Type::String,
+            // How timestamps are serialized depends on the protocol.
+            Shape::Timestamp(_) | Shape::Document(_) => Type::Any,
+            Shape::List(list) => Type::Array(Box::new(Type::of_shape(model, &list.member.target)?)),
+            Shape::Map(map) => Type::Object(Box::new(Type::of_shape(model, &map.value.target)?)),
+            Shape::Structure(_) | Shape::Union(_) => Type::Shape(id.clone()),
+            Shape::Service(_) | Shape::Operation(_) | Shape::Resource(_) => {
+                return Err(TypeError::new(format!(
+                    "{id} is a {}, not a value",
+                    shape.type_name()
+                )))
+            }
+        })
+    }
+
+    /// The type of a JSON value.
+    pub fn of_value(value: &Value) -> Type {
+        match value {
+            Value::Null => Type::Null,
+            Value::Bool(_) => Type::Boolean,
+            Value::Number(_) => Type::Number,
+            Value::String(_) => Type::String,
+            Value::Array(items) => {
+                Type::Array(Box::new(Type::common(items.iter().map(Type::of_value))))
+            }
+            Value::Object(object) => Type::Record(
+                object
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Type::of_value(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The type all the types have in common, which is [`Type::Any`] unless they are all the
+    /// same.
+    fn common(types: impl IntoIterator<Item = Type>) -> Type {
+        let mut types = types.into_iter();
+        let Some(first) = types.next() else {
+            return Type::Any;
+        };
+        if types.all(|other| other == first) {
+            first
+        } else {
+            Type::Any
+        }
+    }
+
+    fn field(&self, model: &Model, name: &str) -> Result<Type, TypeError> {
+        match self {
+            Type::Shape(id) => {
+                let target = match model.resolve(id) {
+                    Some(Shape::Structure(structure)) => {
+                        structure.members.get(name).map(|member| &member.target)
+                    }
+                    Some(Shape::Union(union)) => {
+                        union.members.get(name).map(|member| &member.target)
+                    }
+                    _ => None,
+                };
+                let target =
+                    target.ok_or_else(|| TypeError::new(format!("{id} has no member `{name}`")))?;
+                Type::of_shape(model, target)
+            }
+            Type::Object(value) => Ok((**value).clone()),
+            Type::Record(fields) => fields
+                .get(name)
+                .cloned()
+                .ok_or_else(|| TypeError::new(format!("no field `{name}` in {self}"))),
+            Type::Any | Type::Null => Ok(self.clone()),
+            _ => Err(TypeError::new(format!("can't select `{name}` from {self}"))),
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Any => f.write_str("any value"),
+            Type::Null => f.write_str("null"),
+            Type::Boolean => f.write_str("a boolean"),
+            Type::Number => f.write_str("a number"),
+            Type::String => f.write_str("a string"),
+            Type::Array(_) => f.write_str("an array"),
+            Type::Object(_) | Type::Record(_) => f.write_str("an object"),
+            Type::Shape(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+impl TypeError {
+    fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn evaluates() {
+        let value = json!({
+            "name": "forest",
+            "trees": [
+                { "name": "oak", "height": 20, "tags": ["old", "tall"] },
+                { "name": "birch", "height": 12, "tags": [] },
+                { "name": "pine", "height": 30, "tags": ["tall"] },
+            ],
+            "counts": { "oak": 1, "birch": 2 },
+            "empty": [],
+        });
+        let cases = [
+            ("name", json!("forest")),
+            ("missing.name", json!(null)),
+            ("trees[0].name", json!("oak")),
+            ("trees[-1].name", json!("pine")),
+            ("trees[*].name", json!(["oak", "birch", "pine"])),
+            ("trees[1:].height", json!([12, 30])),
+            ("trees[::-2].name", json!(["pine", "oak"])),
+            ("trees[].tags[]", json!(["old", "tall", "tall"])),
+            ("trees[?height > `15`].name", json!(["oak", "pine"])),
+            ("trees[?name == 'birch'] | [0].height", json!(12)),
+            ("counts.*", json!([1, 2])),
+            ("trees[0].[name, height]", json!(["oak", 20])),
+            (
+                "trees[0].{n: name, \"h\": height}",
+                json!({ "n": "oak", "h": 20 }),
+            ),
+            ("empty || 'default'", json!("default")),
+            ("!empty && name", json!("forest")),
+            ("length(trees[?contains(tags, 'tall')])", json!(2)),
+            ("max_by(trees, &height).name", json!("pine")),
+            (
+                "sort_by(trees, &name)[*].name",
+                json!(["birch", "oak", "pine"]),
+            ),
+            ("map(&length(tags), trees)", json!([2, 0, 1])),
+            ("sum(trees[*].height)", json!(62)),
+            ("avg(trees[*].height) == `20.666666666666668`", json!(true)),
+            ("join(', ', keys(counts))", json!("oak, birch")),
+            ("to_string(`1`) == '1'", json!(true)),
+            ("`1` == `1.0`", json!(true)),
+            ("'a' < 'b'", json!(null)),
+        ];
+        for (expression, expected) in cases {
+            let expr = parse(expression).unwrap_or_else(|e| panic!("{expression}: {e}"));
+            let actual = expr
+                .evaluate(&value)
+                .unwrap_or_else(|e| panic!("{expression}: {e}"));
+            assert_eq!(actual, expected, "{expression}");
+        }
+
+        let error = parse("abs(name)").unwrap().evaluate(&value).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "argument 1 of abs() should be a number, got string"
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        for (expression, message, offset) in [
+            ("foo.", "unexpected end of expression after `.`", 4),
+            ("foo[", "expected `*`, found end of expression", 4),
+            ("foo bar", "unexpected identifier \"bar\"", 4),
+            ("[1:2:0]", "slice step can't be 0", 6),
+            ("foo = bar", "unexpected character '='", 4),
+            ("'unclosed", "unclosed '", 0),
+        ] {
+            let error = parse(expression).unwrap_err();
+            assert_eq!(error.message, message, "{expression}");
+            assert_eq!(error.span.offset(), offset, "{expression}");
+        }
+    }
+
+    #[test]
+    fn type_checks() {
+        let model = serde_json::from_value::<Model>(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#Output": {
+                    "type": "structure",
+                    "members": {
+                        "Tables": { "target": "example#Tables" },
+                        "Next": { "target": "smithy.api#String" },
+                    },
+                },
+                "example#Tables": { "type": "list", "member": { "target": "example#Table" } },
+                "example#Table": {
+                    "type": "structure",
+                    "members": {
+                        "Status": { "target": "smithy.api#String" },
+                        "Size": { "target": "smithy.api#Long" },
+                        "Tags": { "target": "example#Tags" },
+                    },
+                },
+                "example#Tags": {
+                    "type": "map",
+                    "key": { "target": "smithy.api#String" },
+                    "value": { "target": "smithy.api#String" },
+                },
+            },
+        }))
+        .unwrap();
+        let output = ShapeId {
+            namespace: "example".to_string(),
+            name: "Output".to_string(),
+            member: None,
+        };
+        let check = |expression: &str| parse(expression).unwrap().type_check(&model, &output);
+
+        assert_eq!(check("Next").unwrap(), Type::String);
+        assert_eq!(
+            check("Tables[].Status").unwrap(),
+            Type::Array(Box::new(Type::String))
+        );
+        assert_eq!(
+            check("Tables[?Size > `10`].Tags.Owner").unwrap(),
+            Type::Array(Box::new(Type::String))
+        );
+        assert_eq!(
+            check("length(Tables[?Status == 'ACTIVE']) > `0`").unwrap(),
+            Type::Boolean
+        );
+        assert_eq!(
+            check("Tables[].Stats").unwrap_err().to_string(),
+            "example#Table has no member `Stats`"
+        );
+        assert_eq!(
+            check("Next[0]").unwrap_err().to_string(),
+            "can't index into a string"
+        );
+        assert_eq!(
+            check("Tables[?Status > `1`]").unwrap_err().to_string(),
+            "can't order a string"
+        );
+        assert_eq!(
+            check("size(Tables)").unwrap_err().to_string(),
+            "unknown function size()"
+        );
+    }
+}
@@ -1,50 +1,433 @@
 use indexmap::IndexMap;
 
+pub use assembler::{AssemblyError, ModelAssembler};
 pub use endpoint_rules::*;
+pub use graph::ShapeGraph;
+pub use idl::{idl_to_json_ast, parse_idl, IdlError};
+pub use mixins::MixinError;
+pub use parse::{ParseError, ParseOptions, ParseWarning};
+pub use registry::*;
 pub use shape_id::*;
 pub use shapes::*;
 pub use traits::*;
+pub use upgrade::upgrade_json_ast;
+pub use validate::{validate, ValidationError};
+pub use validate_value::{validate_value, ValueError};
 
+pub mod arn;
+mod assembler;
+pub mod diff;
 mod endpoint_rules;
+mod graph;
+mod idl;
+pub mod jmespath;
+mod mixins;
+mod parse;
+pub mod prelude;
+mod projection;
+mod registry;
 mod shape_id;
 mod shapes;
 mod traits;
+mod upgrade;
+mod validate;
+mod validate_value;
 
-// Pushes the monomorphization of the serde::Deserialize trait down to this crate,
-// which makes it a bit faster to recompile after changes outside this crate.
-pub fn parse_model(source: &str) -> serde_json::Result<Model> {
-    serde_json::from_str(source)
+/// Parses a Smithy JSON AST model, rejecting traits this schema doesn't define. The `name`
+/// (usually the file path) is used in errors.
+pub fn parse_model(name: &str, source: &str) -> Result<Model, ParseError> {
+    // Pushes the monomorphization of the serde::Deserialize trait down to this crate,
+    // which makes it a bit faster to recompile after changes outside this crate.
+    let (model, _) = parse::parse_model(name, source, &ParseOptions { strict: true })?;
+    Ok(model)
 }
 
-#[derive(Debug, serde::Deserialize)]
+/// Parses a Smithy JSON AST model, returning warnings for any problems tolerated by the options.
+pub fn parse_model_with_options(
+    name: &str,
+    source: &str,
+    options: &ParseOptions,
+) -> Result<(Model, Vec<ParseWarning>), ParseError> {
+    parse::parse_model(name, source, options)
+}
+
+/// Writes the model as a Smithy JSON AST document.
+pub fn write_model(model: &Model) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(model)
+}
+
+// Used to skip serializing fields that would be omitted in the source model, mostly traits.
+pub(crate) fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+// Used to keep an explicit `null` as `Some(Value::Null)`, rather than the `None` of an omitted field.
+pub(crate) fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: serde::Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Model {
     pub smithy: SmithyVersion,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub metadata: Metadata,
     pub shapes: IndexMap<ShapeId, Shape>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+impl Model {
+    /// Looks up a shape defined by the model, or failing that by the [`prelude`].
+    pub fn resolve(&self, id: &ShapeId) -> Option<&Shape> {
+        self.shapes.get(id).or_else(|| prelude::get(id))
+    }
+
+    /// The shapes defined by the model with the trait applied, not counting their members.
+    pub fn shapes_with_trait<'a>(&'a self, id: &'a ShapeId) -> impl Iterator<Item = &'a ShapeId> {
+        self.shapes
+            .iter()
+            .filter(|(_, shape)| shape.traits().contains(id))
+            .map(|(shape_id, _)| shape_id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub enum SmithyVersion {
-    #[serde(rename = "2.0")]
+    /// Only seen when deserializing a model directly: parsing upgrades 1.0 models to 2.0.
+    #[serde(rename = "1.0", alias = "1")]
+    _1_0,
+    #[serde(rename = "2.0", alias = "2")]
     _2_0,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct Metadata {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub suppressions: Vec<MetadataSuppression>,
+    #[serde(flatten)]
+    pub other: IndexMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct MetadataSuppression {
     pub id: String,
     pub namespace: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct ShapeRef {
     pub target: ShapeId,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn assert_round_trips(name: &str, source: &str, options: &ParseOptions) {
+        let (model, _) = parse_model_with_options(name, source, options)
+            .unwrap_or_else(|error| panic!("{name} should parse: {error}"));
+        let written = write_model(&model).expect("model should serialize");
+        let expected = serde_json::from_str::<Value>(source).unwrap();
+        let actual = serde_json::from_str::<Value>(&written).unwrap();
+        if let Some(path) = first_difference(&expected, &actual) {
+            panic!(
+                "written {name} differs at {path}:\n  expected: {}\n  actual: {}",
+                pointer(&expected, &path),
+                pointer(&actual, &path),
+            );
+        }
+    }
+
+    fn pointer(value: &Value, path: &str) -> String {
+        value
+            .pointer(path)
+            .map_or("<missing>".to_string(), Value::to_string)
+    }
+
+    fn first_difference(expected: &Value, actual: &Value) -> Option<String> {
+        match (expected, actual) {
+            (Value::Object(expected), Value::Object(actual)) => {
+                expected.keys().chain(actual.keys()).find_map(|key| {
+                    let path = format!("/{}", key.replace('~', "~0").replace('/', "~1"));
+                    match (expected.get(key), actual.get(key)) {
+                        (Some(expected), Some(actual)) => {
+                            first_difference(expected, actual).map(|inner| format!("{path}{inner}"))
+                        }
+                        _ => Some(path),
+                    }
+                })
+            }
+            (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+                expected
+                    .iter()
+                    .zip(actual)
+                    .enumerate()
+                    .find_map(|(index, (expected, actual))| {
+                        first_difference(expected, actual).map(|inner| format!("/{index}{inner}"))
+                    })
+            }
+            _ if expected == actual => None,
+            _ => Some(String::new()),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let source = json!({
+            "smithy": "2.0",
+            "metadata": {
+                "suppressions": [{ "id": "HttpMethodSemantics", "namespace": "*" }],
+                "custom": { "value": 1 },
+            },
+            "shapes": {
+                "example#Service": {
+                    "type": "service",
+                    "version": "2023-01-01",
+                    "operations": [{ "target": "example#GetThing" }],
+                    "traits": {
+                        "aws.api#service": { "sdkId": "Example", "endpointPrefix": "example" },
+                        "aws.protocols#awsJson1_0": {},
+                        "smithy.api#title": "Example Service",
+                        "smithy.rules#endpointRuleSet": {
+                            "version": "1.0",
+                            "parameters": {
+                                "Region": {
+                                    "builtIn": "AWS::Region",
+                                    "required": false,
+                                    "documentation": "The AWS region.",
+                                    "type": "String",
+                                },
+                            },
+                            "rules": [
+                                {
+                                    "conditions": [
+                                        { "fn": "isSet", "argv": [{ "ref": "Region" }] },
+                                        {
+                                            "fn": "aws.partition",
+                                            "argv": [{ "ref": "Region" }],
+                                            "assign": "partition",
+                                        },
+                                    ],
+                                    "endpoint": {
+                                        "url": "https://example.{Region}.{partition#dnsSuffix}",
+                                        "properties": {
+                                            "authSchemes": [{
+                                                "name": "sigv4",
+                                                "signingName": "example",
+                                                "signingRegion": "{Region}",
+                                                "disableDoubleEncoding": true,
+                                            }],
+                                        },
+                                        "headers": {},
+                                    },
+                                    "type": "endpoint",
+                                },
+                                { "conditions": [], "error": "Missing region", "type": "error" },
+                            ],
+                        },
+                        "smithy.rules#endpointTests": { "version": "1.0", "testCases": [] },
+                    },
+                },
+                "example#GetThing": {
+                    "type": "operation",
+                    "input": { "target": "example#GetThingInput" },
+                    "output": { "target": "smithy.api#Unit" },
+                    "traits": {
+                        "smithy.api#readonly": {},
+                        "smithy.waiters#waitable": {
+                            "ThingExists": {
+                                "documentation": "Waits for the thing to exist.",
+                                "acceptors": [
+                                    { "state": "success", "matcher": { "success": true } },
+                                    {
+                                        "state": "retry",
+                                        "matcher": { "errorType": "NotFound" },
+                                    },
+                                    {
+                                        "state": "failure",
+                                        "matcher": {
+                                            "inputOutput": {
+                                                "path": "input.Name == output.Name",
+                                                "expected": "false",
+                                                "comparator": "booleanEquals",
+                                            },
+                                        },
+                                    },
+                                ],
+                                "minDelay": 5,
+                                "tags": ["exists"],
+                            },
+                        },
+                    },
+                },
+                "example#GetThingInput": {
+                    "type": "structure",
+                    "members": {
+                        "Name": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#required": {} },
+                        },
+                        "Ratio": {
+                            "target": "example#Ratio",
+                            "traits": { "smithy.api#default": 0 },
+                        },
+                        "Color": { "target": "example#Color" },
+                    },
+                    "traits": { "smithy.api#input": {} },
+                },
+                "example#Ratio": {
+                    "type": "double",
+                    "traits": { "smithy.api#default": 0, "smithy.api#range": { "max": 1 } },
+                },
+                "example#Color": {
+                    "type": "enum",
+                    "members": {
+                        "RED": {
+                            "target": "smithy.api#Unit",
+                            "traits": { "smithy.api#enumValue": "red" },
+                        },
+                    },
+                },
+            },
+        });
+        assert_round_trips(
+            "test.json",
+            &source.to_string(),
+            &ParseOptions { strict: true },
+        );
+    }
+
+    #[test]
+    fn waiters() {
+        let source = json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#GetThing": {
+                    "type": "operation",
+                    "input": { "target": "smithy.api#Unit" },
+                    "output": { "target": "smithy.api#Unit" },
+                    "traits": {
+                        "smithy.waiters#waitable": {
+                            "ThingReady": {
+                                "acceptors": [
+                                    {
+                                        "state": "success",
+                                        "matcher": {
+                                            "output": {
+                                                "path": "Parts[].State",
+                                                "expected": "ready",
+                                                "comparator": "allStringEquals",
+                                            },
+                                        },
+                                    },
+                                    {
+                                        "state": "failure",
+                                        "matcher": {
+                                            "output": {
+                                                "path": "Parts[].State",
+                                                "expected": "failed",
+                                                "comparator": "anyStringEquals",
+                                            },
+                                        },
+                                    },
+                                    {
+                                        "state": "retry",
+                                        "matcher": { "errorType": "example#NotFound" },
+                                    },
+                                ],
+                            },
+                            "ThingGone": {
+                                "acceptors": [
+                                    { "state": "success", "matcher": { "success": false } },
+                                ],
+                                "minDelay": 5,
+                                "maxDelay": 60,
+                                "deprecated": true,
+                            },
+                        },
+                    },
+                },
+            },
+        });
+        assert_round_trips(
+            "test.json",
+            &source.to_string(),
+            &ParseOptions { strict: true },
+        );
+
+        let model = parse_model("test.json", &source.to_string()).unwrap();
+        let id = "example#GetThing".parse::<ShapeId>().unwrap();
+        let Some(Shape::Operation(operation)) = model.shapes.get(&id) else {
+            panic!("expected GetThing to be an operation");
+        };
+        let waiters = &operation.traits.waitable.as_ref().unwrap().waiters;
+
+        let ready = &waiters["ThingReady"];
+        assert_eq!((ready.min_delay(), ready.max_delay()), (2, 120));
+        assert!(!ready.deprecated);
+        let states = ready.acceptors.iter().map(|acceptor| acceptor.state);
+        assert_eq!(
+            states.collect::<Vec<_>>(),
+            [
+                AcceptorState::Success,
+                AcceptorState::Failure,
+                AcceptorState::Retry
+            ],
+        );
+        let Matcher::Output(all) = &ready.acceptors[0].matcher else {
+            panic!("expected an output matcher");
+        };
+        assert_eq!(
+            (all.path.as_str(), all.expected.as_str(), all.comparator),
+            ("Parts[].State", "ready", PathComparator::AllStringEquals),
+        );
+        let Matcher::Output(any) = &ready.acceptors[1].matcher else {
+            panic!("expected an output matcher");
+        };
+        assert_eq!(any.comparator, PathComparator::AnyStringEquals);
+        assert_eq!(
+            ready.acceptors[2].matcher,
+            Matcher::ErrorType("example#NotFound".to_string()),
+        );
+
+        let gone = &waiters["ThingGone"];
+        assert_eq!((gone.min_delay(), gone.max_delay()), (5, 60));
+        assert!(gone.deprecated);
+        assert_eq!(gone.acceptors[0].matcher, Matcher::Success(false));
+    }
+
+    #[test]
+    #[ignore = "needs the models fetched to aws-models by laws-gen"]
+    fn round_trip_fetched_models() {
+        let models_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../aws-models");
+        let entries = std::fs::read_dir(&models_path)
+            .unwrap_or_else(|error| panic!("reading {models_path:?}: {error}"));
+        for entry in entries {
+            let path = entry.unwrap().path();
+            // The partitions are fetched alongside the models, but aren't one.
+            if path.extension().is_none_or(|ext| ext != "json")
+                || path
+                    .file_name()
+                    .is_some_and(|name| name == "partitions.json")
+            {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            assert_round_trips(
+                &path.display().to_string(),
+                &source,
+                &ParseOptions::default(),
+            );
+        }
+    }
+}
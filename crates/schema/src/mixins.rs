@@ -0,0 +1,338 @@
+//! Flattening mixins into the shapes that use them, so nothing downstream has to handle them.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::{Model, ShapeId};
+
+const MIXIN: &str = "smithy.api#mixin";
+
+fn mixin_trait() -> ShapeId {
+    MIXIN.parse().expect("the mixin trait id is valid")
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum MixinError {
+    #[error("{location}: mixin {mixin} is not defined")]
+    #[diagnostic(code(laws_schema::mixins::undefined))]
+    Undefined { location: String, mixin: String },
+    #[error("{location}: {mixin} is not a mixin")]
+    #[diagnostic(
+        code(laws_schema::mixins::not_mixin),
+        help("shapes used as mixins need the `smithy.api#mixin` trait")
+    )]
+    NotMixin { location: String, mixin: String },
+    #[error("{location}: mixin {mixin} is a {mixin_type} rather than a {shape_type}")]
+    #[diagnostic(code(laws_schema::mixins::type_mismatch))]
+    TypeMismatch {
+        location: String,
+        shape_type: &'static str,
+        mixin: String,
+        mixin_type: &'static str,
+    },
+    #[error(
+        "{location}: member {member:?} targets {target} rather than {mixin_target} as in a mixin"
+    )]
+    #[diagnostic(
+        code(laws_schema::mixins::member_conflict),
+        help("members from mixins can only be redefined to add traits")
+    )]
+    MemberConflict {
+        location: String,
+        member: String,
+        target: String,
+        mixin_target: String,
+    },
+    #[error("{location}: mixins form a cycle")]
+    #[diagnostic(code(laws_schema::mixins::cycle))]
+    Cycle { location: String },
+    #[error("{location}: applying mixins: {source}")]
+    #[diagnostic(code(laws_schema::mixins::invalid))]
+    Invalid {
+        location: String,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+impl Model {
+    /// Creates a model with the members and traits of mixins copied into the shapes that use
+    /// them, and the mixins themselves removed.
+    ///
+    /// Mixin members come first, in the order of the mixins, followed by the shape's own. A shape
+    /// can redefine a mixin member to add traits. Traits on the shape override those of its
+    /// mixins, and later mixins override earlier ones, but the `mixin` trait and its
+    /// `localTraits` aren't copied. Other properties, such as operation errors, are merged the
+    /// same way: lists are concatenated and the shape's own values override its mixins'.
+    pub fn flatten_mixins(&self) -> Result<Model, MixinError> {
+        let mut flattener = Flattener {
+            model: self,
+            flattened: HashMap::new(),
+            in_progress: vec![],
+        };
+        let mut shapes = self.shapes.clone();
+        for (id, shape) in &mut shapes {
+            if !shape.mixins().is_empty() {
+                let flattened = flattener.flatten(id)?;
+                *shape =
+                    serde_json::from_value(Value::Object(flattened.clone())).map_err(|source| {
+                        MixinError::Invalid {
+                            location: id.to_string(),
+                            source,
+                        }
+                    })?;
+            }
+        }
+        shapes.retain(|_, shape| !shape.traits().contains(&mixin_trait()));
+        Ok(Model {
+            smithy: self.smithy.clone(),
+            metadata: self.metadata.clone(),
+            shapes,
+        })
+    }
+}
+
+struct Flattener<'a> {
+    model: &'a Model,
+    /// Shapes with their mixins applied, in their JSON AST form.
+    flattened: HashMap<ShapeId, Map<String, Value>>,
+    /// Shapes being flattened, to detect cycles.
+    in_progress: Vec<ShapeId>,
+}
+
+impl Flattener<'_> {
+    fn flatten(&mut self, id: &ShapeId) -> Result<&Map<String, Value>, MixinError> {
+        if self.flattened.contains_key(id) {
+            return Ok(&self.flattened[id]);
+        }
+        if self.in_progress.contains(id) {
+            return Err(MixinError::Cycle {
+                location: id.to_string(),
+            });
+        }
+        self.in_progress.push(id.clone());
+
+        let shape = &self.model.shapes[id];
+        let Value::Object(mut own) = serde_json::to_value(shape).expect("shapes should serialize")
+        else {
+            unreachable!("shapes serialize to objects");
+        };
+        own.remove("mixins");
+
+        let mut inherited = Map::new();
+        for mixin in shape.mixins() {
+            let mixin = &mixin.target;
+            let Some(mixin_shape) = self.model.shapes.get(mixin) else {
+                return Err(MixinError::Undefined {
+                    location: id.to_string(),
+                    mixin: mixin.to_string(),
+                });
+            };
+            let Some(mixin_value) = mixin_shape.trait_value(&mixin_trait()) else {
+                return Err(MixinError::NotMixin {
+                    location: id.to_string(),
+                    mixin: mixin.to_string(),
+                });
+            };
+            if mixin_shape.type_name() != shape.type_name() {
+                return Err(MixinError::TypeMismatch {
+                    location: id.to_string(),
+                    shape_type: shape.type_name(),
+                    mixin: mixin.to_string(),
+                    mixin_type: mixin_shape.type_name(),
+                });
+            }
+
+            let mut copied = self.flatten(mixin)?.clone();
+            if let Some(Value::Object(traits)) = copied.get_mut("traits") {
+                traits.remove(MIXIN);
+                for local in mixin_value["localTraits"].as_array().into_iter().flatten() {
+                    traits.remove(local.as_str().unwrap_or_default());
+                }
+            }
+            merge(&mut inherited, copied);
+        }
+        check_members(id, &inherited, &own)?;
+        merge(&mut inherited, own);
+
+        self.in_progress.pop();
+        Ok(self.flattened.entry(id.clone()).or_insert(inherited))
+    }
+}
+
+/// Merges `over` into `under`: lists are concatenated, objects are merged recursively, except
+/// for traits, which are replaced whole, and other values in `over` replace those in `under`.
+fn merge(under: &mut Map<String, Value>, over: Map<String, Value>) {
+    for (key, value) in over {
+        let Some(existing) = under.get_mut(&key) else {
+            under.insert(key, value);
+            continue;
+        };
+        match (existing, value) {
+            (Value::Object(existing), Value::Object(value)) if key == "traits" => {
+                existing.extend(value);
+            }
+            (Value::Object(existing), Value::Object(value)) => merge(existing, value),
+            (Value::Array(existing), Value::Array(value)) => {
+                for item in value {
+                    if !existing.contains(&item) {
+                        existing.push(item);
+                    }
+                }
+            }
+            (existing, value) => *existing = value,
+        }
+    }
+}
+
+/// Checks that the shape's own members only redefine mixin members to add traits.
+fn check_members(
+    id: &ShapeId,
+    inherited: &Map<String, Value>,
+    own: &Map<String, Value>,
+) -> Result<(), MixinError> {
+    let members = |shape: &Map<String, Value>| {
+        let named = shape.get("members").and_then(Value::as_object).into_iter();
+        let named = named.flat_map(|members| members.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let fixed = ["member", "key", "value"]
+            .into_iter()
+            .filter_map(|name| Some((name.to_string(), shape.get(name)?.clone())));
+        named.chain(fixed).collect::<Map<_, _>>()
+    };
+    let inherited = members(inherited);
+    for (name, member) in members(own) {
+        let Some(mixin_member) = inherited.get(&name) else {
+            continue;
+        };
+        if member["target"] != mixin_member["target"] {
+            return Err(MixinError::MemberConflict {
+                location: id.to_string(),
+                member: name,
+                target: member["target"].as_str().unwrap_or_default().to_string(),
+                mixin_target: mixin_member["target"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn model(shapes: Value) -> Model {
+        serde_json::from_value(json!({ "smithy": "2.0", "shapes": shapes })).unwrap()
+    }
+
+    #[test]
+    fn flattens() {
+        let model = model(json!({
+            "example#Named": {
+                "type": "structure",
+                "members": {
+                    "name": { "target": "smithy.api#String" },
+                    "id": { "target": "smithy.api#String" },
+                },
+                "traits": {
+                    "smithy.api#mixin": { "localTraits": ["smithy.api#sensitive"] },
+                    "smithy.api#documentation": "Named.",
+                    "smithy.api#sensitive": {},
+                },
+            },
+            "example#Tagged": {
+                "type": "structure",
+                "mixins": [{ "target": "example#Named" }],
+                "members": { "tags": { "target": "example#Tags" } },
+                "traits": {
+                    "smithy.api#mixin": {},
+                    "smithy.api#documentation": "Tagged.",
+                },
+            },
+            "example#Tags": { "type": "list", "member": { "target": "smithy.api#String" } },
+            "example#Thing": {
+                "type": "structure",
+                "mixins": [{ "target": "example#Tagged" }],
+                "members": {
+                    "id": {
+                        "target": "smithy.api#String",
+                        "traits": { "smithy.api#required": {} },
+                    },
+                    "size": { "target": "smithy.api#Integer" },
+                },
+            },
+        }));
+        let flattened = model.flatten_mixins().unwrap();
+        assert_eq!(
+            serde_json::to_value(&flattened.shapes).unwrap(),
+            json!({
+                "example#Tags": { "type": "list", "member": { "target": "smithy.api#String" } },
+                "example#Thing": {
+                    "type": "structure",
+                    "members": {
+                        "name": { "target": "smithy.api#String" },
+                        "id": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#required": {} },
+                        },
+                        "tags": { "target": "example#Tags" },
+                        "size": { "target": "smithy.api#Integer" },
+                    },
+                    "traits": { "smithy.api#documentation": "Tagged." },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |shapes| model(shapes).flatten_mixins().unwrap_err().to_string();
+        let thing = |mixin: &str| {
+            json!({
+                "type": "structure",
+                "mixins": [{ "target": mixin }],
+                "members": { "id": { "target": "smithy.api#Integer" } },
+            })
+        };
+        let mixin = json!({
+            "type": "structure",
+            "members": { "id": { "target": "smithy.api#String" } },
+            "traits": { "smithy.api#mixin": {} },
+        });
+
+        assert_eq!(
+            error(json!({ "example#Thing": thing("example#Missing") })),
+            "example#Thing: mixin example#Missing is not defined"
+        );
+        assert_eq!(
+            error(json!({
+                "example#Thing": thing("example#Plain"),
+                "example#Plain": { "type": "structure", "members": {} },
+            })),
+            "example#Thing: example#Plain is not a mixin"
+        );
+        assert_eq!(
+            error(json!({
+                "example#Thing": thing("example#Name"),
+                "example#Name": { "type": "string", "traits": { "smithy.api#mixin": {} } },
+            })),
+            "example#Thing: mixin example#Name is a string rather than a structure"
+        );
+        assert_eq!(
+            error(json!({ "example#Thing": thing("example#Mixin"), "example#Mixin": mixin })),
+            "example#Thing: member \"id\" targets smithy.api#Integer rather than \
+             smithy.api#String as in a mixin"
+        );
+        let mut cyclic = mixin.clone();
+        cyclic["mixins"] = json!([{ "target": "example#Mixin" }]);
+        assert_eq!(
+            error(json!({ "example#Mixin": cyclic })),
+            "example#Mixin: mixins form a cycle"
+        );
+    }
+}
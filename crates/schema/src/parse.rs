@@ -0,0 +1,572 @@
+//! Parsing JSON AST models, with errors pointing at the offending value.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::Range;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use miette::{NamedSource, SourceSpan};
+use serde_json::Value;
+
+use crate::{upgrade_json_ast, Model, Shape, ShapeId, SmithyVersion};
+
+/// Options for [`crate::parse_model_with_options`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ParseOptions {
+    /// Reject traits this schema doesn't define. Otherwise they are collected into the
+    /// `unknown_traits` of each traits struct and reported as [`ParseWarning`]s.
+    pub strict: bool,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+#[error("{message}{}", at_path(.path))]
+#[diagnostic(code(laws_schema::parse))]
+pub struct ParseError {
+    /// The path to the offending value, e.g. `shapes."example#Thing".traits."smithy.api#foo"`.
+    pub path: String,
+    pub message: String,
+    #[source_code]
+    source_code: NamedSource,
+    #[label("here")]
+    span: SourceSpan,
+}
+
+fn at_path(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" at {path}")
+    }
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum ParseWarning {
+    #[error("unknown trait `{trait_id}` at {path}")]
+    #[diagnostic(code(laws_schema::parse::unknown_trait), severity(Warning))]
+    UnknownTrait {
+        trait_id: ShapeId,
+        path: String,
+        #[source_code]
+        source_code: NamedSource,
+        #[label("not defined by the schema")]
+        span: SourceSpan,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+pub(crate) fn parse_model(
+    name: &str,
+    source: &str,
+    options: &ParseOptions,
+) -> Result<(Model, Vec<ParseWarning>), ParseError> {
+    let model = deserialize_model(name, source)?;
+    let unknown_traits = unknown_traits(&model);
+    if unknown_traits.is_empty() {
+        return Ok((model, vec![]));
+    }
+
+    // Find each shape once up front, rather than scanning the source for every trait.
+    let shapes_path = [PathSegment::Key("shapes".to_string())];
+    let shape_offsets = locate(source, &shapes_path)
+        .and_then(|(_, shapes)| object_entries(source, shapes))
+        .into_iter()
+        .flatten()
+        .map(|(key, _, value)| (key, value))
+        .collect::<HashMap<_, _>>();
+    let mut located = unknown_traits
+        .into_iter()
+        .map(|(shape_key, path, trait_id)| {
+            let span = shape_offsets
+                .get(&shape_key)
+                .and_then(|&offset| locate_from(source, offset, &path))
+                .map_or(0..0, |(span, _)| span);
+            let mut full_path = shapes_path.to_vec();
+            full_path.push(PathSegment::Key(shape_key));
+            full_path.extend(path);
+            (trait_id, format_path(&full_path), span)
+        });
+
+    if options.strict {
+        let (trait_id, path, span) = located.next().expect("unknown traits are not empty");
+        return Err(ParseError {
+            path,
+            message: format!("unknown trait `{trait_id}`"),
+            source_code: NamedSource::new(name, source.to_string()),
+            span: (span.start, span.end - span.start).into(),
+        });
+    }
+
+    let source_code = Arc::<str>::from(source);
+    let warnings = located
+        .map(|(trait_id, path, span)| ParseWarning::UnknownTrait {
+            trait_id,
+            path,
+            source_code: NamedSource::new(name, source_code.clone()),
+            span: (span.start, span.end - span.start).into(),
+        })
+        .collect();
+    Ok((model, warnings))
+}
+
+/// Lists the unknown traits in the model, with the shape key and the path within the shape.
+fn unknown_traits(model: &Model) -> Vec<(String, Vec<PathSegment>, ShapeId)> {
+    let mut found = vec![];
+    for (shape_id, shape) in &model.shapes {
+        let mut add = |path: &[&str], traits: &IndexMap<ShapeId, Value>| {
+            for trait_id in traits.keys() {
+                let mut path = path
+                    .iter()
+                    .map(|key| PathSegment::Key(key.to_string()))
+                    .collect::<Vec<_>>();
+                path.push(PathSegment::Key("traits".to_string()));
+                path.push(PathSegment::Key(trait_id.to_string()));
+                found.push((shape_id.to_string(), path, trait_id.clone()));
+            }
+        };
+        match shape {
+            Shape::Service(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Operation(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Resource(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Structure(shape) => {
+                add(&[], &shape.traits.unknown_traits);
+                for (name, member) in &shape.members {
+                    add(&["members", name], &member.traits.unknown_traits);
+                }
+            }
+            Shape::List(shape) => {
+                add(&[], &shape.traits.unknown_traits);
+                add(&["member"], &shape.member.traits.unknown_traits);
+            }
+            Shape::Map(shape) => {
+                add(&[], &shape.traits.unknown_traits);
+                add(&["key"], &shape.key.traits.unknown_traits);
+                add(&["value"], &shape.value.traits.unknown_traits);
+            }
+            Shape::Union(shape) => {
+                add(&[], &shape.traits.unknown_traits);
+                for (name, member) in &shape.members {
+                    add(&["members", name], &member.traits.unknown_traits);
+                }
+            }
+            Shape::Enum(shape) => {
+                add(&[], &shape.traits.unknown_traits);
+                for (name, member) in &shape.members {
+                    add(&["members", name], &member.traits.unknown_traits);
+                }
+            }
+            Shape::Boolean(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Byte(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Short(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Integer(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Long(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Float(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Double(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::BigInteger(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::BigDecimal(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::String(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Blob(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Timestamp(shape) => add(&[], &shape.traits.unknown_traits),
+            Shape::Document(shape) => add(&[], &shape.traits.unknown_traits),
+        }
+    }
+    found
+}
+
+fn deserialize_model(name: &str, source: &str) -> Result<Model, ParseError> {
+    let mut deserializer = serde_json::Deserializer::from_str(source);
+    let result = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|error| ParseError::new(name, source, error_path(&error), error.into_inner()))
+        .and_then(|model: Model| {
+            deserializer
+                .end()
+                .map_err(|error| ParseError::new(name, source, vec![], error))?;
+            Ok(model)
+        });
+    if result
+        .as_ref()
+        .is_ok_and(|model| model.smithy == SmithyVersion::_2_0)
+    {
+        return result;
+    }
+
+    // Smithy 1.0 models can have shapes 2.0 models can't, such as sets, so they're upgraded in
+    // their JSON form. Errors still point into the source, as the upgrade keeps the paths.
+    #[derive(serde::Deserialize)]
+    struct Version {
+        smithy: SmithyVersion,
+    }
+    let is_1_0 = serde_json::from_str::<Version>(source)
+        .is_ok_and(|version| version.smithy == SmithyVersion::_1_0);
+    if !is_1_0 {
+        return result;
+    }
+    let mut ast = serde_json::from_str::<Value>(source)
+        .map_err(|error| ParseError::new(name, source, vec![], error))?;
+    upgrade_json_ast(&mut ast);
+    serde_path_to_error::deserialize(ast)
+        .map_err(|error| ParseError::new(name, source, error_path(&error), error.into_inner()))
+}
+
+fn error_path(error: &serde_path_to_error::Error<serde_json::Error>) -> Vec<PathSegment> {
+    error
+        .path()
+        .iter()
+        .map_while(|segment| match segment {
+            serde_path_to_error::Segment::Seq { index } => Some(PathSegment::Index(*index)),
+            serde_path_to_error::Segment::Map { key } => Some(PathSegment::Key(key.clone())),
+            serde_path_to_error::Segment::Enum { variant } => {
+                Some(PathSegment::Key(variant.clone()))
+            }
+            serde_path_to_error::Segment::Unknown => None,
+        })
+        .collect()
+}
+
+impl ParseError {
+    fn new(name: &str, source: &str, mut path: Vec<PathSegment>, error: serde_json::Error) -> Self {
+        let mut message = error.to_string();
+        if error.line() != 0 {
+            let position = format!(" at line {} column {}", error.line(), error.column());
+            if let Some(stripped) = message.strip_suffix(&position) {
+                message.truncate(stripped.len());
+            }
+        }
+
+        // The path ends at the object containing an unknown field, and values that serde buffers
+        // (flattened and untagged types) are only tracked as a whole, so look for the field.
+        let unknown_field = message
+            .strip_prefix("unknown field `")
+            .and_then(|rest| rest.split_once('`'))
+            .map(|(field, _)| field);
+        if let Some(field) = unknown_field {
+            if let Some(field_path) = locate(source, &path)
+                .and_then(|(_, value)| value_at(source, value))
+                .and_then(|value| find_key(&value, field))
+            {
+                path.extend(field_path);
+            }
+        }
+
+        let located = if error.is_syntax() || error.is_eof() || path.is_empty() {
+            None
+        } else {
+            locate(source, &path)
+        };
+        let span = match located {
+            Some((span, _)) => span,
+            None => {
+                let offset = line_offset(source, error.line()) + error.column().saturating_sub(1);
+                offset.min(source.len())..offset.min(source.len())
+            }
+        };
+
+        Self {
+            path: format_path(&path),
+            message,
+            source_code: NamedSource::new(name, source.to_string()),
+            span: (span.start, span.end - span.start).into(),
+        }
+    }
+}
+
+fn format_path(path: &[PathSegment]) -> String {
+    let mut formatted = String::new();
+    for segment in path {
+        match segment {
+            PathSegment::Key(key) => {
+                if !formatted.is_empty() {
+                    formatted.push('.');
+                }
+                let is_identifier = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if is_identifier {
+                    formatted.push_str(key);
+                } else {
+                    write!(formatted, "{key:?}").unwrap();
+                }
+            }
+            PathSegment::Index(index) => write!(formatted, "[{index}]").unwrap(),
+        }
+    }
+    formatted
+}
+
+fn line_offset(source: &str, line: usize) -> usize {
+    if line <= 1 {
+        return 0;
+    }
+    source
+        .match_indices('\n')
+        .nth(line - 2)
+        .map_or(source.len(), |(offset, _)| offset + 1)
+}
+
+/// Finds the path to the first occurrence of an object key in the value, nearest first.
+fn find_key(value: &Value, key: &str) -> Option<Vec<PathSegment>> {
+    match value {
+        Value::Object(object) => {
+            if object.contains_key(key) {
+                return Some(vec![PathSegment::Key(key.to_string())]);
+            }
+            object.iter().find_map(|(name, value)| {
+                let mut path = find_key(value, key)?;
+                path.insert(0, PathSegment::Key(name.clone()));
+                Some(path)
+            })
+        }
+        Value::Array(items) => items.iter().enumerate().find_map(|(index, value)| {
+            let mut path = find_key(value, key)?;
+            path.insert(0, PathSegment::Index(index));
+            Some(path)
+        }),
+        _ => None,
+    }
+}
+
+fn value_at(source: &str, start: usize) -> Option<Value> {
+    let end = skip_value(source.as_bytes(), start)?;
+    serde_json::from_str(&source[start..end]).ok()
+}
+
+/// Locates the value at the path in the JSON source, returning the span to label (the key for
+/// object members, the value otherwise) and the offset of the value.
+fn locate(source: &str, path: &[PathSegment]) -> Option<(Range<usize>, usize)> {
+    locate_from(source, skip_whitespace(source.as_bytes(), 0), path)
+}
+
+fn locate_from(source: &str, pos: usize, path: &[PathSegment]) -> Option<(Range<usize>, usize)> {
+    let bytes = source.as_bytes();
+    let mut pos = pos;
+    let mut span = pos..skip_value(bytes, pos)?;
+    for segment in path {
+        match segment {
+            PathSegment::Key(key) => {
+                let (_, key_span, value) = object_entries(source, pos)?
+                    .into_iter()
+                    .find(|(name, ..)| name == key)?;
+                (span, pos) = (key_span, value);
+            }
+            PathSegment::Index(index) => {
+                pos = locate_index(bytes, pos, *index)?;
+                span = pos..skip_value(bytes, pos)?;
+            }
+        }
+    }
+    Some((span, pos))
+}
+
+/// Lists the keys of the object at the offset, with their spans and the offsets of their values.
+fn object_entries(source: &str, pos: usize) -> Option<Vec<(String, Range<usize>, usize)>> {
+    let bytes = source.as_bytes();
+    if bytes.get(pos) != Some(&b'{') {
+        return None;
+    }
+    let mut entries = vec![];
+    let mut pos = skip_whitespace(bytes, pos + 1);
+    if bytes.get(pos) == Some(&b'}') {
+        return Some(entries);
+    }
+    loop {
+        let key_end = skip_string(bytes, pos)?;
+        let name: String = serde_json::from_str(&source[pos..key_end]).ok()?;
+        let colon = skip_whitespace(bytes, key_end);
+        if bytes.get(colon) != Some(&b':') {
+            return None;
+        }
+        let value = skip_whitespace(bytes, colon + 1);
+        entries.push((name, pos..key_end, value));
+        pos = skip_whitespace(bytes, skip_value(bytes, value)?);
+        match bytes.get(pos)? {
+            b',' => pos = skip_whitespace(bytes, pos + 1),
+            b'}' => return Some(entries),
+            _ => return None,
+        }
+    }
+}
+
+fn locate_index(bytes: &[u8], pos: usize, index: usize) -> Option<usize> {
+    if bytes.get(pos) != Some(&b'[') {
+        return None;
+    }
+    let mut pos = skip_whitespace(bytes, pos + 1);
+    for _ in 0..index {
+        pos = skip_whitespace(bytes, skip_value(bytes, pos)?);
+        if bytes.get(pos) != Some(&b',') {
+            return None;
+        }
+        pos = skip_whitespace(bytes, pos + 1);
+    }
+    (bytes.get(pos)? != &b']').then_some(pos)
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+    pos
+}
+
+fn skip_string(bytes: &[u8], pos: usize) -> Option<usize> {
+    if bytes.get(pos) != Some(&b'"') {
+        return None;
+    }
+    let mut pos = pos + 1;
+    loop {
+        match bytes.get(pos)? {
+            b'\\' => pos += 2,
+            b'"' => return Some(pos + 1),
+            _ => pos += 1,
+        }
+    }
+}
+
+fn skip_value(bytes: &[u8], pos: usize) -> Option<usize> {
+    match bytes.get(pos)? {
+        b'"' => skip_string(bytes, pos),
+        b'{' | b'[' => {
+            let mut depth = 0;
+            let mut pos = pos;
+            loop {
+                match bytes.get(pos)? {
+                    b'"' => {
+                        pos = skip_string(bytes, pos)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(pos + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                pos += 1;
+            }
+        }
+        _ => {
+            let mut pos = pos;
+            while bytes.get(pos).is_some_and(|byte| {
+                !matches!(byte, b',' | b'}' | b']') && !byte.is_ascii_whitespace()
+            }) {
+                pos += 1;
+            }
+            Some(pos)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn labeled<'a>(source: &'a str, error: &ParseError) -> &'a str {
+        &source[error.span.offset()..error.span.offset() + error.span.len()]
+    }
+
+    fn s3_source() -> String {
+        serde_json::to_string_pretty(&json!({
+            "smithy": "2.0",
+            "shapes": {
+                "com.amazonaws.s3#PutObject": {
+                    "type": "operation",
+                    "input": { "target": "com.amazonaws.s3#PutObjectRequest" },
+                    "output": { "target": "smithy.api#Unit" },
+                    "traits": { "smithy.api#documentation": "Adds an object.", "smithy.api#foo": {} },
+                },
+                "com.amazonaws.s3#PutObjectRequest": {
+                    "type": "structure",
+                    "members": {
+                        "Bucket": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#required": {}, "aws.api#bar": "baz" },
+                        },
+                    },
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn unknown_trait_strict() {
+        let source = s3_source();
+        let error = parse_model("s3.json", &source, &ParseOptions { strict: true }).unwrap_err();
+        assert_eq!(
+            error.path,
+            r#"shapes."com.amazonaws.s3#PutObject".traits."smithy.api#foo""#
+        );
+        assert_eq!(error.message, "unknown trait `smithy.api#foo`");
+        assert_eq!(labeled(&source, &error), r#""smithy.api#foo""#);
+    }
+
+    #[test]
+    fn unknown_trait_lenient() {
+        let source = s3_source();
+        let (model, warnings) = parse_model("s3.json", &source, &ParseOptions::default()).unwrap();
+        let warnings = warnings.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            [
+                r#"unknown trait `smithy.api#foo` at shapes."com.amazonaws.s3#PutObject".traits."smithy.api#foo""#,
+                r#"unknown trait `aws.api#bar` at shapes."com.amazonaws.s3#PutObjectRequest".members.Bucket.traits."aws.api#bar""#,
+            ]
+        );
+
+        let Some(Shape::Structure(request)) = model.shapes.values().nth(1) else {
+            panic!("expected a structure");
+        };
+        let bucket = &request.members["Bucket"].traits;
+        assert!(bucket.required.is_some());
+        assert_eq!(
+            serde_json::to_value(&bucket.unknown_traits).unwrap(),
+            json!({ "aws.api#bar": "baz" })
+        );
+    }
+
+    #[test]
+    fn unknown_field() {
+        let source = r#"{
+            "smithy": "2.0",
+            "shapes": {
+                "example#Thing": { "type": "structure", "members": {}, "extends": [] }
+            }
+        }"#;
+        let error = parse_model("example.json", source, &ParseOptions::default()).unwrap_err();
+        assert_eq!(error.path, r#"shapes."example#Thing".extends"#);
+        assert_eq!(labeled(source, &error), r#""extends""#);
+    }
+
+    #[test]
+    fn invalid_member() {
+        let source = r#"{
+            "smithy": "2.0",
+            "shapes": {
+                "example#Thing": {
+                    "type": "structure",
+                    "members": { "Name": { "target": 1 } }
+                }
+            }
+        }"#;
+        let error = parse_model("example.json", source, &ParseOptions::default()).unwrap_err();
+        assert_eq!(error.path, r#"shapes."example#Thing".members.Name.target"#);
+        assert_eq!(labeled(source, &error), r#""target""#);
+    }
+
+    #[test]
+    fn syntax_error() {
+        let source = "{\n  \"smithy\": \"2.0\",\n  \"shapes\": {]\n}";
+        let error = parse_model("example.json", source, &ParseOptions::default()).unwrap_err();
+        assert_eq!(error.path, "shapes");
+        assert_eq!(error.to_string(), "key must be a string at shapes");
+        assert_eq!(error.span.offset(), source.find(']').unwrap());
+    }
+}
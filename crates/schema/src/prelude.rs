@@ -0,0 +1,95 @@
+//! The Smithy prelude: shapes in the `smithy.api` namespace that every model can refer to
+//! without defining them.
+
+use std::sync::OnceLock;
+
+use indexmap::IndexMap;
+use serde_json::json;
+
+use crate::{Shape, ShapeId};
+
+/// The namespace of the prelude shapes and traits.
+pub const NAMESPACE: &str = "smithy.api";
+
+/// All the prelude shapes, with their traits.
+pub fn shapes() -> &'static IndexMap<ShapeId, Shape> {
+    static SHAPES: OnceLock<IndexMap<ShapeId, Shape>> = OnceLock::new();
+    SHAPES.get_or_init(|| {
+        let primitive = |type_name: &str, default| {
+            json!({ "type": type_name, "traits": { "smithy.api#default": default } })
+        };
+        let shapes = json!({
+            "smithy.api#String": { "type": "string" },
+            "smithy.api#Blob": { "type": "blob" },
+            "smithy.api#BigInteger": { "type": "bigInteger" },
+            "smithy.api#BigDecimal": { "type": "bigDecimal" },
+            "smithy.api#Timestamp": { "type": "timestamp" },
+            "smithy.api#Document": { "type": "document" },
+            "smithy.api#Boolean": { "type": "boolean" },
+            "smithy.api#PrimitiveBoolean": primitive("boolean", json!(false)),
+            "smithy.api#Byte": { "type": "byte" },
+            "smithy.api#PrimitiveByte": primitive("byte", json!(0)),
+            "smithy.api#Short": { "type": "short" },
+            "smithy.api#PrimitiveShort": primitive("short", json!(0)),
+            "smithy.api#Integer": { "type": "integer" },
+            "smithy.api#PrimitiveInteger": primitive("integer", json!(0)),
+            "smithy.api#Long": { "type": "long" },
+            "smithy.api#PrimitiveLong": primitive("long", json!(0)),
+            "smithy.api#Float": { "type": "float" },
+            "smithy.api#PrimitiveFloat": primitive("float", json!(0)),
+            "smithy.api#Double": { "type": "double" },
+            "smithy.api#PrimitiveDouble": primitive("double", json!(0)),
+            "smithy.api#Unit": {
+                "type": "structure",
+                "members": {},
+                "traits": { "smithy.api#unitType": {} },
+            },
+        });
+        serde_json::from_value(shapes).expect("prelude shapes should be valid")
+    })
+}
+
+/// Looks up a prelude shape.
+pub fn get(id: &ShapeId) -> Option<&'static Shape> {
+    if id.namespace != NAMESPACE {
+        return None;
+    }
+    shapes().get(id)
+}
+
+/// Whether the prelude defines a shape with this name, used to resolve relative shape ids.
+pub fn contains_name(name: &str) -> bool {
+    shapes().keys().any(|id| id.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitives_have_defaults() {
+        let id = |name: &str| ShapeId {
+            namespace: NAMESPACE.to_string(),
+            name: name.to_string(),
+            member: None,
+        };
+        let Some(Shape::Long(long)) = get(&id("PrimitiveLong")) else {
+            panic!("expected PrimitiveLong to be a long");
+        };
+        assert_eq!(long.traits.default, Some(0));
+        let Some(Shape::Long(long)) = get(&id("Long")) else {
+            panic!("expected Long to be a long");
+        };
+        assert_eq!(long.traits.default, None);
+        let Some(Shape::Structure(unit)) = get(&id("Unit")) else {
+            panic!("expected Unit to be a structure");
+        };
+        assert!(unit.traits.unit_type.is_some());
+        assert!(get(&ShapeId {
+            namespace: "example".to_string(),
+            name: "String".to_string(),
+            member: None,
+        })
+        .is_none());
+    }
+}
@@ -0,0 +1,160 @@
+//! Subsetting a model down to the shapes needed by some of its operations.
+
+use std::collections::HashSet;
+
+use crate::{Model, Shape, ShapeGraph, ShapeId, ShapeRef};
+
+impl Model {
+    /// Creates a model with only the given operations, the services and resources they are bound
+    /// to, and the shapes those reach.
+    ///
+    /// Services are always kept, with their bindings to other operations and resources removed,
+    /// as are their common errors. Ids that aren't operations in the model are ignored.
+    pub fn project(&self, operations: &[ShapeId]) -> Model {
+        let graph = ShapeGraph::new(self);
+        let selected = operations
+            .iter()
+            .filter(|id| matches!(self.shapes.get(*id), Some(Shape::Operation(_))))
+            .collect::<HashSet<_>>();
+        let resources = self
+            .shapes
+            .iter()
+            .filter(|(id, shape)| {
+                matches!(shape, Shape::Resource(_))
+                    && graph.closure(id).iter().any(|id| selected.contains(id))
+            })
+            .map(|(id, _)| id)
+            .collect::<HashSet<_>>();
+        let keep_ref = |shape_ref: &ShapeRef| {
+            selected.contains(&shape_ref.target) || resources.contains(&shape_ref.target)
+        };
+
+        // Remove the bindings to the operations and resources that aren't kept, then everything
+        // still reachable from what is kept is needed.
+        let mut projected = self.clone();
+        for (id, shape) in &mut projected.shapes {
+            match shape {
+                Shape::Service(service) => {
+                    service.operations.retain(keep_ref);
+                    service.resources.retain(keep_ref);
+                }
+                Shape::Resource(resource) if resources.contains(id) => {
+                    for lifecycle in [
+                        &mut resource.put,
+                        &mut resource.create,
+                        &mut resource.read,
+                        &mut resource.update,
+                        &mut resource.delete,
+                        &mut resource.list,
+                    ] {
+                        if !lifecycle.as_ref().is_some_and(keep_ref) {
+                            *lifecycle = None;
+                        }
+                    }
+                    resource.collection_operations.retain(keep_ref);
+                    resource.operations.retain(keep_ref);
+                    resource.resources.retain(keep_ref);
+                }
+                _ => {}
+            }
+        }
+
+        let projected_graph = ShapeGraph::new(&projected);
+        let needed = projected
+            .shapes
+            .iter()
+            .filter(|(id, shape)| {
+                matches!(shape, Shape::Service(_))
+                    || selected.contains(id)
+                    || resources.contains(id)
+            })
+            .flat_map(|(id, _)| projected_graph.closure(id))
+            .cloned()
+            .collect::<HashSet<_>>();
+        projected.shapes.retain(|id, _| needed.contains(id));
+        projected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn project() {
+        let model = serde_json::from_value::<Model>(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#Bucket": {
+                    "type": "resource",
+                    "identifiers": { "Name": { "target": "example#BucketName" } },
+                    "read": { "target": "example#GetBucket" },
+                    "delete": { "target": "example#DeleteBucket" },
+                    "resources": [{ "target": "example#Object" }],
+                },
+                "example#Object": {
+                    "type": "resource",
+                    "read": { "target": "example#GetObject" },
+                },
+                "example#GetBucket": {
+                    "type": "operation",
+                    "input": { "target": "example#GetBucketInput" },
+                    "output": { "target": "smithy.api#Unit" },
+                },
+                "example#GetBucketInput": {
+                    "type": "structure",
+                    "members": { "Name": { "target": "example#BucketName" } },
+                },
+                "example#BucketName": { "type": "string" },
+                "example#DeleteBucket": {
+                    "type": "operation",
+                    "input": { "target": "example#DeleteBucketInput" },
+                    "output": { "target": "smithy.api#Unit" },
+                },
+                "example#DeleteBucketInput": {
+                    "type": "structure",
+                    "members": { "Name": { "target": "example#BucketName" } },
+                },
+                "example#GetObject": {
+                    "type": "operation",
+                    "input": { "target": "smithy.api#Unit" },
+                    "output": { "target": "smithy.api#Unit" },
+                },
+            },
+        }))
+        .unwrap();
+        let id = |name: &str| ShapeId {
+            namespace: "example".to_string(),
+            name: name.to_string(),
+            member: None,
+        };
+
+        let projected = model.project(&[id("GetBucket"), id("Missing")]);
+        assert_eq!(
+            projected
+                .shapes
+                .keys()
+                .map(|id| id.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Bucket", "GetBucket", "GetBucketInput", "BucketName"]
+        );
+        let Some(Shape::Resource(bucket)) = projected.shapes.get(&id("Bucket")) else {
+            panic!("expected the bucket resource to be kept");
+        };
+        assert!(bucket.read.is_some());
+        assert!(bucket.delete.is_none());
+        assert!(bucket.resources.is_empty());
+
+        let projected = model.project(&[id("GetObject")]);
+        assert_eq!(
+            projected
+                .shapes
+                .keys()
+                .map(|id| id.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Bucket", "Object", "BucketName", "GetObject"]
+        );
+    }
+}
@@ -0,0 +1,936 @@
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::shapes::*;
+use crate::ShapeId;
+
+/// The traits applied to a shape or member, whatever its type.
+///
+/// Every typed `*Traits` struct implements this, so tools can look traits up by shape id without
+/// matching on each shape type. Values are in their JSON AST form.
+pub trait TraitSet {
+    /// All the traits, including unknown traits, by shape id.
+    fn to_values(&self) -> IndexMap<ShapeId, Value>;
+
+    fn get(&self, id: &ShapeId) -> Option<Value>;
+
+    fn contains(&self, id: &ShapeId) -> bool;
+}
+
+macro_rules! impl_trait_set {
+    ($($traits:ty),* $(,)?) => {
+        $(
+            impl TraitSet for $traits {
+                fn to_values(&self) -> IndexMap<ShapeId, Value> {
+                    to_values(self)
+                }
+
+                fn get(&self, id: &ShapeId) -> Option<Value> {
+                    find_trait(self, id, true)
+                }
+
+                fn contains(&self, id: &ShapeId) -> bool {
+                    find_trait(self, id, false).is_some()
+                }
+            }
+        )*
+    };
+}
+
+impl_trait_set!(
+    BooleanTraits,
+    ByteTraits,
+    ShortTraits,
+    IntegerTraits,
+    LongTraits,
+    FloatTraits,
+    DoubleTraits,
+    BigIntegerTraits,
+    BigDecimalTraits,
+    StringTraits,
+    BlobTraits,
+    TimestampTraits,
+    DocumentTraits,
+    ServiceTraits,
+    OperationTraits,
+    ResourceTraits,
+    StructureTraits,
+    MemberTraits,
+    ListTraits,
+    ListMemberTraits,
+    MapTraits,
+    MapShapeTraits,
+    UnionTraits,
+    UnionMemberTraits,
+    EnumTraits,
+    EnumMemberTraits,
+);
+
+// The typed traits serialize to the JSON AST `traits` object, keyed by valid shape ids.
+fn to_values(traits: &impl serde::Serialize) -> IndexMap<ShapeId, Value> {
+    let Value::Object(object) = serde_json::to_value(traits).expect("traits should serialize")
+    else {
+        unreachable!("traits should serialize to an object");
+    };
+    object
+        .into_iter()
+        .map(|(id, value)| (id.parse().expect("trait ids should be valid"), value))
+        .collect()
+}
+
+/// Finds a trait in a traits struct, serializing only its value, and only if `value` is set.
+///
+/// The typed traits serialize as a map from trait id to value, with unset traits skipped and
+/// unknown traits flattened in, so watching the keys go by finds a trait without serializing
+/// the others.
+fn find_trait(traits: &impl serde::Serialize, id: &ShapeId, value: bool) -> Option<Value> {
+    let mut finder = TraitFinder {
+        id: id.to_string(),
+        value,
+        matched: false,
+        found: None,
+    };
+    traits
+        .serialize(&mut finder)
+        .expect("traits should serialize");
+    finder.found
+}
+
+struct TraitFinder {
+    id: String,
+    /// Whether to serialize the value of the trait, or just note that it's set.
+    value: bool,
+    /// Whether the last key was the trait's id.
+    matched: bool,
+    found: Option<Value>,
+}
+
+impl TraitFinder {
+    fn field(&mut self, value: &(impl serde::Serialize + ?Sized)) -> serde_json::Result<()> {
+        if self.matched && self.found.is_none() {
+            self.found = Some(if self.value {
+                serde_json::to_value(value)?
+            } else {
+                Value::Null
+            });
+        }
+        self.matched = false;
+        Ok(())
+    }
+}
+
+/// Implements the serializer methods for values a serializer doesn't expect.
+macro_rules! unsupported {
+    ($(fn $method:ident $(<$T:ident>)? ($($arg:ty),*) -> $ok:ty;)*) => {
+        $(
+            fn $method $(<$T: ?Sized + serde::Serialize>)? (
+                self,
+                $(_: $arg),*
+            ) -> Result<$ok, Self::Error> {
+                Err(serde::ser::Error::custom("traits should serialize to an object"))
+            }
+        )*
+    };
+}
+
+impl serde::Serializer for &mut TraitFinder {
+    type Ok = ();
+    type Error = serde_json::Error;
+    type SerializeSeq = serde::ser::Impossible<(), Self::Error>;
+    type SerializeTuple = serde::ser::Impossible<(), Self::Error>;
+    type SerializeTupleStruct = serde::ser::Impossible<(), Self::Error>;
+    type SerializeTupleVariant = serde::ser::Impossible<(), Self::Error>;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = serde::ser::Impossible<(), Self::Error>;
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    unsupported! {
+        fn serialize_bool(bool) -> ();
+        fn serialize_i8(i8) -> ();
+        fn serialize_i16(i16) -> ();
+        fn serialize_i32(i32) -> ();
+        fn serialize_i64(i64) -> ();
+        fn serialize_u8(u8) -> ();
+        fn serialize_u16(u16) -> ();
+        fn serialize_u32(u32) -> ();
+        fn serialize_u64(u64) -> ();
+        fn serialize_f32(f32) -> ();
+        fn serialize_f64(f64) -> ();
+        fn serialize_char(char) -> ();
+        fn serialize_str(&str) -> ();
+        fn serialize_bytes(&[u8]) -> ();
+        fn serialize_none() -> ();
+        fn serialize_some<T>(&T) -> ();
+        fn serialize_unit() -> ();
+        fn serialize_unit_struct(&'static str) -> ();
+        fn serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        fn serialize_newtype_struct<T>(&'static str, &T) -> ();
+        fn serialize_newtype_variant<T>(&'static str, u32, &'static str, &T) -> ();
+        fn serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        fn serialize_tuple(usize) -> Self::SerializeTuple;
+        fn serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        fn serialize_tuple_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeTupleVariant;
+        fn serialize_struct_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeStructVariant;
+    }
+}
+
+impl serde::ser::SerializeMap for &mut TraitFinder {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_key<T: ?Sized + serde::Serialize>(&mut self, key: &T) -> serde_json::Result<()> {
+        self.matched = key.serialize(KeyMatcher(&self.id))?;
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + serde::Serialize>(
+        &mut self,
+        value: &T,
+    ) -> serde_json::Result<()> {
+        self.field(value)
+    }
+
+    fn end(self) -> serde_json::Result<()> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeStruct for &mut TraitFinder {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + serde::Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> serde_json::Result<()> {
+        self.matched = key == self.id;
+        self.field(value)
+    }
+
+    fn end(self) -> serde_json::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serializes a map key to whether it's the id.
+struct KeyMatcher<'a>(&'a str);
+
+impl serde::Serializer for KeyMatcher<'_> {
+    type Ok = bool;
+    type Error = serde_json::Error;
+    type SerializeSeq = serde::ser::Impossible<bool, Self::Error>;
+    type SerializeTuple = serde::ser::Impossible<bool, Self::Error>;
+    type SerializeTupleStruct = serde::ser::Impossible<bool, Self::Error>;
+    type SerializeTupleVariant = serde::ser::Impossible<bool, Self::Error>;
+    type SerializeMap = serde::ser::Impossible<bool, Self::Error>;
+    type SerializeStruct = serde::ser::Impossible<bool, Self::Error>;
+    type SerializeStructVariant = serde::ser::Impossible<bool, Self::Error>;
+
+    fn serialize_str(self, key: &str) -> Result<bool, Self::Error> {
+        Ok(key == self.0)
+    }
+
+    unsupported! {
+        fn serialize_bool(bool) -> bool;
+        fn serialize_i8(i8) -> bool;
+        fn serialize_i16(i16) -> bool;
+        fn serialize_i32(i32) -> bool;
+        fn serialize_i64(i64) -> bool;
+        fn serialize_u8(u8) -> bool;
+        fn serialize_u16(u16) -> bool;
+        fn serialize_u32(u32) -> bool;
+        fn serialize_u64(u64) -> bool;
+        fn serialize_f32(f32) -> bool;
+        fn serialize_f64(f64) -> bool;
+        fn serialize_char(char) -> bool;
+        fn serialize_bytes(&[u8]) -> bool;
+        fn serialize_none() -> bool;
+        fn serialize_some<T>(&T) -> bool;
+        fn serialize_unit() -> bool;
+        fn serialize_unit_struct(&'static str) -> bool;
+        fn serialize_unit_variant(&'static str, u32, &'static str) -> bool;
+        fn serialize_newtype_struct<T>(&'static str, &T) -> bool;
+        fn serialize_newtype_variant<T>(&'static str, u32, &'static str, &T) -> bool;
+        fn serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        fn serialize_tuple(usize) -> Self::SerializeTuple;
+        fn serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        fn serialize_tuple_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeTupleVariant;
+        fn serialize_map(Option<usize>) -> Self::SerializeMap;
+        fn serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+        fn serialize_struct_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeStructVariant;
+    }
+}
+
+/// The kind of value a trait takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraitValue {
+    /// An empty object, for traits that mark a shape rather than configure it.
+    Annotation,
+    Boolean,
+    Integer,
+    String,
+    List,
+    Object,
+    /// Any value, e.g. `smithy.api#default`, which takes a value of the shape it's applied to.
+    Document,
+}
+
+impl TraitValue {
+    /// Whether the value is of this kind.
+    pub fn accepts(self, value: &Value) -> bool {
+        match self {
+            TraitValue::Annotation => value.as_object().is_some_and(|object| object.is_empty()),
+            TraitValue::Boolean => value.is_boolean(),
+            TraitValue::Integer => value.is_u64() || value.is_i64(),
+            TraitValue::String => value.is_string(),
+            TraitValue::List => value.is_array(),
+            TraitValue::Object => value.is_object(),
+            TraitValue::Document => true,
+        }
+    }
+}
+
+/// A trait this schema knows about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraitDefinition {
+    pub id: &'static str,
+    /// The types of shape the trait can be applied to, as in [`Shape::type_name`], with
+    /// `member` for any member.
+    pub shape_types: &'static [&'static str],
+    pub value: TraitValue,
+}
+
+impl TraitDefinition {
+    pub fn shape_id(&self) -> ShapeId {
+        self.id
+            .parse()
+            .expect("trait definition ids should be valid")
+    }
+
+    /// Whether the trait can be applied to shapes of the type, as in [`Shape::type_name`].
+    pub fn applies_to(&self, type_name: &str) -> bool {
+        self.shape_types.contains(&type_name)
+    }
+}
+
+const fn definition(
+    id: &'static str,
+    shape_types: &'static [&'static str],
+    value: TraitValue,
+) -> TraitDefinition {
+    TraitDefinition {
+        id,
+        shape_types,
+        value,
+    }
+}
+
+/// Looks up the definition of a known trait.
+pub fn trait_definition(id: &ShapeId) -> Option<&'static TraitDefinition> {
+    let id = id.to_string();
+    TRAIT_DEFINITIONS
+        .binary_search_by(|definition| definition.id.cmp(&id))
+        .ok()
+        .map(|index| &TRAIT_DEFINITIONS[index])
+}
+
+/// Data shapes: every shape type except service, operation and resource.
+const DATA: &[&str] = &[
+    "boolean",
+    "byte",
+    "short",
+    "integer",
+    "long",
+    "float",
+    "double",
+    "bigInteger",
+    "bigDecimal",
+    "string",
+    "enum",
+    "blob",
+    "timestamp",
+    "document",
+    "structure",
+    "list",
+    "map",
+    "union",
+];
+
+/// Data shapes and members, for traits that describe data wherever it appears.
+const DATA_AND_MEMBERS: &[&str] = &[
+    "boolean",
+    "byte",
+    "short",
+    "integer",
+    "long",
+    "float",
+    "double",
+    "bigInteger",
+    "bigDecimal",
+    "string",
+    "enum",
+    "blob",
+    "timestamp",
+    "document",
+    "structure",
+    "list",
+    "map",
+    "union",
+    "member",
+];
+
+/// Every shape type, but not members, for the `:not(member)` selector.
+const SHAPES: &[&str] = &[
+    "service",
+    "operation",
+    "resource",
+    "boolean",
+    "byte",
+    "short",
+    "integer",
+    "long",
+    "float",
+    "double",
+    "bigInteger",
+    "bigDecimal",
+    "string",
+    "enum",
+    "blob",
+    "timestamp",
+    "document",
+    "structure",
+    "list",
+    "map",
+    "union",
+];
+
+/// Every shape type and members, for the `*` selector.
+const ALL: &[&str] = &[
+    "service",
+    "operation",
+    "resource",
+    "boolean",
+    "byte",
+    "short",
+    "integer",
+    "long",
+    "float",
+    "double",
+    "bigInteger",
+    "bigDecimal",
+    "string",
+    "enum",
+    "blob",
+    "timestamp",
+    "document",
+    "structure",
+    "list",
+    "map",
+    "union",
+    "member",
+];
+
+/// The traits with typed fields in the shapes' `*Traits` structs, sorted by id.
+///
+/// The shape types and values are those of the trait definitions in the Smithy specification,
+/// not just the shapes this schema happens to give each trait a field on. Selectors are reduced
+/// to the shape types they can match: `string` also matches `enum` shapes, and member
+/// selectors such as `structure > member :test(> string)` are just `member`, so a member trait
+/// may still not apply to a particular member. Traits for both shapes and the members targeting
+/// them, such as `length`, list `member` alongside those shape types.
+pub static TRAIT_DEFINITIONS: &[TraitDefinition] = &[
+    definition("aws.api#arn", &["resource"], TraitValue::Object),
+    definition(
+        "aws.api#arnReference",
+        &["string", "enum"],
+        TraitValue::Object,
+    ),
+    definition(
+        "aws.api#clientDiscoveredEndpoint",
+        &["operation"],
+        TraitValue::Object,
+    ),
+    definition(
+        "aws.api#clientEndpointDiscovery",
+        &["service"],
+        TraitValue::Object,
+    ),
+    definition(
+        "aws.api#controlPlane",
+        &["service", "operation", "resource"],
+        TraitValue::Annotation,
+    ),
+    definition("aws.api#data", DATA_AND_MEMBERS, TraitValue::String),
+    definition(
+        "aws.api#dataPlane",
+        &["service", "operation", "resource"],
+        TraitValue::Annotation,
+    ),
+    definition("aws.api#service", &["service"], TraitValue::Object),
+    definition("aws.api#tagEnabled", &["service"], TraitValue::Object),
+    definition("aws.api#taggable", &["resource"], TraitValue::Object),
+    definition("aws.auth#sigv4", &["service"], TraitValue::Object),
+    definition(
+        "aws.auth#unsignedPayload",
+        &["operation"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "aws.cloudformation#cfnAdditionalIdentifier",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "aws.cloudformation#cfnExcludeProperty",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "aws.cloudformation#cfnMutability",
+        &["member"],
+        TraitValue::String,
+    ),
+    definition(
+        "aws.cloudformation#cfnResource",
+        &["resource"],
+        TraitValue::Object,
+    ),
+    definition(
+        "aws.customizations#s3UnwrappedXmlOutput",
+        &["operation"],
+        TraitValue::Annotation,
+    ),
+    definition("aws.iam#actionName", &["operation"], TraitValue::String),
+    definition(
+        "aws.iam#actionPermissionDescription",
+        &["operation"],
+        TraitValue::String,
+    ),
+    definition(
+        "aws.iam#conditionKeys",
+        &["operation", "resource"],
+        TraitValue::List,
+    ),
+    definition(
+        "aws.iam#defineConditionKeys",
+        &["service"],
+        TraitValue::Object,
+    ),
+    definition(
+        "aws.iam#disableConditionKeyInference",
+        &["service", "resource"],
+        TraitValue::Annotation,
+    ),
+    definition("aws.iam#iamResource", &["resource"], TraitValue::Object),
+    definition("aws.iam#requiredActions", &["operation"], TraitValue::List),
+    definition(
+        "aws.iam#supportedPrincipalTypes",
+        &["service", "operation"],
+        TraitValue::List,
+    ),
+    definition("aws.protocols#awsJson1_0", &["service"], TraitValue::Object),
+    definition("aws.protocols#awsJson1_1", &["service"], TraitValue::Object),
+    definition(
+        "aws.protocols#awsQuery",
+        &["service"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "aws.protocols#awsQueryCompatible",
+        &["service"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "aws.protocols#awsQueryError",
+        &["structure"],
+        TraitValue::Object,
+    ),
+    definition(
+        "aws.protocols#ec2Query",
+        &["service"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "aws.protocols#ec2QueryName",
+        &["member"],
+        TraitValue::String,
+    ),
+    definition(
+        "aws.protocols#httpChecksum",
+        &["operation"],
+        TraitValue::Object,
+    ),
+    definition("aws.protocols#restJson1", &["service"], TraitValue::Object),
+    definition("aws.protocols#restXml", &["service"], TraitValue::Object),
+    definition(
+        "smithy.api#addedDefault",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#auth",
+        &["service", "operation"],
+        TraitValue::List,
+    ),
+    definition(
+        "smithy.api#clientOptional",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition("smithy.api#cors", &["service"], TraitValue::Object),
+    definition(
+        "smithy.api#default",
+        &[
+            "boolean",
+            "byte",
+            "short",
+            "integer",
+            "long",
+            "float",
+            "double",
+            "bigInteger",
+            "bigDecimal",
+            "string",
+            "enum",
+            "blob",
+            "timestamp",
+            "document",
+            "list",
+            "map",
+            "member",
+        ],
+        TraitValue::Document,
+    ),
+    definition("smithy.api#deprecated", ALL, TraitValue::Object),
+    definition("smithy.api#documentation", ALL, TraitValue::String),
+    definition("smithy.api#endpoint", &["operation"], TraitValue::Object),
+    definition("smithy.api#enum", &["string"], TraitValue::List),
+    definition("smithy.api#enumValue", &["member"], TraitValue::Document),
+    definition("smithy.api#error", &["structure"], TraitValue::String),
+    definition(
+        "smithy.api#eventPayload",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition("smithy.api#examples", &["operation"], TraitValue::List),
+    definition("smithy.api#externalDocumentation", ALL, TraitValue::Object),
+    definition("smithy.api#hostLabel", &["member"], TraitValue::Annotation),
+    definition("smithy.api#http", &["operation"], TraitValue::Object),
+    definition(
+        "smithy.api#httpBearerAuth",
+        &["service"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#httpChecksumRequired",
+        &["operation"],
+        TraitValue::Annotation,
+    ),
+    definition("smithy.api#httpError", &["structure"], TraitValue::Integer),
+    definition("smithy.api#httpHeader", &["member"], TraitValue::String),
+    definition("smithy.api#httpLabel", &["member"], TraitValue::Annotation),
+    definition(
+        "smithy.api#httpPayload",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#httpPrefixHeaders",
+        &["member"],
+        TraitValue::String,
+    ),
+    definition("smithy.api#httpQuery", &["member"], TraitValue::String),
+    definition(
+        "smithy.api#httpQueryParams",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#httpResponseCode",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#idempotencyToken",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#idempotent",
+        &["operation"],
+        TraitValue::Annotation,
+    ),
+    definition("smithy.api#input", &["structure"], TraitValue::Annotation),
+    definition("smithy.api#jsonName", &["member"], TraitValue::String),
+    definition(
+        "smithy.api#length",
+        &["string", "enum", "blob", "list", "map", "member"],
+        TraitValue::Object,
+    ),
+    definition(
+        "smithy.api#mediaType",
+        &["string", "enum", "blob"],
+        TraitValue::String,
+    ),
+    definition("smithy.api#mixin", SHAPES, TraitValue::Object),
+    definition(
+        "smithy.api#nestedProperties",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#noReplace",
+        &["resource"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#notProperty",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#optionalAuth",
+        &["operation"],
+        TraitValue::Annotation,
+    ),
+    definition("smithy.api#output", &["structure"], TraitValue::Annotation),
+    definition(
+        "smithy.api#paginated",
+        &["service", "operation"],
+        TraitValue::Object,
+    ),
+    definition(
+        "smithy.api#pattern",
+        &["string", "enum", "member"],
+        TraitValue::String,
+    ),
+    definition("smithy.api#property", &["member"], TraitValue::Object),
+    definition(
+        "smithy.api#range",
+        &[
+            "byte",
+            "short",
+            "integer",
+            "long",
+            "float",
+            "double",
+            "bigInteger",
+            "bigDecimal",
+            "member",
+        ],
+        TraitValue::Object,
+    ),
+    definition(
+        "smithy.api#readonly",
+        &["operation"],
+        TraitValue::Annotation,
+    ),
+    definition("smithy.api#recommended", &["member"], TraitValue::Object),
+    definition(
+        "smithy.api#references",
+        &["string", "enum", "structure"],
+        TraitValue::List,
+    ),
+    definition("smithy.api#required", &["member"], TraitValue::Annotation),
+    definition(
+        "smithy.api#requiresLength",
+        &["blob"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#resourceIdentifier",
+        &["member"],
+        TraitValue::String,
+    ),
+    definition("smithy.api#retryable", &["structure"], TraitValue::Object),
+    definition("smithy.api#sensitive", DATA, TraitValue::Annotation),
+    definition(
+        "smithy.api#sparse",
+        &["list", "map"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#streaming",
+        &["blob", "union"],
+        TraitValue::Annotation,
+    ),
+    definition("smithy.api#suppress", ALL, TraitValue::List),
+    definition("smithy.api#tags", ALL, TraitValue::List),
+    definition(
+        "smithy.api#timestampFormat",
+        &["timestamp", "member"],
+        TraitValue::String,
+    ),
+    definition(
+        "smithy.api#title",
+        &["service", "resource"],
+        TraitValue::String,
+    ),
+    definition("smithy.api#uniqueItems", &["list"], TraitValue::Annotation),
+    definition(
+        "smithy.api#unitType",
+        &["structure"],
+        TraitValue::Annotation,
+    ),
+    definition("smithy.api#unstable", ALL, TraitValue::Annotation),
+    definition(
+        "smithy.api#xmlAttribute",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#xmlFlattened",
+        &["member"],
+        TraitValue::Annotation,
+    ),
+    definition(
+        "smithy.api#xmlName",
+        &["structure", "union", "member"],
+        TraitValue::String,
+    ),
+    definition(
+        "smithy.api#xmlNamespace",
+        &[
+            "service",
+            "boolean",
+            "byte",
+            "short",
+            "integer",
+            "long",
+            "float",
+            "double",
+            "bigInteger",
+            "bigDecimal",
+            "string",
+            "enum",
+            "blob",
+            "timestamp",
+            "document",
+            "structure",
+            "list",
+            "map",
+            "union",
+            "member",
+        ],
+        TraitValue::Object,
+    ),
+    definition(
+        "smithy.rules#clientContextParams",
+        &["service"],
+        TraitValue::Object,
+    ),
+    definition("smithy.rules#contextParam", &["member"], TraitValue::Object),
+    definition(
+        "smithy.rules#endpointRuleSet",
+        &["service"],
+        TraitValue::Object,
+    ),
+    definition(
+        "smithy.rules#endpointTests",
+        &["service"],
+        TraitValue::Object,
+    ),
+    definition(
+        "smithy.rules#staticContextParams",
+        &["operation"],
+        TraitValue::Object,
+    ),
+    definition(
+        "smithy.waiters#waitable",
+        &["operation"],
+        TraitValue::Object,
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::Model;
+
+    #[test]
+    fn traits() {
+        let model = serde_json::from_value::<Model>(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#Password": {
+                    "type": "string",
+                    "traits": {
+                        "smithy.api#sensitive": {},
+                        "smithy.api#length": { "min": 8 },
+                    },
+                },
+                "example#Login": {
+                    "type": "structure",
+                    "members": {
+                        "password": {
+                            "target": "example#Password",
+                            "traits": { "smithy.api#required": {} },
+                        },
+                    },
+                    "traits": { "example#custom": "value" },
+                },
+                "example#Secret": {
+                    "type": "blob",
+                    "traits": { "smithy.api#sensitive": {} },
+                },
+            },
+        }))
+        .unwrap();
+        let id = |id: &str| id.parse::<ShapeId>().unwrap();
+
+        let sensitive = id("smithy.api#sensitive");
+        let shapes = model.shapes_with_trait(&sensitive).collect::<Vec<_>>();
+        assert_eq!(shapes, [&id("example#Password"), &id("example#Secret")]);
+
+        let password = &model.shapes[&id("example#Password")];
+        let length = password.trait_value(&id("smithy.api#length"));
+        assert_eq!(length, Some(json!({ "min": 8 })));
+        assert_eq!(password.traits().to_values().len(), 2);
+        for (id, value) in password.traits().to_values() {
+            assert_eq!(password.trait_value(&id), Some(value));
+        }
+        assert!(!password.traits().contains(&id("smithy.api#pattern")));
+
+        let login = &model.shapes[&id("example#Login")];
+        let custom = login.trait_value(&id("example#custom"));
+        assert_eq!(custom, Some(json!("value")));
+        let member = login.member_traits("password").unwrap();
+        assert!(member.contains(&id("smithy.api#required")));
+        assert!(login.member_traits("missing").is_none());
+
+        let definition = trait_definition(&sensitive).unwrap();
+        assert!(definition.applies_to("string"));
+        assert!(!definition.applies_to("operation"));
+        assert!(definition.value.accepts(&json!({})));
+        assert!(!definition.value.accepts(&json!(true)));
+        assert!(trait_definition(&id("example#custom")).is_none());
+        let definition = |trait_id| trait_definition(&id(trait_id)).unwrap();
+        assert!(definition("smithy.api#sensitive").applies_to("float"));
+        assert!(definition("smithy.api#deprecated").applies_to("blob"));
+        let aws_json = definition("aws.protocols#awsJson1_0");
+        assert!(aws_json
+            .value
+            .accepts(&json!({ "http": ["h2", "http/1.1"] })));
+        for definitions in TRAIT_DEFINITIONS.windows(2) {
+            assert!(definitions[0].id < definitions[1].id);
+        }
+        for definition in TRAIT_DEFINITIONS {
+            definition.shape_id();
+        }
+    }
+}
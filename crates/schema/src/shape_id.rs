@@ -1,41 +1,236 @@
 use std::fmt;
+use std::str::FromStr;
 
+use crate::prelude;
+
+/// An absolute shape id, e.g. `com.example#Shape`, or `com.example#Shape$member` for a member.
 #[derive(Clone, Hash, PartialEq, Eq, Ord, PartialOrd)]
 pub struct ShapeId {
     pub namespace: String,
     pub name: String,
+    pub member: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum ShapeIdError {
+    #[error("invalid shape id {id:?}: {message}")]
+    #[diagnostic(
+        code(laws_schema::shape_id::invalid),
+        help("shape ids look like `com.example#Shape` or `com.example#Shape$member`")
+    )]
+    Invalid { id: String, message: &'static str },
+    #[error("shape id {0:?} is relative")]
+    #[diagnostic(
+        code(laws_schema::shape_id::relative),
+        help("relative shape ids are resolved against a namespace with `ShapeId::resolve`")
+    )]
+    Relative(String),
+}
+
+impl ShapeId {
+    /// The id of a member of this shape.
+    pub fn with_member(&self, member: &str) -> ShapeId {
+        ShapeId {
+            member: Some(member.to_string()),
+            ..self.root()
+        }
+    }
+
+    /// The id of the shape itself, without any member.
+    pub fn root(&self) -> ShapeId {
+        ShapeId {
+            namespace: self.namespace.clone(),
+            name: self.name.clone(),
+            member: None,
+        }
+    }
+
+    /// Resolves an absolute or relative shape id as it appears in a model file. A relative id
+    /// names the shape imported with `use`, else the prelude shape if `is_prelude` says the name
+    /// refers to one, else a shape in the namespace.
+    pub fn resolve(
+        id: &str,
+        namespace: &str,
+        uses: &[ShapeId],
+        is_prelude: impl Fn(&str) -> bool,
+    ) -> Result<ShapeId, ShapeIdError> {
+        if id.contains('#') {
+            return id.parse();
+        }
+        let (name, member) = split_member(id)?;
+        if !is_identifier(name) {
+            return Err(invalid(id, "the shape name is not an identifier"));
+        }
+        let member = member.map(str::to_string);
+        if let Some(used) = uses.iter().find(|used| used.name == name) {
+            return Ok(ShapeId {
+                member,
+                ..used.root()
+            });
+        }
+        let namespace = if is_prelude(name) {
+            prelude::NAMESPACE
+        } else if is_namespace(namespace) {
+            namespace
+        } else {
+            return Err(invalid(
+                namespace,
+                "the namespace is not dot separated identifiers",
+            ));
+        };
+        Ok(ShapeId {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            member,
+        })
+    }
+}
+
+impl FromStr for ShapeId {
+    type Err = ShapeIdError;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        let Some((namespace, rest)) = id.split_once('#') else {
+            return Err(ShapeIdError::Relative(id.to_string()));
+        };
+        if !is_namespace(namespace) {
+            return Err(invalid(
+                id,
+                "the namespace is not dot separated identifiers",
+            ));
+        }
+        let (name, member) = split_member(rest)?;
+        if !is_identifier(name) {
+            return Err(invalid(id, "the shape name is not an identifier"));
+        }
+        Ok(ShapeId {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            member: member.map(str::to_string),
+        })
+    }
+}
+
+/// Splits `Shape$member` into the shape name and member, checking the member is an identifier.
+fn split_member(id: &str) -> Result<(&str, Option<&str>), ShapeIdError> {
+    let Some((name, member)) = id.split_once('$') else {
+        return Ok((id, None));
+    };
+    if !is_identifier(member) {
+        return Err(invalid(id, "the member name is not an identifier"));
+    }
+    Ok((name, Some(member)))
+}
+
+fn invalid(id: &str, message: &'static str) -> ShapeIdError {
+    ShapeIdError::Invalid {
+        id: id.to_string(),
+        message,
+    }
+}
+
+/// Whether the value is a Smithy identifier: ASCII letters, digits and underscores, starting
+/// with a letter or underscores followed by a letter or digit.
+pub fn is_identifier(value: &str) -> bool {
+    let rest = value.trim_start_matches('_');
+    let starts_validly = if rest.len() < value.len() {
+        rest.starts_with(|c: char| c.is_ascii_alphanumeric())
+    } else {
+        rest.starts_with(|c: char| c.is_ascii_alphabetic())
+    };
+    starts_validly && rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether the value is a namespace: dot separated identifiers.
+pub fn is_namespace(value: &str) -> bool {
+    value.split('.').all(is_identifier)
 }
 
 impl<'de> serde::Deserialize<'de> for ShapeId {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-        where
-            D: serde::Deserializer<'de>,
+    where
+        D: serde::Deserializer<'de>,
     {
         let shape_id = String::deserialize(deserializer)?;
-        if shape_id.contains('$') {
-            // used for method references
-            return Err(serde::de::Error::custom(format!(
-                "Unexpected '$' {shape_id}"
-            )));
-        }
-        let (namespace, name) = shape_id
-            .split_once('#')
-            .ok_or_else(|| serde::de::Error::custom(format!("Missing '#': {shape_id:?}")))?;
-        let namespace = namespace.to_string();
-        let name = name.to_string();
-        Ok(ShapeId { namespace, name })
+        shape_id.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl serde::Serialize for ShapeId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
     }
 }
 
 impl fmt::Debug for ShapeId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}#{}", self.namespace, self.name)
+        fmt::Display::fmt(self, f)
     }
 }
 
 impl fmt::Display for ShapeId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}#{}", self.namespace, self.name)
+        write!(f, "{}#{}", self.namespace, self.name)?;
+        if let Some(member) = &self.member {
+            write!(f, "${member}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_resolve() {
+        let id = "com.example#Shape$member".parse::<ShapeId>().unwrap();
+        assert_eq!(id.namespace, "com.example");
+        assert_eq!(id.name, "Shape");
+        assert_eq!(id.member.as_deref(), Some("member"));
+        assert_eq!(id.to_string(), "com.example#Shape$member");
+        assert_eq!(id.root().to_string(), "com.example#Shape");
+        assert_eq!(
+            id.root().with_member("other").to_string(),
+            "com.example#Shape$other"
+        );
+
+        let error = "Shape".parse::<ShapeId>().unwrap_err();
+        assert!(matches!(error, ShapeIdError::Relative(_)));
+        for invalid in [
+            "#Shape",
+            "com..example#Shape",
+            "com.example#",
+            "com.example#1Shape",
+            "com.example#__",
+            "com.example#Shape$",
+            "com.example#Shape$a$b",
+            "com.example#Sha-pe",
+        ] {
+            let error = invalid.parse::<ShapeId>().unwrap_err();
+            assert!(matches!(error, ShapeIdError::Invalid { .. }), "{invalid}");
+        }
+        assert!("_com._1#__Shape_".parse::<ShapeId>().is_ok());
+
+        let uses = ["other.ns#Imported".parse::<ShapeId>().unwrap()];
+        let resolve = |id: &str| {
+            ShapeId::resolve(id, "com.example", &uses, |name| {
+                name != "String" && prelude::contains_name(name)
+            })
+            .map(|id| id.to_string())
+        };
+        assert_eq!(
+            resolve("Imported$member").unwrap(),
+            "other.ns#Imported$member"
+        );
+        assert_eq!(resolve("Integer").unwrap(), "smithy.api#Integer");
+        assert_eq!(resolve("String").unwrap(), "com.example#String");
+        assert_eq!(resolve("Shape").unwrap(), "com.example#Shape");
+        assert_eq!(resolve("a.b#Shape").unwrap(), "a.b#Shape");
+        assert!(resolve("Shape$").is_err());
+        assert!(ShapeId::resolve("Shape", "com..example", &[], |_| false).is_err());
     }
 }
-    
@@ -2,10 +2,10 @@ use indexmap::IndexMap;
 
 use crate::shape_id::ShapeId;
 use crate::traits::*;
-use crate::ShapeRef;
+use crate::{ShapeRef, TraitSet};
 
-#[derive(Debug, serde::Deserialize)]
-#[serde(tag = "type", rename_all = "camelCase", deny_unknown_fields)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
 pub enum Shape {
     Service(Box<ServiceShape>),
     Operation(Box<OperationShape>),
@@ -18,10 +18,14 @@ pub enum Shape {
     Enum(Box<EnumShape>),
 
     Boolean(Box<BooleanShape>),
+    Byte(Box<ByteShape>),
+    Short(Box<ShortShape>),
     Integer(Box<IntegerShape>),
     Long(Box<LongShape>),
     Float(Box<FloatShape>),
     Double(Box<DoubleShape>),
+    BigInteger(Box<BigIntegerShape>),
+    BigDecimal(Box<BigDecimalShape>),
     String(Box<StringShape>),
     Blob(Box<BlobShape>),
 
@@ -29,727 +33,2099 @@ pub enum Shape {
     Document(Box<DocumentShape>),
 }
 
-#[derive(Debug, serde::Deserialize)]
+impl Shape {
+    /// The shape type name, as used in the `type` property of the JSON AST.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Shape::Service(_) => "service",
+            Shape::Operation(_) => "operation",
+            Shape::Resource(_) => "resource",
+            Shape::Structure(_) => "structure",
+            Shape::List(_) => "list",
+            Shape::Map(_) => "map",
+            Shape::Union(_) => "union",
+            Shape::Enum(_) => "enum",
+            Shape::Boolean(_) => "boolean",
+            Shape::Byte(_) => "byte",
+            Shape::Short(_) => "short",
+            Shape::Integer(_) => "integer",
+            Shape::Long(_) => "long",
+            Shape::Float(_) => "float",
+            Shape::Double(_) => "double",
+            Shape::BigInteger(_) => "bigInteger",
+            Shape::BigDecimal(_) => "bigDecimal",
+            Shape::String(_) => "string",
+            Shape::Blob(_) => "blob",
+            Shape::Timestamp(_) => "timestamp",
+            Shape::Document(_) => "document",
+        }
+    }
+
+    /// The mixins the shape uses, which are gone once the model's mixins are flattened.
+    pub fn mixins(&self) -> &[ShapeRef] {
+        match self {
+            Shape::Service(shape) => &shape.mixins,
+            Shape::Operation(shape) => &shape.mixins,
+            Shape::Resource(shape) => &shape.mixins,
+            Shape::Structure(shape) => &shape.mixins,
+            Shape::List(shape) => &shape.mixins,
+            Shape::Map(shape) => &shape.mixins,
+            Shape::Union(shape) => &shape.mixins,
+            Shape::Enum(shape) => &shape.mixins,
+            Shape::Boolean(shape) => &shape.mixins,
+            Shape::Byte(shape) => &shape.mixins,
+            Shape::Short(shape) => &shape.mixins,
+            Shape::Integer(shape) => &shape.mixins,
+            Shape::Long(shape) => &shape.mixins,
+            Shape::Float(shape) => &shape.mixins,
+            Shape::Double(shape) => &shape.mixins,
+            Shape::BigInteger(shape) => &shape.mixins,
+            Shape::BigDecimal(shape) => &shape.mixins,
+            Shape::String(shape) => &shape.mixins,
+            Shape::Blob(shape) => &shape.mixins,
+            Shape::Timestamp(shape) => &shape.mixins,
+            Shape::Document(shape) => &shape.mixins,
+        }
+    }
+
+    /// The traits applied to the shape, whatever its type.
+    pub fn traits(&self) -> &dyn TraitSet {
+        match self {
+            Shape::Service(shape) => &shape.traits,
+            Shape::Operation(shape) => &shape.traits,
+            Shape::Resource(shape) => &shape.traits,
+            Shape::Structure(shape) => &shape.traits,
+            Shape::List(shape) => &shape.traits,
+            Shape::Map(shape) => &shape.traits,
+            Shape::Union(shape) => &shape.traits,
+            Shape::Enum(shape) => &shape.traits,
+            Shape::Boolean(shape) => &shape.traits,
+            Shape::Byte(shape) => &shape.traits,
+            Shape::Short(shape) => &shape.traits,
+            Shape::Integer(shape) => &shape.traits,
+            Shape::Long(shape) => &shape.traits,
+            Shape::Float(shape) => &shape.traits,
+            Shape::Double(shape) => &shape.traits,
+            Shape::BigInteger(shape) => &shape.traits,
+            Shape::BigDecimal(shape) => &shape.traits,
+            Shape::String(shape) => &shape.traits,
+            Shape::Blob(shape) => &shape.traits,
+            Shape::Timestamp(shape) => &shape.traits,
+            Shape::Document(shape) => &shape.traits,
+        }
+    }
+
+    /// The value of a trait applied to the shape, in its JSON AST form.
+    pub fn trait_value(&self, id: &ShapeId) -> Option<serde_json::Value> {
+        self.traits().get(id)
+    }
+
+    /// The traits applied to a member of the shape, if it has a member with the name.
+    pub fn member_traits(&self, name: &str) -> Option<&dyn TraitSet> {
+        match self {
+            Shape::Structure(shape) => Some(&shape.members.get(name)?.traits),
+            Shape::Union(shape) => Some(&shape.members.get(name)?.traits),
+            Shape::Enum(shape) => Some(&shape.members.get(name)?.traits),
+            Shape::List(shape) if name == "member" => Some(&shape.member.traits),
+            Shape::Map(shape) if name == "key" => Some(&shape.key.traits),
+            Shape::Map(shape) if name == "value" => Some(&shape.value.traits),
+            _ => None,
+        }
+    }
+
+    fn deserialize_type<'de, D: serde::Deserializer<'de>>(
+        type_name: &str,
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        use serde::Deserialize;
+
+        const TYPE_NAMES: &[&str] = &[
+            "service",
+            "operation",
+            "resource",
+            "structure",
+            "list",
+            "map",
+            "union",
+            "enum",
+            "boolean",
+            "byte",
+            "short",
+            "integer",
+            "long",
+            "float",
+            "double",
+            "bigInteger",
+            "bigDecimal",
+            "string",
+            "blob",
+            "timestamp",
+            "document",
+        ];
+        Ok(match type_name {
+            "service" => Shape::Service(Box::deserialize(deserializer)?),
+            "operation" => Shape::Operation(Box::deserialize(deserializer)?),
+            "resource" => Shape::Resource(Box::deserialize(deserializer)?),
+            "structure" => Shape::Structure(Box::deserialize(deserializer)?),
+            "list" => Shape::List(Box::deserialize(deserializer)?),
+            "map" => Shape::Map(Box::deserialize(deserializer)?),
+            "union" => Shape::Union(Box::deserialize(deserializer)?),
+            "enum" => Shape::Enum(Box::deserialize(deserializer)?),
+            "boolean" => Shape::Boolean(Box::deserialize(deserializer)?),
+            "byte" => Shape::Byte(Box::deserialize(deserializer)?),
+            "short" => Shape::Short(Box::deserialize(deserializer)?),
+            "integer" => Shape::Integer(Box::deserialize(deserializer)?),
+            "long" => Shape::Long(Box::deserialize(deserializer)?),
+            "float" => Shape::Float(Box::deserialize(deserializer)?),
+            "double" => Shape::Double(Box::deserialize(deserializer)?),
+            "bigInteger" => Shape::BigInteger(Box::deserialize(deserializer)?),
+            "bigDecimal" => Shape::BigDecimal(Box::deserialize(deserializer)?),
+            "string" => Shape::String(Box::deserialize(deserializer)?),
+            "blob" => Shape::Blob(Box::deserialize(deserializer)?),
+            "timestamp" => Shape::Timestamp(Box::deserialize(deserializer)?),
+            "document" => Shape::Document(Box::deserialize(deserializer)?),
+            _ => return Err(serde::de::Error::unknown_variant(type_name, TYPE_NAMES)),
+        })
+    }
+}
+
+// Written by hand rather than with `#[serde(tag = "type")]`, which buffers the whole shape before
+// deserializing it and so loses the location of any errors inside it.
+impl<'de> serde::Deserialize<'de> for Shape {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ShapeVisitor)
+    }
+}
+
+struct ShapeVisitor;
+
+impl<'de> serde::de::Visitor<'de> for ShapeVisitor {
+    type Value = Shape;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a shape")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Shape, A::Error> {
+        use serde::de::Error;
+
+        let Some(key) = map.next_key::<String>()? else {
+            return Err(A::Error::missing_field("type"));
+        };
+        // Smithy always writes the type first, so the rest of the shape can be streamed.
+        if key == "type" {
+            let type_name = map.next_value::<String>()?;
+            return Shape::deserialize_type(
+                &type_name,
+                serde::de::value::MapAccessDeserializer::new(map),
+            );
+        }
+
+        let mut object = serde_json::Map::new();
+        object.insert(key, map.next_value()?);
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        let Some(serde_json::Value::String(type_name)) = object.remove("type") else {
+            return Err(A::Error::missing_field("type"));
+        };
+        Shape::deserialize_type(&type_name, serde_json::Value::Object(object))
+            .map_err(A::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct BooleanShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: BooleanTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct BooleanTraits {
-    #[serde(rename = "smithy.api#default", default)]
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub default: Option<bool>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub documentation: Option<String>,
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sensitive: Option<SensitiveTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ByteShape {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
+    pub traits: ByteTraits,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ByteTraits {
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default: Option<i8>,
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub documentation: Option<String>,
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub range: Option<RangeTrait>,
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sensitive: Option<SensitiveTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShortShape {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
+    pub traits: ShortTraits,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ShortTraits {
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default: Option<i16>,
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub range: Option<RangeTrait>,
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct IntegerShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: IntegerTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct IntegerTraits {
-    #[serde(rename = "smithy.api#default", default)]
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub default: Option<i32>,
-    #[serde(rename = "smithy.api#deprecated", default)]
+    #[serde(
+        rename = "smithy.api#deprecated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub deprecated: Option<DeprecatedTrait>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#range", default)]
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub range: Option<RangeTrait>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct LongShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: LongTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct LongTraits {
-    #[serde(rename = "smithy.api#default", default)]
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub default: Option<i64>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#range", default)]
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub range: Option<RangeTrait>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct FloatShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: FloatTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct FloatTraits {
-    #[serde(rename = "smithy.api#default", default)]
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_float_default"
+    )]
     pub default: Option<f32>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#range", default)]
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub range: Option<RangeTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct DoubleShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: DoubleTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct DoubleTraits {
-    #[serde(rename = "smithy.api#default", default)]
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_float_default"
+    )]
     pub default: Option<f64>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub documentation: Option<String>,
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub range: Option<RangeTrait>,
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sensitive: Option<SensitiveTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct BigIntegerShape {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
+    pub traits: BigIntegerTraits,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct BigIntegerTraits {
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default: Option<serde_json::Number>,
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub documentation: Option<String>,
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub range: Option<RangeTrait>,
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sensitive: Option<SensitiveTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct BigDecimalShape {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
+    pub traits: BigDecimalTraits,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct BigDecimalTraits {
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default: Option<serde_json::Number>,
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#range", default)]
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub range: Option<RangeTrait>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
+}
+
+// Smithy writes whole number defaults like `0` without a fraction, which serde would write as `0.0`.
+fn serialize_float_default<S, F>(value: &Option<F>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    F: Copy + Into<f64> + serde::Serialize,
+{
+    match *value {
+        Some(float) if float.into().fract() == 0.0 => {
+            serializer.serialize_some(&(float.into() as i64))
+        }
+        _ => serde::Serialize::serialize(value, serializer),
+    }
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct ServiceShape {
     pub version: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub operations: Vec<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub resources: Vec<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub errors: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
     pub traits: ServiceTraits,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct StringShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: StringTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct StringTraits {
-    #[serde(rename = "aws.api#arnReference", default)]
+    #[serde(
+        rename = "aws.api#arnReference",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub arn_reference: Option<ArnReferenceTrait>,
-    #[serde(rename = "aws.api#data", default)]
+    #[serde(
+        rename = "aws.api#data",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub data: Option<DataTrait>,
 
-    #[serde(rename = "smithy.api#deprecated", default)]
+    #[serde(
+        rename = "smithy.api#deprecated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub deprecated: Option<DeprecatedTrait>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#externalDocumentation", default)]
+    #[serde(
+        rename = "smithy.api#externalDocumentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub external_documentation: Option<IndexMap<String, String>>,
-    #[serde(rename = "smithy.api#enum", default)]
+    #[serde(
+        rename = "smithy.api#enum",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub enum_: Option<Vec<StringEnumItem>>,
-    #[serde(rename = "smithy.api#length", default)]
+    #[serde(
+        rename = "smithy.api#length",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub length: Option<LengthTrait>,
-    #[serde(rename = "smithy.api#mediaType", default)]
+    #[serde(
+        rename = "smithy.api#mediaType",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub media_type: Option<String>,
-    #[serde(rename = "smithy.api#pattern", default)]
+    #[serde(
+        rename = "smithy.api#pattern",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub pattern: Option<String>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct BlobShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: BlobTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct BlobTraits {
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#length", default)]
+    #[serde(
+        rename = "smithy.api#length",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub length: Option<LengthTrait>,
-    #[serde(rename = "smithy.api#mediaType", default)]
+    #[serde(
+        rename = "smithy.api#mediaType",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub media_type: Option<String>,
-    #[serde(rename = "smithy.api#requiresLength", default)]
+    #[serde(
+        rename = "smithy.api#requiresLength",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub requires_length: Option<RequiresLengthTrait>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
-    #[serde(rename = "smithy.api#streaming", default)]
+    #[serde(
+        rename = "smithy.api#streaming",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub streaming: Option<StreamingTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct TimestampShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: TimestampTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct TimestampTraits {
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#timestampFormat", default)]
+    #[serde(
+        rename = "smithy.api#timestampFormat",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub timestamp_format: Option<TimestampFormatTrait>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct DocumentShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: DocumentTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct DocumentTraits {
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct StringEnumItem {
     pub value: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub documentation: Option<String>,
 }
 
-#[derive(Debug, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct ServiceTraits {
-    #[serde(rename = "aws.api#clientEndpointDiscovery", default)]
+    #[serde(
+        rename = "aws.api#clientEndpointDiscovery",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub client_endpoint_discovery: Option<ClientEndpointDiscoveryTrait>,
-    #[serde(rename = "aws.api#controlPlane", default)]
+    #[serde(
+        rename = "aws.api#controlPlane",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub control_plane: Option<ControlPlaneTrait>,
-    #[serde(rename = "aws.api#dataPlane", default)]
+    #[serde(
+        rename = "aws.api#dataPlane",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub data_plane: Option<DataPlaneTrait>,
     #[serde(rename = "aws.api#service")]
     pub service: ServiceInfoTrait,
-    #[serde(rename = "aws.api#tagEnabled")]
+    #[serde(rename = "aws.api#tagEnabled", skip_serializing_if = "Option::is_none")]
     pub tag_enabled: Option<TagEnabledTrait>,
 
-    #[serde(rename = "aws.auth#sigv4", default)]
+    #[serde(
+        rename = "aws.auth#sigv4",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub auth_sigv4: Option<Sigv4Trait>,
 
-    #[serde(rename = "aws.iam#defineConditionKeys", default)]
+    #[serde(
+        rename = "aws.iam#defineConditionKeys",
+        default,
+        skip_serializing_if = "IndexMap::is_empty"
+    )]
     pub iam_define_condition_keys: IndexMap<String, IamConditionKeyDef>,
-    #[serde(rename = "aws.iam#supportedPrincipalTypes", default)]
+    #[serde(
+        rename = "aws.iam#supportedPrincipalTypes",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub iam_supported_principal_types: Vec<String>,
 
-    #[serde(rename = "aws.protocols#awsJson1_0", default)]
+    #[serde(
+        rename = "aws.protocols#awsJson1_0",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub protocols_aws_json_1_0: Option<AwsJson1_0Trait>,
-    #[serde(rename = "aws.protocols#awsJson1_1", default)]
+    #[serde(
+        rename = "aws.protocols#awsJson1_1",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub protocols_aws_json_1_1: Option<AwsJson1_1Trait>,
-    #[serde(rename = "aws.protocols#awsQuery", default)]
+    #[serde(
+        rename = "aws.protocols#awsQuery",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub protocols_aws_query: Option<AwsQueryTrait>,
-    #[serde(rename = "aws.protocols#awsQueryCompatible", default)]
+    #[serde(
+        rename = "aws.protocols#awsQueryCompatible",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub protocols_aws_query_compatible: Option<AwsQueryCompatibleTrait>,
-    #[serde(rename = "aws.protocols#awsQueryError", default)]
+    #[serde(
+        rename = "aws.protocols#awsQueryError",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub protocols_aws_query_error: Option<AwsQueryErrorTrait>,
-    #[serde(rename = "aws.protocols#ec2Query", default)]
+    #[serde(
+        rename = "aws.protocols#ec2Query",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub protocols_ec2_query: Option<Ec2QueryTrait>,
-    #[serde(rename = "aws.protocols#restJson1", default)]
+    #[serde(
+        rename = "aws.protocols#restJson1",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub protocols_rest_json_1: Option<RestJson1Trait>,
-    #[serde(rename = "aws.protocols#restXml", default)]
+    #[serde(
+        rename = "aws.protocols#restXml",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub protocols_rest_xml: Option<RestXmlTrait>,
 
-    #[serde(rename = "smithy.api#deprecated", default)]
+    #[serde(
+        rename = "smithy.api#deprecated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub deprecated: Option<DeprecatedTrait>,
-    #[serde(rename = "smithy.api#cors", default)]
+    #[serde(
+        rename = "smithy.api#cors",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub cors: Option<CorsTrait>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#externalDocumentation", default)]
+    #[serde(
+        rename = "smithy.api#externalDocumentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub external_documentation: Option<IndexMap<String, String>>,
-    #[serde(rename = "smithy.api#httpBearerAuth", default)]
+    #[serde(
+        rename = "smithy.api#httpBearerAuth",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_bearer_auth: Option<HttpBearerAuthTrait>,
-    #[serde(rename = "smithy.api#paginated", default)]
+    #[serde(
+        rename = "smithy.api#paginated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub paginated: Option<PaginatedTrait>,
-    #[serde(rename = "smithy.api#suppress", default)]
+    #[serde(
+        rename = "smithy.api#suppress",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub suppress: Vec<String>,
     #[serde(rename = "smithy.api#title")]
     pub title: String,
-    #[serde(rename = "smithy.api#unstable", default)]
+    #[serde(
+        rename = "smithy.api#unstable",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub unstable: Option<UnstableTrait>,
-    #[serde(rename = "smithy.api#xmlNamespace", default)]
+    #[serde(
+        rename = "smithy.api#xmlNamespace",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub xml_namespace: Option<XmlNamespaceTrait>,
 
-    #[serde(rename = "smithy.rules#clientContextParams", default)]
+    #[serde(
+        rename = "smithy.rules#clientContextParams",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub client_context_params: Option<IndexMap<String, ClientContextParamDef>>,
     #[serde(rename = "smithy.rules#endpointRuleSet")]
     pub endpoint_rule_set: EndpointRuleSetTrait,
     #[serde(rename = "smithy.rules#endpointTests")]
     pub endpoint_tests: EndpointTestsTrait,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct OperationShape {
     pub input: ShapeRef,
     pub output: ShapeRef,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub errors: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: OperationTraits,
 }
 
-#[derive(Debug, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct OperationTraits {
-    #[serde(rename = "aws.auth#unsignedPayload", default)]
+    #[serde(
+        rename = "aws.auth#unsignedPayload",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub auth_unsigned_payload: Option<UnsignedPayloadTrait>,
 
-    #[serde(rename = "aws.customizations#s3UnwrappedXmlOutput", default)]
+    #[serde(
+        rename = "aws.customizations#s3UnwrappedXmlOutput",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub customizations_s3_unwrapped_xml_output: Option<S3UnwrappedXmlOutputTrait>,
 
-    #[serde(rename = "aws.api#controlPlane", default)]
+    #[serde(
+        rename = "aws.api#controlPlane",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub control_plane: Option<ControlPlaneTrait>,
-    #[serde(rename = "aws.api#dataPlane", default)]
+    #[serde(
+        rename = "aws.api#dataPlane",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub data_plane: Option<DataPlaneTrait>,
 
-    #[serde(rename = "aws.api#clientDiscoveredEndpoint", default)]
+    #[serde(
+        rename = "aws.api#clientDiscoveredEndpoint",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub client_discovered_endpoint: Option<ClientDiscoveredEndpointTrait>,
 
-    #[serde(rename = "aws.iam#actionName", default)]
+    #[serde(
+        rename = "aws.iam#actionName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub iam_action_name: Option<String>,
-    #[serde(rename = "aws.iam#actionPermissionDescription", default)]
+    #[serde(
+        rename = "aws.iam#actionPermissionDescription",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub iam_action_permission_description: Option<String>,
-    #[serde(rename = "aws.iam#conditionKeys", default)]
+    #[serde(
+        rename = "aws.iam#conditionKeys",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub iam_condition_keys: Vec<String>,
-    #[serde(rename = "aws.iam#requiredActions", default)]
+    #[serde(
+        rename = "aws.iam#requiredActions",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub iam_required_actions: Vec<String>,
 
-    #[serde(rename = "aws.protocols#httpChecksum", default)]
+    #[serde(
+        rename = "aws.protocols#httpChecksum",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_checksum: Option<HttpChecksumTrait>,
 
-    #[serde(rename = "smithy.api#auth", default)]
+    #[serde(
+        rename = "smithy.api#auth",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub auth: Vec<String>,
-    #[serde(rename = "smithy.api#deprecated", default)]
+    #[serde(
+        rename = "smithy.api#deprecated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub deprecated: Option<DeprecatedTrait>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#externalDocumentation", default)]
+    #[serde(
+        rename = "smithy.api#externalDocumentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub external_documentation: Option<IndexMap<String, String>>,
-    #[serde(rename = "smithy.api#endpoint", default)]
+    #[serde(
+        rename = "smithy.api#endpoint",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub endpoint: Option<EndpointTrait>,
-    #[serde(rename = "smithy.api#examples", default)]
+    #[serde(
+        rename = "smithy.api#examples",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub examples: Vec<Example>,
-    #[serde(rename = "smithy.api#optionalAuth", default)]
+    #[serde(
+        rename = "smithy.api#optionalAuth",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub optional_auth: Option<OptionalAuthTrait>,
-    #[serde(rename = "smithy.api#http", default)]
+    #[serde(
+        rename = "smithy.api#http",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http: Option<HttpTrait>,
-    #[serde(rename = "smithy.api#httpChecksumRequired", default)]
+    #[serde(
+        rename = "smithy.api#httpChecksumRequired",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_checksum_required: Option<HttpChecksumRequiredTrait>,
-    #[serde(rename = "smithy.api#idempotent", default)]
+    #[serde(
+        rename = "smithy.api#idempotent",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub idempotent: Option<IdempotentTrait>,
-    #[serde(rename = "smithy.api#readonly", default)]
+    #[serde(
+        rename = "smithy.api#readonly",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub readonly: Option<ReadonlyTrait>,
-    #[serde(rename = "smithy.api#suppress", default)]
+    #[serde(
+        rename = "smithy.api#suppress",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub suppress: Vec<String>,
-    #[serde(rename = "smithy.api#tags", default)]
+    #[serde(
+        rename = "smithy.api#tags",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub tags: Vec<String>,
-    #[serde(rename = "smithy.api#paginated", default)]
+    #[serde(
+        rename = "smithy.api#paginated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub paginated: Option<PaginatedTrait>,
 
-    #[serde(rename = "smithy.rules#staticContextParams", default)]
+    #[serde(
+        rename = "smithy.rules#staticContextParams",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub static_context_params: Option<IndexMap<String, StaticParam>>,
 
-    #[serde(rename = "smithy.waiters#waitable", default)]
+    #[serde(
+        rename = "smithy.waiters#waitable",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub waitable: Option<WaitableTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct ResourceShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub identifiers: Option<IndexMap<String, ShapeRef>>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub put: Option<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub create: Option<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub read: Option<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub update: Option<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub delete: Option<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub list: Option<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub collection_operations: Vec<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub operations: Vec<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub resources: Vec<ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub properties: IndexMap<String, ShapeRef>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: ResourceTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct ResourceTraits {
-    #[serde(rename = "aws.api#arn", default)]
+    #[serde(
+        rename = "aws.api#arn",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub arn: Option<ArnTrait>,
-    #[serde(rename = "aws.api#controlPlane", default)]
+    #[serde(
+        rename = "aws.api#controlPlane",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub control_plane: Option<ControlPlaneTrait>,
-    #[serde(rename = "aws.api#dataPlane", default)]
+    #[serde(
+        rename = "aws.api#dataPlane",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub data_plane: Option<DataPlaneTrait>,
-    #[serde(rename = "aws.api#taggable", default)]
+    #[serde(
+        rename = "aws.api#taggable",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub taggable: Option<TaggableTrait>,
 
-    #[serde(rename = "aws.cloudformation#cfnResource", default)]
+    #[serde(
+        rename = "aws.cloudformation#cfnResource",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub cloudformation_cfn_resource: Option<CloudformationResourceTrait>,
 
-    #[serde(rename = "aws.iam#conditionKeys", default)]
+    #[serde(
+        rename = "aws.iam#conditionKeys",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub iam_condition_keys: Vec<String>,
-    #[serde(rename = "aws.iam#disableConditionKeyInference", default)]
+    #[serde(
+        rename = "aws.iam#disableConditionKeyInference",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub iam_disable_condition_key_inference: Option<IamDisableConditionKeyInferenceTrait>,
-    #[serde(rename = "aws.iam#iamResource", default)]
+    #[serde(
+        rename = "aws.iam#iamResource",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub iam_resource: Option<IamResourceTrait>,
 
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#externalDocumentation", default)]
+    #[serde(
+        rename = "smithy.api#externalDocumentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub external_documentation: Option<IndexMap<String, String>>,
-    #[serde(rename = "smithy.api#suppress", default)]
+    #[serde(
+        rename = "smithy.api#suppress",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub suppress: Vec<String>,
-    #[serde(rename = "smithy.api#noReplace", default)]
+    #[serde(
+        rename = "smithy.api#noReplace",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub no_replace: Option<NoReplaceTrait>,
-    #[serde(rename = "smithy.api#unstable", default)]
+    #[serde(
+        rename = "smithy.api#unstable",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub unstable: Option<UnstableTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct StructureShape {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub members: IndexMap<String, Member>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: StructureTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct StructureTraits {
-    #[serde(rename = "aws.protocols#awsQueryError", default)]
+    #[serde(
+        rename = "aws.protocols#awsQueryError",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub protocols_aws_query_error: Option<AwsQueryErrorTrait>,
 
-    #[serde(rename = "aws.api#data", default)]
+    #[serde(
+        rename = "aws.api#data",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub data: Option<DataTrait>,
 
-    #[serde(rename = "smithy.api#deprecated", default)]
+    #[serde(
+        rename = "smithy.api#deprecated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub deprecated: Option<DeprecatedTrait>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#externalDocumentation", default)]
+    #[serde(
+        rename = "smithy.api#externalDocumentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub external_documentation: Option<IndexMap<String, String>>,
-    #[serde(rename = "smithy.api#error", default)]
+    #[serde(
+        rename = "smithy.api#error",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub error: Option<ErrorTrait>,
-    #[serde(rename = "smithy.api#input", default)]
+    #[serde(
+        rename = "smithy.api#input",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub input: Option<InputTrait>,
-    #[serde(rename = "smithy.api#output", default)]
+    #[serde(
+        rename = "smithy.api#output",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub output: Option<OutputTrait>,
-    #[serde(rename = "smithy.api#httpError", default)]
+    #[serde(
+        rename = "smithy.api#httpError",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_error: Option<u32>,
-    #[serde(rename = "smithy.api#retryable", default)]
+    #[serde(
+        rename = "smithy.api#retryable",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub retryable: Option<RetryableTrait>,
-    #[serde(rename = "smithy.api#references", default)]
+    #[serde(
+        rename = "smithy.api#references",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub references: Vec<Reference>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
-    #[serde(rename = "smithy.api#xmlName", default)]
+    #[serde(
+        rename = "smithy.api#unitType",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub unit_type: Option<UnitTypeTrait>,
+    #[serde(
+        rename = "smithy.api#xmlName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub xml_name: Option<String>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Member {
     pub target: ShapeId,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: MemberTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct MemberTraits {
-    #[serde(rename = "aws.cloudformation#cfnAdditionalIdentifier", default)]
+    #[serde(
+        rename = "aws.cloudformation#cfnAdditionalIdentifier",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub cloudformation_cfn_additional_identifier: Option<CloudformationAdditionalIdentifierTrait>,
-    #[serde(rename = "aws.cloudformation#cfnExcludeProperty", default)]
+    #[serde(
+        rename = "aws.cloudformation#cfnExcludeProperty",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub cloudformation_exclude_property: Option<CloudformationExcludePropertyTrait>,
-    #[serde(rename = "aws.cloudformation#cfnMutability", default)]
+    #[serde(
+        rename = "aws.cloudformation#cfnMutability",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub cloudformation_mutability: Option<CloudformationMutabilityTrait>,
 
-    #[serde(rename = "aws.protocols#ec2QueryName", default)]
+    #[serde(
+        rename = "aws.protocols#ec2QueryName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub ec2_query_name: Option<String>,
 
-    #[serde(rename = "smithy.api#addedDefault", default)]
+    #[serde(
+        rename = "smithy.api#addedDefault",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub added_default: Option<AddedDefaultTrait>,
-    #[serde(rename = "smithy.api#clientOptional", default)]
+    #[serde(
+        rename = "smithy.api#clientOptional",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub client_optional: Option<ClientOptionalTrait>,
-    #[serde(rename = "smithy.api#default", default)]
+    /// `null` if the member has no default, though its target does.
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        deserialize_with = "crate::deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub default: Option<serde_json::Value>,
-    #[serde(rename = "smithy.api#deprecated", default)]
+    #[serde(
+        rename = "smithy.api#deprecated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub deprecated: Option<DeprecatedTrait>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#externalDocumentation", default)]
+    #[serde(
+        rename = "smithy.api#externalDocumentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub external_documentation: Option<IndexMap<String, String>>,
-    #[serde(rename = "smithy.api#eventPayload", default)]
+    #[serde(
+        rename = "smithy.api#eventPayload",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub event_payload: Option<EventPayloadTrait>,
-    #[serde(rename = "smithy.api#hostLabel", default)]
+    #[serde(
+        rename = "smithy.api#hostLabel",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub host_label: Option<HostLabelTrait>,
-    #[serde(rename = "smithy.api#httpLabel", default)]
+    #[serde(
+        rename = "smithy.api#httpLabel",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_label: Option<HttpLabelTrait>,
-    #[serde(rename = "smithy.api#httpHeader", default)]
+    #[serde(
+        rename = "smithy.api#httpHeader",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_header: Option<String>,
-    #[serde(rename = "smithy.api#httpPrefixHeaders", default)]
+    #[serde(
+        rename = "smithy.api#httpPrefixHeaders",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_prefix_headers: Option<String>,
-    #[serde(rename = "smithy.api#httpPayload", default)]
+    #[serde(
+        rename = "smithy.api#httpPayload",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_payload: Option<HttpPayloadTrait>,
-    #[serde(rename = "smithy.api#httpQuery", default)]
+    #[serde(
+        rename = "smithy.api#httpQuery",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_query: Option<String>,
-    #[serde(rename = "smithy.api#httpQueryParams", default)]
+    #[serde(
+        rename = "smithy.api#httpQueryParams",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_query_params: Option<HttpQueryParamsTrait>,
-    #[serde(rename = "smithy.api#httpResponseCode", default)]
+    #[serde(
+        rename = "smithy.api#httpResponseCode",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub http_response_code: Option<HttpResponseCodeTrait>,
-    #[serde(rename = "smithy.api#idempotencyToken", default)]
+    #[serde(
+        rename = "smithy.api#idempotencyToken",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub idempotency_token: Option<IdempotencyTokenTrait>,
-    #[serde(rename = "smithy.api#jsonName", default)]
+    #[serde(
+        rename = "smithy.api#jsonName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub json_name: Option<String>,
-    #[serde(rename = "smithy.api#length", default)]
+    #[serde(
+        rename = "smithy.api#length",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub length: Option<LengthTrait>,
-    #[serde(rename = "smithy.api#nestedProperties", default)]
+    #[serde(
+        rename = "smithy.api#nestedProperties",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub nested_properties: Option<NestedPropertiesTrait>,
-    #[serde(rename = "smithy.api#notProperty", default)]
+    #[serde(
+        rename = "smithy.api#notProperty",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub not_property: Option<NotPropertyTrait>,
-    #[serde(rename = "smithy.api#pattern", default)]
+    #[serde(
+        rename = "smithy.api#pattern",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub pattern: Option<String>,
-    #[serde(rename = "smithy.api#property", default)]
+    #[serde(
+        rename = "smithy.api#property",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub property: Option<PropertyTrait>,
-    #[serde(rename = "smithy.api#range", default)]
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub range: Option<RangeTrait>,
-    #[serde(rename = "smithy.api#recommended", default)]
+    #[serde(
+        rename = "smithy.api#recommended",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub recommended: Option<RecommendedTrait>,
-    #[serde(rename = "smithy.api#resourceIdentifier", default)]
+    #[serde(
+        rename = "smithy.api#resourceIdentifier",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub resource_identifier: Option<String>,
-    #[serde(rename = "smithy.api#required", default)]
+    #[serde(
+        rename = "smithy.api#required",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub required: Option<RequiredTrait>,
-    #[serde(rename = "smithy.api#suppress", default)]
+    #[serde(
+        rename = "smithy.api#suppress",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub suppress: Vec<String>,
-    #[serde(rename = "smithy.api#tags", default)]
+    #[serde(
+        rename = "smithy.api#tags",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub tags: Vec<String>,
-    #[serde(rename = "smithy.api#timestampFormat", default)]
+    #[serde(
+        rename = "smithy.api#timestampFormat",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub timestamp_format: Option<TimestampFormatTrait>,
-    #[serde(rename = "smithy.api#unstable", default)]
+    #[serde(
+        rename = "smithy.api#unstable",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub unstable: Option<UnstableTrait>,
-    #[serde(rename = "smithy.api#xmlAttribute", default)]
+    #[serde(
+        rename = "smithy.api#xmlAttribute",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub xml_attribute: Option<XmlAttributeTrait>,
-    #[serde(rename = "smithy.api#xmlNamespace", default)]
+    #[serde(
+        rename = "smithy.api#xmlNamespace",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub xml_namespace: Option<XmlNamespaceTrait>,
-    #[serde(rename = "smithy.api#xmlName", default)]
+    #[serde(
+        rename = "smithy.api#xmlName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub xml_name: Option<String>,
-    #[serde(rename = "smithy.api#xmlFlattened", default)]
+    #[serde(
+        rename = "smithy.api#xmlFlattened",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub xml_flattened: Option<XmlFlattenedTrait>,
 
-    #[serde(rename = "smithy.rules#contextParam", default)]
+    #[serde(
+        rename = "smithy.rules#contextParam",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub context_param: Option<ContextParamTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct ListShape {
     pub member: ListMember,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: ListTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct ListTraits {
-    #[serde(rename = "smithy.api#deprecated", default)]
+    #[serde(
+        rename = "smithy.api#deprecated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub deprecated: Option<DeprecatedTrait>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#externalDocumentation", default)]
+    #[serde(
+        rename = "smithy.api#externalDocumentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub external_documentation: Option<IndexMap<String, String>>,
-    #[serde(rename = "smithy.api#length", default)]
+    #[serde(
+        rename = "smithy.api#length",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub length: Option<LengthTrait>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
-    #[serde(rename = "smithy.api#sparse", default)]
+    #[serde(
+        rename = "smithy.api#sparse",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sparse: Option<SparseTrait>,
-    #[serde(rename = "smithy.api#uniqueItems", default)]
+    #[serde(
+        rename = "smithy.api#uniqueItems",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub unique_items: Option<UniqueItemsTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct ListMember {
     pub target: ShapeId,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: ListMemberTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct ListMemberTraits {
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#length", default)]
+    #[serde(
+        rename = "smithy.api#length",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub length: Option<LengthTrait>,
-    #[serde(rename = "smithy.api#tags", default)]
+    #[serde(
+        rename = "smithy.api#pattern",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pattern: Option<String>,
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub range: Option<RangeTrait>,
+    #[serde(
+        rename = "smithy.api#tags",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub tags: Vec<String>,
-    #[serde(rename = "smithy.api#xmlName", default)]
+    #[serde(
+        rename = "smithy.api#timestampFormat",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timestamp_format: Option<TimestampFormatTrait>,
+    #[serde(
+        rename = "smithy.api#xmlName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub xml_name: Option<String>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct MapShape {
     pub key: MapShapeRef,
     pub value: MapShapeRef,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: MapTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct MapTraits {
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#length", default)]
+    #[serde(
+        rename = "smithy.api#length",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub length: Option<LengthTrait>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
-    #[serde(rename = "smithy.api#sparse", default)]
+    #[serde(
+        rename = "smithy.api#sparse",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sparse: Option<SparseTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct MapShapeRef {
     pub target: ShapeId,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: MapShapeTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct MapShapeTraits {
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#length", default)]
+    #[serde(
+        rename = "smithy.api#length",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub length: Option<LengthTrait>,
-    #[serde(rename = "smithy.api#pattern", default)]
+    #[serde(
+        rename = "smithy.api#pattern",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub pattern: Option<String>,
-    #[serde(rename = "smithy.api#tags", default)]
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub range: Option<RangeTrait>,
+    #[serde(
+        rename = "smithy.api#tags",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub tags: Vec<String>,
-    #[serde(rename = "smithy.api#xmlName", default)]
+    #[serde(
+        rename = "smithy.api#timestampFormat",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timestamp_format: Option<TimestampFormatTrait>,
+    #[serde(
+        rename = "smithy.api#xmlName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub xml_name: Option<String>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct UnionShape {
     pub members: IndexMap<String, UnionMember>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: UnionTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct UnionTraits {
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
-    #[serde(rename = "smithy.api#streaming", default)]
+    #[serde(
+        rename = "smithy.api#streaming",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub streaming: Option<StreamingTrait>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct UnionMember {
     pub target: ShapeId,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: UnionMemberTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct UnionMemberTraits {
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#length", default)]
+    #[serde(
+        rename = "smithy.api#length",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub length: Option<LengthTrait>,
-    #[serde(rename = "smithy.api#range", default)]
+    #[serde(
+        rename = "smithy.api#pattern",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pattern: Option<String>,
+    #[serde(
+        rename = "smithy.api#range",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub range: Option<RangeTrait>,
-    #[serde(rename = "smithy.api#tags", default)]
+    #[serde(
+        rename = "smithy.api#tags",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub tags: Vec<String>,
+    #[serde(
+        rename = "smithy.api#timestampFormat",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timestamp_format: Option<TimestampFormatTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct EnumShape {
     pub members: IndexMap<String, EnumMember>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixins: Vec<ShapeRef>,
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: EnumTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct EnumTraits {
-    #[serde(rename = "smithy.api#default", default)]
+    #[serde(
+        rename = "smithy.api#default",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub default: Option<String>,
-    #[serde(rename = "smithy.api#deprecated", default)]
+    #[serde(
+        rename = "smithy.api#deprecated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub deprecated: Option<DeprecatedTrait>,
-    #[serde(rename = "smithy.api#documentation", default)]
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub documentation: Option<String>,
-    #[serde(rename = "smithy.api#sensitive", default)]
+    #[serde(
+        rename = "smithy.api#sensitive",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub sensitive: Option<SensitiveTrait>,
-    #[serde(rename = "smithy.api#length", default)]
+    #[serde(
+        rename = "smithy.api#length",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub length: Option<LengthTrait>,
-    #[serde(rename = "smithy.api#pattern", default)]
+    #[serde(
+        rename = "smithy.api#pattern",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
     pub pattern: Option<String>,
+    #[serde(
+        rename = "smithy.api#mixin",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mixin: Option<MixinTrait>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct EnumMember {
     pub target: ShapeId,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "crate::is_default")]
     pub traits: EnumMemberTraits,
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
-#[serde(deny_unknown_fields)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct EnumMemberTraits {
+    #[serde(
+        rename = "smithy.api#deprecated",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deprecated: Option<DeprecatedTrait>,
+    #[serde(
+        rename = "smithy.api#documentation",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub documentation: Option<String>,
     #[serde(rename = "smithy.api#enumValue")]
     pub enum_value: String,
+    #[serde(
+        rename = "smithy.api#tags",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub unknown_traits: IndexMap<ShapeId, serde_json::Value>,
 }
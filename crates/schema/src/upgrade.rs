@@ -0,0 +1,320 @@
+//! Upgrading Smithy 1.0 models to the equivalent 2.0 models, which is all this schema describes.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::{is_identifier, prelude, trait_definition, ShapeId};
+
+const BOX: &str = "smithy.api#box";
+const DEFAULT: &str = "smithy.api#default";
+const ENUM: &str = "smithy.api#enum";
+
+/// Upgrades a Smithy 1.0 JSON AST to 2.0 in place, leaving 2.0 models as they are.
+///
+/// - `set` shapes become `list` shapes with the `uniqueItems` trait.
+/// - Boolean and number shapes without the `box` trait get a zero `default`, as do structure
+///   members targeting them (or the prelude's `Primitive*` shapes) without the `box` trait.
+///   Members with the `box` trait get a `null` default instead, so they stay nullable. The
+///   `box` trait itself is removed.
+/// - Operations without an input or output get `smithy.api#Unit`.
+/// - String shapes with the `enum` trait become `enum` shapes, when every value has a name and
+///   the shape's other traits can be applied to enums.
+///
+/// Only shapes defined in the same model are considered when looking up member targets, so a
+/// member targeting an unboxed shape in another file is left without a default.
+pub fn upgrade_json_ast(ast: &mut Value) {
+    if !matches!(ast["smithy"].as_str(), Some("1" | "1.0")) {
+        return;
+    }
+    ast["smithy"] = json!("2.0");
+    let Some(shapes) = ast.get_mut("shapes").and_then(Value::as_object_mut) else {
+        return;
+    };
+    let zero_values = shapes
+        .iter()
+        .filter_map(|(id, shape)| Some((id.clone(), zero_value(shape.as_object()?)?)))
+        .collect::<HashMap<_, _>>();
+    for shape in shapes.values_mut() {
+        if let Value::Object(shape) = shape {
+            upgrade_shape(shape, &zero_values);
+        }
+    }
+}
+
+/// The default of a shape that 1.0 considers primitive: a boolean or number without `box`.
+fn zero_value(shape: &Map<String, Value>) -> Option<Value> {
+    if shape
+        .get("traits")
+        .and_then(|traits| traits.get(BOX))
+        .is_some()
+    {
+        return None;
+    }
+    match shape["type"].as_str()? {
+        "boolean" => Some(json!(false)),
+        "byte" | "short" | "integer" | "long" | "float" | "double" => Some(json!(0)),
+        _ => None,
+    }
+}
+
+fn upgrade_shape(shape: &mut Map<String, Value>, zero_values: &HashMap<String, Value>) {
+    let zero = zero_value(shape);
+    if let Some(traits) = traits_mut(shape) {
+        traits.remove(BOX);
+        if let Some(zero) = zero {
+            traits.entry(DEFAULT).or_insert(zero);
+        }
+    }
+
+    match shape["type"].as_str() {
+        Some("set") => {
+            shape.insert("type".to_string(), json!("list"));
+            if let Some(traits) = traits_mut(shape) {
+                traits.insert("smithy.api#uniqueItems".to_string(), json!({}));
+            }
+        }
+        Some("operation") => {
+            for key in ["input", "output"] {
+                shape
+                    .entry(key)
+                    .or_insert_with(|| json!({ "target": "smithy.api#Unit" }));
+            }
+        }
+        Some("string") => upgrade_string_enum(shape),
+        _ => {}
+    }
+
+    let is_structure = shape["type"] == "structure";
+    for member in members(shape) {
+        let target = member.get("target").and_then(Value::as_str);
+        let zero = target.and_then(|target| target_zero_value(target, zero_values));
+        let Some(traits) = traits_mut(member) else {
+            continue;
+        };
+        let boxed = traits.remove(BOX).is_some();
+        if let Some(zero) = zero.filter(|_| is_structure) {
+            traits
+                .entry(DEFAULT)
+                .or_insert(if boxed { Value::Null } else { zero });
+        }
+    }
+    remove_empty_traits(shape);
+}
+
+fn target_zero_value(target: &str, zero_values: &HashMap<String, Value>) -> Option<Value> {
+    if let Some(zero) = zero_values.get(target) {
+        return Some(zero.clone());
+    }
+    let id = target.parse::<ShapeId>().ok()?;
+    prelude::get(&id)?.trait_value(&DEFAULT.parse().ok()?)
+}
+
+/// Converts a string shape with the `enum` trait to an enum shape, if it can be one.
+fn upgrade_string_enum(shape: &mut Map<String, Value>) {
+    let Some(Value::Array(items)) = shape.get("traits").and_then(|traits| traits.get(ENUM)) else {
+        return;
+    };
+    let named = items
+        .iter()
+        .all(|item| item["name"].as_str().is_some_and(is_identifier) && item["value"].is_string());
+    let applies_to_enums = shape["traits"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(id, _)| *id != ENUM)
+        .all(|(id, _)| {
+            id.parse::<ShapeId>()
+                .ok()
+                .and_then(|id| trait_definition(&id))
+                .is_some_and(|definition| definition.applies_to("enum"))
+        });
+    if !named || !applies_to_enums {
+        return;
+    }
+
+    let Some(Value::Array(items)) = traits_mut(shape).and_then(|traits| traits.remove(ENUM)) else {
+        unreachable!("the enum trait was checked above");
+    };
+    let mut members = Map::new();
+    for item in items {
+        let mut traits = Map::new();
+        traits.insert("smithy.api#enumValue".to_string(), item["value"].clone());
+        if let Some(documentation) = item.get("documentation") {
+            traits.insert(
+                "smithy.api#documentation".to_string(),
+                documentation.clone(),
+            );
+        }
+        if let Some(tags) = item.get("tags") {
+            traits.insert("smithy.api#tags".to_string(), tags.clone());
+        }
+        if item["deprecated"] == true {
+            traits.insert("smithy.api#deprecated".to_string(), json!({}));
+        }
+        let name = item["name"].as_str().unwrap_or_default().to_string();
+        let member = json!({ "target": "smithy.api#Unit", "traits": traits });
+        members.insert(name, member);
+    }
+    shape.insert("type".to_string(), json!("enum"));
+    shape.insert("members".to_string(), Value::Object(members));
+}
+
+/// The traits of a shape or member, which are added if it has none. Invalid traits are left for
+/// deserializing the upgraded model to report.
+fn traits_mut(shape: &mut Map<String, Value>) -> Option<&mut Map<String, Value>> {
+    shape
+        .entry("traits")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+}
+
+/// The members of a structure, union, list, set or map shape.
+fn members(shape: &mut Map<String, Value>) -> impl Iterator<Item = &mut Map<String, Value>> {
+    let mut members = vec![];
+    for (key, value) in shape.iter_mut() {
+        match (key.as_str(), value) {
+            ("members", Value::Object(named)) => {
+                members.extend(named.values_mut().filter_map(Value::as_object_mut));
+            }
+            ("member" | "key" | "value", Value::Object(member)) => members.push(member),
+            _ => {}
+        }
+    }
+    members.into_iter()
+}
+
+/// Removes the `traits` added to the shape or its members, but left empty.
+fn remove_empty_traits(shape: &mut Map<String, Value>) {
+    let is_empty = |traits: &Value| traits.as_object().is_some_and(Map::is_empty);
+    if shape.get("traits").is_some_and(is_empty) {
+        shape.remove("traits");
+    }
+    for member in members(shape) {
+        if member.get("traits").is_some_and(is_empty) {
+            member.remove("traits");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_model, write_model};
+
+    #[test]
+    fn upgrades() {
+        let source = json!({
+            "smithy": "1.0",
+            "shapes": {
+                "example#Count": { "type": "integer" },
+                "example#MaybeCount": {
+                    "type": "integer",
+                    "traits": { "smithy.api#box": {} },
+                },
+                "example#Names": { "type": "set", "member": { "target": "smithy.api#String" } },
+                "example#Color": {
+                    "type": "string",
+                    "traits": {
+                        "smithy.api#documentation": "A color.",
+                        "smithy.api#enum": [
+                            { "value": "red", "name": "RED", "documentation": "Red." },
+                            { "value": "old", "name": "OLD", "deprecated": true },
+                        ],
+                    },
+                },
+                "example#Size": {
+                    "type": "string",
+                    "traits": { "smithy.api#enum": [{ "value": "big" }] },
+                },
+                "example#Thing": {
+                    "type": "structure",
+                    "members": {
+                        "count": { "target": "example#Count" },
+                        "boxed": {
+                            "target": "example#Count",
+                            "traits": { "smithy.api#box": {} },
+                        },
+                        "maybe": { "target": "example#MaybeCount" },
+                        "flag": { "target": "smithy.api#PrimitiveBoolean" },
+                        "other": { "target": "smithy.api#Boolean" },
+                    },
+                },
+                "example#Ping": { "type": "operation" },
+            },
+        });
+        let model = parse_model("example.json", &source.to_string()).unwrap();
+        assert!(crate::validate(&model).is_empty());
+        let upgraded: Value = serde_json::from_str(&write_model(&model).unwrap()).unwrap();
+        assert_eq!(
+            upgraded,
+            json!({
+                "smithy": "2.0",
+                "shapes": {
+                    "example#Count": {
+                        "type": "integer",
+                        "traits": { "smithy.api#default": 0 },
+                    },
+                    "example#MaybeCount": { "type": "integer" },
+                    "example#Names": {
+                        "type": "list",
+                        "member": { "target": "smithy.api#String" },
+                        "traits": { "smithy.api#uniqueItems": {} },
+                    },
+                    "example#Color": {
+                        "type": "enum",
+                        "members": {
+                            "RED": {
+                                "target": "smithy.api#Unit",
+                                "traits": {
+                                    "smithy.api#documentation": "Red.",
+                                    "smithy.api#enumValue": "red",
+                                },
+                            },
+                            "OLD": {
+                                "target": "smithy.api#Unit",
+                                "traits": {
+                                    "smithy.api#deprecated": {},
+                                    "smithy.api#enumValue": "old",
+                                },
+                            },
+                        },
+                        "traits": { "smithy.api#documentation": "A color." },
+                    },
+                    "example#Size": {
+                        "type": "string",
+                        "traits": { "smithy.api#enum": [{ "value": "big" }] },
+                    },
+                    "example#Thing": {
+                        "type": "structure",
+                        "members": {
+                            "count": {
+                                "target": "example#Count",
+                                "traits": { "smithy.api#default": 0 },
+                            },
+                            "boxed": {
+                                "target": "example#Count",
+                                "traits": { "smithy.api#default": null },
+                            },
+                            "maybe": { "target": "example#MaybeCount" },
+                            "flag": {
+                                "target": "smithy.api#PrimitiveBoolean",
+                                "traits": { "smithy.api#default": false },
+                            },
+                            "other": { "target": "smithy.api#Boolean" },
+                        },
+                    },
+                    "example#Ping": {
+                        "type": "operation",
+                        "input": { "target": "smithy.api#Unit" },
+                        "output": { "target": "smithy.api#Unit" },
+                    },
+                },
+            })
+        );
+
+        let mut ast = upgraded.clone();
+        upgrade_json_ast(&mut ast);
+        assert_eq!(ast, upgraded);
+    }
+}
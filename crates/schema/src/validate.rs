@@ -0,0 +1,468 @@
+//! Semantic validation of a parsed [`Model`].
+//!
+//! Deserializing a model only checks its structure, this checks that shape references resolve to
+//! shapes of the right type and that traits are applied to shapes they make sense on.
+
+use std::collections::HashMap;
+
+use crate::arn::ArnTemplate;
+use crate::{
+    trait_definition, ArnTrait, Model, OperationShape, ResourceShape, ServiceShape, Shape, ShapeId,
+    ShapeRef, TraitSet,
+};
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum ValidationError {
+    #[error("{location}: {role} target {target} does not exist")]
+    #[diagnostic(code(laws_schema::validate::unresolved_target))]
+    UnresolvedTarget {
+        location: String,
+        role: &'static str,
+        target: ShapeId,
+    },
+    #[error("{location}: {role} target {target} should be {expected}, not {actual}")]
+    #[diagnostic(code(laws_schema::validate::wrong_shape_type))]
+    WrongShapeType {
+        location: String,
+        role: &'static str,
+        target: ShapeId,
+        expected: &'static str,
+        actual: &'static str,
+    },
+    #[error("{location}: error target {target} has no smithy.api#error trait")]
+    #[diagnostic(code(laws_schema::validate::missing_error_trait))]
+    MissingErrorTrait { location: String, target: ShapeId },
+    #[error("{location}: member {member:?} conflicts with member {existing:?}")]
+    #[diagnostic(
+        code(laws_schema::validate::duplicate_member),
+        help("member names must be case-insensitively unique")
+    )]
+    DuplicateMember {
+        location: String,
+        member: String,
+        existing: String,
+    },
+    #[error("{location}: trait {trait_id} cannot be applied to {}", applied_to(*.member, .shape_type))]
+    #[diagnostic(code(laws_schema::validate::trait_target))]
+    TraitTarget {
+        location: String,
+        trait_id: ShapeId,
+        /// Whether the trait is applied to a member, which targets a shape of the type.
+        member: bool,
+        shape_type: &'static str,
+    },
+    #[error("{location}: {message}")]
+    #[diagnostic(code(laws_schema::validate::arn_template))]
+    ArnTemplate { location: String, message: String },
+}
+
+fn applied_to(member: bool, shape_type: &str) -> String {
+    if member {
+        format!("members targeting {shape_type} shapes")
+    } else {
+        format!("{shape_type} shapes")
+    }
+}
+
+/// Checks that the model is semantically valid, returning all the problems found.
+pub fn validate(model: &Model) -> Vec<ValidationError> {
+    let mut validator = Validator {
+        model,
+        errors: vec![],
+    };
+    for (id, shape) in &model.shapes {
+        validator.shape(id, shape);
+    }
+    validator.errors
+}
+
+struct Validator<'model> {
+    model: &'model Model,
+    errors: Vec<ValidationError>,
+}
+
+impl<'model> Validator<'model> {
+    fn shape(&mut self, id: &ShapeId, shape: &'model Shape) {
+        let location = id.to_string();
+        self.shape_traits(&location, shape.traits(), shape.type_name());
+        match shape {
+            Shape::Service(service) => self.service(&location, service),
+            Shape::Operation(operation) => self.operation(&location, operation),
+            Shape::Resource(resource) => self.resource(&location, resource),
+            Shape::Structure(structure) => {
+                self.unique_members(&location, structure.members.keys());
+                for reference in &structure.traits.references {
+                    self.target(&location, "reference", &reference.resource, &["resource"]);
+                }
+                for (name, member) in &structure.members {
+                    let location = format!("{location}${name}");
+                    self.member(&location, "member", &member.target, &[], &member.traits);
+                }
+            }
+            Shape::Union(union) => {
+                self.unique_members(&location, union.members.keys());
+                for (name, member) in &union.members {
+                    let location = format!("{location}${name}");
+                    self.member(&location, "member", &member.target, &[], &member.traits);
+                }
+            }
+            Shape::Enum(enum_) => {
+                self.unique_members(&location, enum_.members.keys());
+                for (name, member) in &enum_.members {
+                    let location = format!("{location}${name}");
+                    let (target, traits) = (&member.target, &member.traits);
+                    self.member(&location, "member", target, &["structure"], traits);
+                }
+            }
+            Shape::List(list) => {
+                let (target, traits) = (&list.member.target, &list.member.traits);
+                self.member(&format!("{location}$member"), "member", target, &[], traits);
+            }
+            Shape::Map(map) => {
+                let (key, key_traits) = (&map.key.target, &map.key.traits);
+                let location_key = format!("{location}$key");
+                self.member(&location_key, "key", key, &["string", "enum"], key_traits);
+                let (value, value_traits) = (&map.value.target, &map.value.traits);
+                self.member(
+                    &format!("{location}$value"),
+                    "value",
+                    value,
+                    &[],
+                    value_traits,
+                );
+            }
+            Shape::Boolean(_)
+            | Shape::Byte(_)
+            | Shape::Short(_)
+            | Shape::Integer(_)
+            | Shape::Long(_)
+            | Shape::Float(_)
+            | Shape::Double(_)
+            | Shape::BigInteger(_)
+            | Shape::BigDecimal(_)
+            | Shape::String(_)
+            | Shape::Blob(_)
+            | Shape::Timestamp(_)
+            | Shape::Document(_) => {}
+        }
+    }
+
+    fn service(&mut self, location: &str, service: &ServiceShape) {
+        self.targets(location, "operation", &service.operations, &["operation"]);
+        self.targets(location, "resource", &service.resources, &["resource"]);
+        self.errors(location, &service.errors);
+    }
+
+    fn operation(&mut self, location: &str, operation: &OperationShape) {
+        let input = &operation.input.target;
+        self.target(location, "input", input, &["structure"]);
+        let output = &operation.output.target;
+        self.target(location, "output", output, &["structure"]);
+        self.errors(location, &operation.errors);
+    }
+
+    fn resource(&mut self, location: &str, resource: &ResourceShape) {
+        for (name, identifier) in resource.identifiers.iter().flatten() {
+            let location = format!("{location} identifier {name:?}");
+            self.target(
+                &location,
+                "identifier",
+                &identifier.target,
+                &["string", "enum"],
+            );
+        }
+        for (name, property) in &resource.properties {
+            let location = format!("{location} property {name:?}");
+            self.target(&location, "property", &property.target, &[]);
+        }
+        let lifecycle = [
+            ("put", &resource.put),
+            ("create", &resource.create),
+            ("read", &resource.read),
+            ("update", &resource.update),
+            ("delete", &resource.delete),
+            ("list", &resource.list),
+        ];
+        for (role, shape_ref) in lifecycle {
+            if let Some(shape_ref) = shape_ref {
+                self.target(location, role, &shape_ref.target, &["operation"]);
+            }
+        }
+        self.targets(location, "operation", &resource.operations, &["operation"]);
+        let collection_operations = &resource.collection_operations;
+        self.targets(location, "operation", collection_operations, &["operation"]);
+        self.targets(location, "resource", &resource.resources, &["resource"]);
+        if let Some(arn) = &resource.traits.arn {
+            self.arn_template(location, resource, arn);
+        }
+    }
+
+    fn arn_template(&mut self, location: &str, resource: &ResourceShape, arn: &ArnTrait) {
+        let template = match ArnTemplate::new(arn) {
+            Ok(template) => template,
+            Err(error) => {
+                self.errors.push(ValidationError::ArnTemplate {
+                    location: location.to_string(),
+                    message: error.to_string(),
+                });
+                return;
+            }
+        };
+        for label in template.labels() {
+            let is_identifier = resource
+                .identifiers
+                .as_ref()
+                .is_some_and(|identifiers| identifiers.contains_key(label));
+            if !is_identifier {
+                self.errors.push(ValidationError::ArnTemplate {
+                    location: location.to_string(),
+                    message: format!("ARN template label {label:?} is not an identifier"),
+                });
+            }
+        }
+    }
+
+    fn errors(&mut self, location: &str, errors: &[ShapeRef]) {
+        for error in errors {
+            let target = &error.target;
+            if !self.target(location, "error", target, &["structure"]) {
+                continue;
+            }
+            if let Some(Shape::Structure(structure)) = self.model.shapes.get(target) {
+                if structure.traits.error.is_none() {
+                    self.errors.push(ValidationError::MissingErrorTrait {
+                        location: location.to_string(),
+                        target: target.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Checks the target of a member of any kind, then the traits applied to it.
+    fn member(
+        &mut self,
+        location: &str,
+        role: &'static str,
+        target: &ShapeId,
+        types: &[&str],
+        traits: &dyn TraitSet,
+    ) {
+        if self.target(location, role, target, types) {
+            if let Some(type_name) = self.type_name(target) {
+                self.member_traits(location, traits, type_name);
+            }
+        }
+    }
+
+    fn shape_traits(&mut self, location: &str, traits: &dyn TraitSet, shape_type: &'static str) {
+        for trait_id in traits.to_values().into_keys() {
+            let Some(definition) = trait_definition(&trait_id) else {
+                continue;
+            };
+            if !definition.applies_to(shape_type) {
+                self.errors.push(ValidationError::TraitTarget {
+                    location: location.to_string(),
+                    trait_id,
+                    member: false,
+                    shape_type,
+                });
+            }
+        }
+    }
+
+    /// Checks the traits of a member apply to members. Traits that also apply to some types of
+    /// shape, such as `length`, only apply to members targeting those types.
+    fn member_traits(&mut self, location: &str, traits: &dyn TraitSet, target_type: &'static str) {
+        for trait_id in traits.to_values().into_keys() {
+            let Some(definition) = trait_definition(&trait_id) else {
+                continue;
+            };
+            let member_only = definition.shape_types == ["member"];
+            let applies = definition.applies_to("member")
+                && (member_only || definition.applies_to(target_type));
+            if !applies {
+                self.errors.push(ValidationError::TraitTarget {
+                    location: location.to_string(),
+                    trait_id,
+                    member: true,
+                    shape_type: target_type,
+                });
+            }
+        }
+    }
+
+    fn unique_members<'a>(&mut self, location: &str, names: impl Iterator<Item = &'a String>) {
+        let mut seen = HashMap::<String, &String>::new();
+        for name in names {
+            if let Some(existing) = seen.insert(name.to_lowercase(), name) {
+                self.errors.push(ValidationError::DuplicateMember {
+                    location: location.to_string(),
+                    member: name.clone(),
+                    existing: existing.clone(),
+                });
+            }
+        }
+    }
+
+    fn targets(&mut self, location: &str, role: &'static str, refs: &[ShapeRef], types: &[&str]) {
+        for shape_ref in refs {
+            self.target(location, role, &shape_ref.target, types);
+        }
+    }
+
+    /// Checks the target exists and is one of the types, or any non-entity type if empty.
+    /// Returns whether the target is valid.
+    fn target(
+        &mut self,
+        location: &str,
+        role: &'static str,
+        target: &ShapeId,
+        types: &[&str],
+    ) -> bool {
+        let Some(actual) = self.type_name(target) else {
+            self.errors.push(ValidationError::UnresolvedTarget {
+                location: location.to_string(),
+                role,
+                target: target.clone(),
+            });
+            return false;
+        };
+        let valid = if types.is_empty() {
+            !matches!(actual, "service" | "operation" | "resource")
+        } else {
+            types.contains(&actual)
+        };
+        if !valid {
+            self.errors.push(ValidationError::WrongShapeType {
+                location: location.to_string(),
+                role,
+                target: target.clone(),
+                expected: match types {
+                    [] => "a data shape",
+                    ["structure"] => "a structure",
+                    ["operation"] => "an operation",
+                    ["resource"] => "a resource",
+                    _ => "a string or enum",
+                },
+                actual,
+            });
+        }
+        valid
+    }
+
+    fn type_name(&self, id: &ShapeId) -> Option<&'static str> {
+        self.model.resolve(id).map(Shape::type_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn validate_json(value: serde_json::Value) -> Vec<String> {
+        let model = serde_json::from_value::<Model>(value).unwrap();
+        validate(&model).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn valid_model() {
+        let errors = validate_json(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#GetThing": {
+                    "type": "operation",
+                    "input": { "target": "example#GetThingInput" },
+                    "output": { "target": "smithy.api#Unit" },
+                    "errors": [{ "target": "example#NotFound" }],
+                },
+                "example#GetThingInput": {
+                    "type": "structure",
+                    "members": {
+                        "Name": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#length": { "min": 1 } },
+                        },
+                    },
+                },
+                "example#NotFound": {
+                    "type": "structure",
+                    "traits": { "smithy.api#error": "client" },
+                },
+            },
+        }));
+        assert_eq!(errors, Vec::<String>::new());
+    }
+
+    #[test]
+    fn invalid_model() {
+        let errors = validate_json(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#GetThing": {
+                    "type": "operation",
+                    "input": { "target": "example#Missing" },
+                    "output": { "target": "smithy.api#String" },
+                    "errors": [{ "target": "example#Thing" }],
+                },
+                "example#Thing": {
+                    "type": "structure",
+                    "members": {
+                        "name": { "target": "smithy.api#String" },
+                        "Name": {
+                            "target": "smithy.api#Integer",
+                            "traits": { "smithy.api#pattern": "^a+$" },
+                        },
+                        "Op": { "target": "example#GetThing" },
+                    },
+                    "traits": { "smithy.api#pattern": "^a+$" },
+                },
+                "example#Things": {
+                    "type": "map",
+                    "key": { "target": "smithy.api#Integer" },
+                    "value": {
+                        "target": "smithy.api#Blob",
+                        "traits": { "smithy.api#pattern": "^a+$" },
+                    },
+                },
+                "example#Choice": {
+                    "type": "union",
+                    "members": {
+                        "name": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#range": { "max": 1 } },
+                        },
+                    },
+                },
+                "example#Queue": {
+                    "type": "resource",
+                    "identifiers": { "QueueName": { "target": "smithy.api#String" } },
+                    "traits": { "aws.api#arn": { "template": "queue/{QueueName}/{Owner}" } },
+                },
+                "example#Flag": {
+                    "type": "boolean",
+                    "traits": { "smithy.api#length": { "max": 1 } },
+                },
+            },
+        }));
+        assert_eq!(
+            errors,
+            [
+                "example#GetThing: input target example#Missing does not exist",
+                "example#GetThing: output target smithy.api#String should be a structure, not string",
+                "example#GetThing: error target example#Thing has no smithy.api#error trait",
+                "example#Thing: trait smithy.api#pattern cannot be applied to structure shapes",
+                "example#Thing: member \"Name\" conflicts with member \"name\"",
+                "example#Thing$Name: trait smithy.api#pattern cannot be applied to members targeting integer shapes",
+                "example#Thing$Op: member target example#GetThing should be a data shape, not operation",
+                "example#Things$key: key target smithy.api#Integer should be a string or enum, not integer",
+                "example#Things$value: trait smithy.api#pattern cannot be applied to members targeting blob shapes",
+                "example#Choice$name: trait smithy.api#range cannot be applied to members targeting string shapes",
+                "example#Queue: ARN template label \"Owner\" is not an identifier",
+                "example#Flag: trait smithy.api#length cannot be applied to boolean shapes",
+            ],
+        );
+    }
+}
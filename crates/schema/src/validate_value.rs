@@ -0,0 +1,871 @@
+//! Validation of JSON values, such as request payloads, against the shapes of a [`Model`].
+//!
+//! Values are in the form the JSON protocols use: structures, unions and maps are objects keyed
+//! by member name or map key, blobs are base64 strings, and timestamps are epoch seconds or
+//! strings in their `timestampFormat`.
+
+use std::collections::HashMap;
+
+use fancy_regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::{LengthTrait, Model, RangeTrait, Shape, ShapeId, StringEnumItem, TimestampFormatTrait};
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum ValueError {
+    #[error("shape {shape_id} is not defined{}", at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::undefined_shape))]
+    UndefinedShape { path: String, shape_id: ShapeId },
+    #[error("{shape_id} is a {type_name} shape, which has no values{}", at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::not_data))]
+    NotData {
+        path: String,
+        shape_id: ShapeId,
+        type_name: &'static str,
+    },
+    #[error("expected {expected}, found {found}{}", at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::wrong_type))]
+    WrongType {
+        path: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[error("missing required member {member:?}{}", at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::missing))]
+    Missing { path: String, member: String },
+    #[error("unknown member {member:?}{}", at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::unknown_member))]
+    UnknownMember { path: String, member: String },
+    #[error("length {length} is not within {}{}", bounds(.min, .max), at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::length))]
+    Length {
+        path: String,
+        length: usize,
+        min: Option<String>,
+        max: Option<String>,
+    },
+    #[error("{value} is not within {}{}", bounds(.min, .max), at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::range))]
+    Range {
+        path: String,
+        value: String,
+        min: Option<String>,
+        max: Option<String>,
+    },
+    #[error("{value:?} does not match the pattern {pattern:?}{}", at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::pattern))]
+    Pattern {
+        path: String,
+        value: String,
+        pattern: String,
+    },
+    #[error("the pattern {pattern:?} can't be checked: {message}{}", at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::invalid_pattern))]
+    InvalidPattern {
+        path: String,
+        pattern: String,
+        message: String,
+    },
+    #[error("{value:?} is not one of the enum values {allowed:?}{}", at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::enum_value))]
+    EnumValue {
+        path: String,
+        value: String,
+        allowed: Vec<String>,
+    },
+    #[error("item {index} duplicates item {first}{}", at(.path))]
+    #[diagnostic(
+        code(laws_schema::validate_value::duplicate),
+        help("the list has the `uniqueItems` trait")
+    )]
+    Duplicate {
+        path: String,
+        index: usize,
+        first: usize,
+    },
+    #[error("null values are only allowed in sparse lists and maps{}", at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::null))]
+    Null { path: String },
+    #[error("a union value must set exactly one member, not {count}{}", at(.path))]
+    #[diagnostic(code(laws_schema::validate_value::union))]
+    Union { path: String, count: usize },
+}
+
+fn at(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" at {path}")
+    }
+}
+
+fn bounds(min: &Option<String>, max: &Option<String>) -> String {
+    match (min, max) {
+        (Some(min), Some(max)) => format!("[{min}, {max}]"),
+        (Some(min), None) => format!("[{min}, ∞)"),
+        (None, Some(max)) => format!("(-∞, {max}]"),
+        (None, None) => "(-∞, ∞)".to_string(),
+    }
+}
+
+/// Checks a JSON value against a shape, returning all the violations found with JSON pointers
+/// to the offending values.
+pub fn validate_value(model: &Model, shape_id: &ShapeId, value: &Value) -> Vec<ValueError> {
+    let mut validator = Validator {
+        model,
+        patterns: HashMap::new(),
+        path: String::new(),
+        errors: vec![],
+    };
+    validator.value(shape_id, &Constraints::default(), value);
+    validator.errors
+}
+
+/// The constraint traits of a member, which take precedence over those of its target.
+#[derive(Default, Clone, Copy)]
+struct Constraints<'model> {
+    length: Option<&'model LengthTrait>,
+    range: Option<&'model RangeTrait>,
+    pattern: Option<&'model str>,
+    timestamp_format: Option<&'model TimestampFormatTrait>,
+}
+
+/// The constraints of a member, whose traits are one of the member `*Traits` structs.
+macro_rules! member_constraints {
+    ($traits:expr) => {{
+        let traits = $traits;
+        Constraints {
+            length: traits.length.as_ref(),
+            range: traits.range.as_ref(),
+            pattern: traits.pattern.as_deref(),
+            timestamp_format: traits.timestamp_format.as_ref(),
+        }
+    }};
+}
+
+impl<'model> Constraints<'model> {
+    fn length(length: Option<&'model LengthTrait>) -> Self {
+        Constraints {
+            length,
+            ..Constraints::default()
+        }
+    }
+
+    fn range(range: Option<&'model RangeTrait>) -> Self {
+        Constraints {
+            range,
+            ..Constraints::default()
+        }
+    }
+
+    fn or(self, shape: Constraints<'model>) -> Self {
+        Constraints {
+            length: self.length.or(shape.length),
+            range: self.range.or(shape.range),
+            pattern: self.pattern.or(shape.pattern),
+            timestamp_format: self.timestamp_format.or(shape.timestamp_format),
+        }
+    }
+}
+
+struct Validator<'model> {
+    model: &'model Model,
+    /// Compiled patterns, or why they can't be compiled.
+    patterns: HashMap<&'model str, Result<Regex, String>>,
+    /// The JSON pointer to the value being validated.
+    path: String,
+    errors: Vec<ValueError>,
+}
+
+impl<'model> Validator<'model> {
+    fn value(&mut self, shape_id: &ShapeId, member: &Constraints<'model>, value: &Value) {
+        let Some(shape) = self.model.resolve(shape_id) else {
+            self.errors.push(ValueError::UndefinedShape {
+                path: self.path.clone(),
+                shape_id: shape_id.clone(),
+            });
+            return;
+        };
+        match shape {
+            Shape::Service(_) | Shape::Operation(_) | Shape::Resource(_) => {
+                self.errors.push(ValueError::NotData {
+                    path: self.path.clone(),
+                    shape_id: shape_id.clone(),
+                    type_name: shape.type_name(),
+                });
+            }
+            Shape::Structure(structure) => {
+                let Some(object) = self.expect_object(value) else {
+                    return;
+                };
+                for (name, member) in &structure.members {
+                    let traits = &member.traits;
+                    let is_set = object.get(name).is_some_and(|value| !value.is_null());
+                    let has_default = traits
+                        .default
+                        .as_ref()
+                        .is_some_and(|value| !value.is_null());
+                    if !is_set && traits.required.is_some() && !has_default {
+                        self.errors.push(ValueError::Missing {
+                            path: self.path.clone(),
+                            member: name.clone(),
+                        });
+                    }
+                }
+                for (name, value) in object {
+                    let Some(member) = structure.members.get(name) else {
+                        self.unknown_member(name);
+                        continue;
+                    };
+                    if value.is_null() {
+                        continue;
+                    }
+                    let constraints = member_constraints!(&member.traits);
+                    self.at(name, |v| v.value(&member.target, &constraints, value));
+                }
+            }
+            Shape::Union(union) => {
+                let Some(object) = self.expect_object(value) else {
+                    return;
+                };
+                let count = object.values().filter(|value| !value.is_null()).count();
+                if count != 1 {
+                    self.errors.push(ValueError::Union {
+                        path: self.path.clone(),
+                        count,
+                    });
+                }
+                for (name, value) in object {
+                    let Some(member) = union.members.get(name) else {
+                        self.unknown_member(name);
+                        continue;
+                    };
+                    if value.is_null() {
+                        continue;
+                    }
+                    let constraints = member_constraints!(&member.traits);
+                    self.at(name, |v| v.value(&member.target, &constraints, value));
+                }
+            }
+            Shape::List(list) => {
+                let Value::Array(items) = value else {
+                    self.wrong_type("an array", value);
+                    return;
+                };
+                let traits = &list.traits;
+                self.length(
+                    member.or(Constraints::length(traits.length.as_ref())),
+                    items.len(),
+                );
+                let constraints = member_constraints!(&list.member.traits);
+                for (index, item) in items.iter().enumerate() {
+                    if let Some(first) = traits
+                        .unique_items
+                        .as_ref()
+                        .and_then(|_| items[..index].iter().position(|other| other == item))
+                    {
+                        self.errors.push(ValueError::Duplicate {
+                            path: self.path.clone(),
+                            index,
+                            first,
+                        });
+                    }
+                    self.at(&index.to_string(), |v| {
+                        if item.is_null() {
+                            if traits.sparse.is_none() {
+                                v.null();
+                            }
+                            return;
+                        }
+                        v.value(&list.member.target, &constraints, item);
+                    });
+                }
+            }
+            Shape::Map(map) => {
+                let Some(object) = self.expect_object(value) else {
+                    return;
+                };
+                let traits = &map.traits;
+                self.length(
+                    member.or(Constraints::length(traits.length.as_ref())),
+                    object.len(),
+                );
+                let key_constraints = member_constraints!(&map.key.traits);
+                let value_constraints = member_constraints!(&map.value.traits);
+                for (key, value) in object {
+                    self.at(key, |v| {
+                        let key = Value::String(key.clone());
+                        v.value(&map.key.target, &key_constraints, &key);
+                        if value.is_null() {
+                            if traits.sparse.is_none() {
+                                v.null();
+                            }
+                            return;
+                        }
+                        v.value(&map.value.target, &value_constraints, value);
+                    });
+                }
+            }
+            Shape::Enum(enum_) => {
+                let Some(string) = self.expect_string(value) else {
+                    return;
+                };
+                let traits = &enum_.traits;
+                let constraints = Constraints {
+                    length: traits.length.as_ref(),
+                    pattern: traits.pattern.as_deref(),
+                    ..Constraints::default()
+                };
+                self.string(member.or(constraints), string);
+                let allowed = enum_
+                    .members
+                    .values()
+                    .map(|member| &member.traits.enum_value);
+                self.enum_value(string, allowed);
+            }
+            Shape::String(shape) => {
+                let Some(string) = self.expect_string(value) else {
+                    return;
+                };
+                let traits = &shape.traits;
+                let constraints = Constraints {
+                    length: traits.length.as_ref(),
+                    pattern: traits.pattern.as_deref(),
+                    ..Constraints::default()
+                };
+                self.string(member.or(constraints), string);
+                if let Some(items) = &traits.enum_ {
+                    self.enum_value(
+                        string,
+                        items.iter().map(|item: &StringEnumItem| &item.value),
+                    );
+                }
+            }
+            Shape::Blob(blob) => {
+                let Some(string) = self.expect_string(value) else {
+                    return;
+                };
+                let Some(length) = base64_length(string) else {
+                    self.wrong_type("a base64 encoded string", value);
+                    return;
+                };
+                let constraints = Constraints::length(blob.traits.length.as_ref());
+                self.length(member.or(constraints), length);
+            }
+            Shape::Timestamp(timestamp) => {
+                let format = member
+                    .timestamp_format
+                    .or(timestamp.traits.timestamp_format.as_ref());
+                let valid = match (format, value) {
+                    (None | Some(TimestampFormatTrait::EpochSeconds), Value::Number(_)) => true,
+                    (None | Some(TimestampFormatTrait::DateTime), Value::String(string)) => {
+                        is_date_time(string)
+                    }
+                    (Some(TimestampFormatTrait::HttpDate), Value::String(string)) => {
+                        is_http_date(string)
+                    }
+                    _ => false,
+                };
+                if !valid {
+                    let expected = match format {
+                        None => "epoch seconds or a date-time string",
+                        Some(TimestampFormatTrait::EpochSeconds) => "epoch seconds",
+                        Some(TimestampFormatTrait::DateTime) => "a date-time string",
+                        Some(TimestampFormatTrait::HttpDate) => "an http-date string",
+                    };
+                    self.wrong_type(expected, value);
+                }
+            }
+            Shape::Document(_) => {}
+            Shape::Boolean(_) => {
+                if !value.is_boolean() {
+                    self.wrong_type("a boolean", value);
+                }
+            }
+            Shape::Byte(shape) => self.integer(
+                member,
+                shape.traits.range.as_ref(),
+                value,
+                i8::MIN.into(),
+                i8::MAX.into(),
+                "a byte",
+            ),
+            Shape::Short(shape) => self.integer(
+                member,
+                shape.traits.range.as_ref(),
+                value,
+                i16::MIN.into(),
+                i16::MAX.into(),
+                "a short",
+            ),
+            Shape::Integer(shape) => self.integer(
+                member,
+                shape.traits.range.as_ref(),
+                value,
+                i32::MIN.into(),
+                i32::MAX.into(),
+                "an integer",
+            ),
+            Shape::Long(shape) => self.integer(
+                member,
+                shape.traits.range.as_ref(),
+                value,
+                i64::MIN,
+                i64::MAX,
+                "a long",
+            ),
+            Shape::BigInteger(shape) => {
+                let is_integer = value.is_i64()
+                    || value.is_u64()
+                    || value.as_f64().is_some_and(|number| number.fract() == 0.0);
+                if !is_integer {
+                    self.wrong_type("an integer", value);
+                    return;
+                }
+                self.range(
+                    member.or(Constraints::range(shape.traits.range.as_ref())),
+                    value,
+                );
+            }
+            Shape::Float(shape) => self.float(
+                member.or(Constraints::range(shape.traits.range.as_ref())),
+                value,
+            ),
+            Shape::Double(shape) => self.float(
+                member.or(Constraints::range(shape.traits.range.as_ref())),
+                value,
+            ),
+            Shape::BigDecimal(shape) => {
+                if !value.is_number() {
+                    self.wrong_type("a number", value);
+                    return;
+                }
+                self.range(
+                    member.or(Constraints::range(shape.traits.range.as_ref())),
+                    value,
+                );
+            }
+        }
+    }
+
+    /// Validates a value with the path extended by a key or index.
+    fn at(&mut self, segment: &str, validate: impl FnOnce(&mut Self)) {
+        let len = self.path.len();
+        self.path.push('/');
+        self.path
+            .push_str(&segment.replace('~', "~0").replace('/', "~1"));
+        validate(self);
+        self.path.truncate(len);
+    }
+
+    fn expect_object<'v>(&mut self, value: &'v Value) -> Option<&'v Map<String, Value>> {
+        let object = value.as_object();
+        if object.is_none() {
+            self.wrong_type("an object", value);
+        }
+        object
+    }
+
+    fn expect_string<'v>(&mut self, value: &'v Value) -> Option<&'v str> {
+        let string = value.as_str();
+        if string.is_none() {
+            self.wrong_type("a string", value);
+        }
+        string
+    }
+
+    fn wrong_type(&mut self, expected: &'static str, value: &Value) {
+        let found = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Array(_) => "an array",
+            Value::Object(_) => "an object",
+        };
+        self.errors.push(ValueError::WrongType {
+            path: self.path.clone(),
+            expected,
+            found,
+        });
+    }
+
+    fn unknown_member(&mut self, name: &str) {
+        self.errors.push(ValueError::UnknownMember {
+            path: self.path.clone(),
+            member: name.to_string(),
+        });
+    }
+
+    fn null(&mut self) {
+        self.errors.push(ValueError::Null {
+            path: self.path.clone(),
+        });
+    }
+
+    fn string(&mut self, constraints: Constraints<'model>, string: &str) {
+        self.length(constraints, string.chars().count());
+        let Some(pattern) = constraints.pattern else {
+            return;
+        };
+        // Patterns are ECMA-262 regular expressions, which can use lookaround, e.g.
+        // `^(?!aws:)`, so they need a backtracking engine, which gives up on matches that take
+        // too long rather than saying whether they match.
+        let regex = self
+            .patterns
+            .entry(pattern)
+            .or_insert_with(|| Regex::new(pattern).map_err(|error| error.to_string()));
+        let error = match regex {
+            Ok(regex) => match regex.is_match(string) {
+                Ok(true) => return,
+                Ok(false) => ValueError::Pattern {
+                    path: self.path.clone(),
+                    value: string.to_string(),
+                    pattern: pattern.to_string(),
+                },
+                Err(error) => ValueError::InvalidPattern {
+                    path: self.path.clone(),
+                    pattern: pattern.to_string(),
+                    message: error.to_string(),
+                },
+            },
+            Err(message) => ValueError::InvalidPattern {
+                path: self.path.clone(),
+                pattern: pattern.to_string(),
+                message: message.clone(),
+            },
+        };
+        self.errors.push(error);
+    }
+
+    fn enum_value<'a>(&mut self, string: &str, allowed: impl Iterator<Item = &'a String>) {
+        let allowed = allowed.cloned().collect::<Vec<_>>();
+        if !allowed.iter().any(|value| value == string) {
+            self.errors.push(ValueError::EnumValue {
+                path: self.path.clone(),
+                value: string.to_string(),
+                allowed,
+            });
+        }
+    }
+
+    fn length(&mut self, constraints: Constraints, length: usize) {
+        let Some(LengthTrait { min, max }) = constraints.length else {
+            return;
+        };
+        let too_short = min.is_some_and(|min| length < min as usize);
+        let too_long = max.is_some_and(|max| length > max as usize);
+        if too_short || too_long {
+            self.errors.push(ValueError::Length {
+                path: self.path.clone(),
+                length,
+                min: min.map(|min| min.to_string()),
+                max: max.map(|max| max.to_string()),
+            });
+        }
+    }
+
+    fn integer(
+        &mut self,
+        member: &Constraints<'model>,
+        range: Option<&'model RangeTrait>,
+        value: &Value,
+        min: i64,
+        max: i64,
+        expected: &'static str,
+    ) {
+        if !value
+            .as_i64()
+            .is_some_and(|value| (min..=max).contains(&value))
+        {
+            self.wrong_type(expected, value);
+            return;
+        }
+        self.range(member.or(Constraints::range(range)), value);
+    }
+
+    fn float(&mut self, constraints: Constraints, value: &Value) {
+        // JSON has no literals for these, so the JSON protocols send them as strings.
+        if matches!(value.as_str(), Some("NaN" | "Infinity" | "-Infinity")) {
+            return;
+        }
+        if !value.is_number() {
+            self.wrong_type("a number", value);
+            return;
+        }
+        self.range(constraints, value);
+    }
+
+    fn range(&mut self, constraints: Constraints, value: &Value) {
+        let Some(RangeTrait { min, max }) = constraints.range else {
+            return;
+        };
+        let Some(number) = value.as_f64() else {
+            return;
+        };
+        let bound = |bound: &Option<Value>| match bound {
+            Some(Value::Number(bound)) => bound.as_f64(),
+            Some(Value::String(bound)) => bound.parse().ok(),
+            _ => None,
+        };
+        let too_small = bound(min).is_some_and(|min| number < min);
+        let too_large = bound(max).is_some_and(|max| number > max);
+        if too_small || too_large {
+            self.errors.push(ValueError::Range {
+                path: self.path.clone(),
+                value: value.to_string(),
+                min: min.as_ref().map(|min| min.to_string()),
+                max: max.as_ref().map(|max| max.to_string()),
+            });
+        }
+    }
+}
+
+/// The number of bytes a base64 string decodes to, if it is valid padded base64.
+fn base64_length(string: &str) -> Option<usize> {
+    let bytes = string.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+    let data = string.trim_end_matches('=');
+    let padding = bytes.len() - data.len();
+    let is_base64 = |c: char| c.is_ascii_alphanumeric() || c == '+' || c == '/';
+    if padding > 2 || !data.chars().all(is_base64) {
+        return None;
+    }
+    Some(bytes.len() / 4 * 3 - padding)
+}
+
+/// Whether the string is an RFC 3339 date-time, e.g. `1985-04-12T23:20:50.52Z`.
+fn is_date_time(string: &str) -> bool {
+    static DATE_TIME: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    DATE_TIME
+        .get_or_init(|| {
+            Regex::new(r"(?i)^\d{4}-\d{2}-\d{2}t\d{2}:\d{2}:\d{2}(\.\d+)?(z|[+-]\d{2}:\d{2})$")
+                .expect("the date-time pattern is valid")
+        })
+        .is_match(string)
+        .unwrap_or(false)
+}
+
+/// Whether the string is an RFC 7231 IMF-fixdate, e.g. `Tue, 29 Apr 2014 18:30:38 GMT`.
+fn is_http_date(string: &str) -> bool {
+    static HTTP_DATE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    HTTP_DATE
+        .get_or_init(|| {
+            Regex::new(
+                r"^(Mon|Tue|Wed|Thu|Fri|Sat|Sun), \d{2} (Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec) \d{4} \d{2}:\d{2}:\d{2} GMT$",
+            )
+            .expect("the http-date pattern is valid")
+        })
+        .is_match(string)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn model() -> Model {
+        serde_json::from_value(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#Thing": {
+                    "type": "structure",
+                    "members": {
+                        "id": {
+                            "target": "smithy.api#String",
+                            "traits": {
+                                "smithy.api#required": {},
+                                "smithy.api#pattern": "^[a-z]+$",
+                            },
+                        },
+                        "size": {
+                            "target": "example#Size",
+                            "traits": { "smithy.api#range": { "max": 10 } },
+                        },
+                        "color": { "target": "example#Color" },
+                        "tags": { "target": "example#Tags" },
+                        "labels": { "target": "example#Labels" },
+                        "shape": { "target": "example#Shape" },
+                        "data": { "target": "smithy.api#Blob" },
+                        "created": {
+                            "target": "smithy.api#Timestamp",
+                            "traits": { "smithy.api#timestampFormat": "http-date" },
+                        },
+                    },
+                },
+                "example#Size": {
+                    "type": "integer",
+                    "traits": { "smithy.api#range": { "min": 1, "max": 100 } },
+                },
+                "example#Color": {
+                    "type": "enum",
+                    "members": {
+                        "RED": {
+                            "target": "smithy.api#Unit",
+                            "traits": { "smithy.api#enumValue": "red" },
+                        },
+                    },
+                },
+                "example#Tags": {
+                    "type": "list",
+                    "member": {
+                        "target": "smithy.api#String",
+                        "traits": { "smithy.api#pattern": "^[a-z]+$" },
+                    },
+                    "traits": {
+                        "smithy.api#length": { "max": 3 },
+                        "smithy.api#uniqueItems": {},
+                    },
+                },
+                "example#Labels": {
+                    "type": "map",
+                    "key": {
+                        "target": "smithy.api#String",
+                        "traits": {
+                            "smithy.api#length": { "min": 2 },
+                            "smithy.api#pattern": "^(?!aws:)",
+                        },
+                    },
+                    "value": { "target": "smithy.api#String" },
+                    "traits": { "smithy.api#sparse": {} },
+                },
+                "example#Shape": {
+                    "type": "union",
+                    "members": {
+                        "circle": { "target": "smithy.api#Integer" },
+                        "square": { "target": "smithy.api#Integer" },
+                        "label": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#pattern": "^[a-z]+$" },
+                        },
+                        "color": { "target": "example#Color" },
+                        "tags": { "target": "example#Tags" },
+                    },
+                },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn validates() {
+        let thing = "example#Thing".parse().unwrap();
+        let value = json!({
+            "id": "abc",
+            "size": 10,
+            "color": "red",
+            "tags": ["a", "b"],
+            "labels": { "ab": "x", "cd": null },
+            "shape": { "circle": 1, "square": null },
+            "data": "aGk=",
+            "created": "Tue, 29 Apr 2014 18:30:38 GMT",
+        });
+        let errors = validate_value(&model(), &thing, &value);
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let value = json!({
+            "size": 11,
+            "color": "blue",
+            "tags": ["a", "b", "a", null],
+            "labels": { "/": "x", "cd": 1, "aws:x": "y" },
+            "shape": {},
+            "data": "aGk",
+            "created": 1398796238,
+            "extra": true,
+        });
+        let errors = validate_value(&model(), &thing, &value)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "missing required member \"id\"",
+                "11 is not within (-∞, 10] at /size",
+                "\"blue\" is not one of the enum values [\"red\"] at /color",
+                "length 4 is not within (-∞, 3] at /tags",
+                "item 2 duplicates item 0 at /tags",
+                "null values are only allowed in sparse lists and maps at /tags/3",
+                "length 1 is not within [2, ∞) at /labels/~1",
+                "expected a string, found a number at /labels/cd",
+                "\"aws:x\" does not match the pattern \"^(?!aws:)\" at /labels/aws:x",
+                "a union value must set exactly one member, not 0 at /shape",
+                "expected a base64 encoded string, found a string at /data",
+                "expected an http-date string, found a number at /created",
+                "unknown member \"extra\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_union_and_list_members() {
+        let thing = "example#Thing".parse().unwrap();
+        let errors = |shape: Value| {
+            let value = json!({ "id": "abc", "tags": ["a", "B"], "shape": shape });
+            validate_value(&model(), &thing, &value)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+        let pattern_error = "\"B\" does not match the pattern \"^[a-z]+$\" at /tags/1";
+        assert_eq!(
+            errors(json!({ "label": "X" })),
+            [
+                pattern_error,
+                "\"X\" does not match the pattern \"^[a-z]+$\" at /shape/label",
+            ]
+        );
+        assert_eq!(
+            errors(json!({ "color": "blue" })),
+            [
+                pattern_error,
+                "\"blue\" is not one of the enum values [\"red\"] at /shape/color",
+            ]
+        );
+        assert_eq!(
+            errors(json!({ "tags": ["a", "a"] })),
+            [pattern_error, "item 1 duplicates item 0 at /shape/tags"]
+        );
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        let model = serde_json::from_value::<Model>(json!({
+            "smithy": "2.0",
+            "shapes": {
+                "example#Patterns": {
+                    "type": "structure",
+                    "members": {
+                        "unclosed": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#pattern": "[a-z" },
+                        },
+                        "slow": {
+                            "target": "smithy.api#String",
+                            "traits": { "smithy.api#pattern": "^((?=a)a|a)*b$" },
+                        },
+                    },
+                },
+            },
+        }))
+        .unwrap();
+        let value = json!({ "unclosed": "a", "slow": format!("{}c", "a".repeat(30)) });
+        let errors = validate_value(&model, &"example#Patterns".parse().unwrap(), &value)
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "the pattern \"[a-z\" can't be checked: Parsing error at position 4: Invalid \
+                 character class at /unclosed",
+                "the pattern \"^((?=a)a|a)*b$\" can't be checked: Error executing regex: Max \
+                 limit for backtracking count exceeded at /slow",
+            ]
+        );
+    }
+}
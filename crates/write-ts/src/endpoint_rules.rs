@@ -0,0 +1,583 @@
+//! Compiles endpoint rule sets to TypeScript `resolveEndpoint` functions, which call the standard
+//! library functions in `ts-client/src/endpoints.ts`.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use miette::Result;
+use serde_json::Value;
+
+use laws_endpoint_rules::{self as endpoint_rules, TemplatePart};
+use laws_schema as schema;
+
+#[derive(Debug)]
+pub struct CompiledRuleSet {
+    /// The `resolveEndpoint` function, taking `EndpointParams`.
+    pub code: String,
+    /// The rules call `aws.partition`, so the code uses `__partitions`.
+    pub uses_partitions: bool,
+}
+
+/// Type checks and optimizes the rule set, then compiles it to a function that throws an
+/// `EndpointError` if an error rule or no rule matches.
+pub fn compile(rule_set: &schema::EndpointRuleSet) -> Result<CompiledRuleSet> {
+    if let Some(error) = endpoint_rules::check(rule_set).into_iter().next() {
+        return Err(miette::Report::new(error).wrap_err("invalid endpoint rules"));
+    }
+    let rule_set = endpoint_rules::Evaluator::new().optimize(rule_set)?;
+
+    let mut compiler = Compiler {
+        code: String::new(),
+        indent: 1,
+        uses_partitions: false,
+        idents: idents(&rule_set),
+    };
+    for (name, param) in &rule_set.parameters {
+        let value = match &param.default {
+            Some(default) => format!("__params.{name} ?? {default}"),
+            None => format!("__params.{name}"),
+        };
+        let ident = compiler.ident(name);
+        compiler.line(format_args!("const {ident} = {value};"));
+        if param.required && param.default.is_none() {
+            compiler.line(format_args!("if ({ident} === undefined) {{"));
+            compiler.line(format_args!(
+                "  throw new __endpoints.EndpointError({});",
+                string(&format!("missing required parameter {name}"))
+            ));
+            compiler.line("}");
+        }
+    }
+    compiler.rules(&rule_set.rules);
+
+    let mut code = String::new();
+    writeln!(
+        code,
+        "export function resolveEndpoint(__params: EndpointParams): __endpoints.Endpoint {{\n{}}}",
+        compiler.code
+    )
+    .expect("write to String should not fail");
+    Ok(CompiledRuleSet {
+        code,
+        uses_partitions: compiler.uses_partitions,
+    })
+}
+
+struct Compiler {
+    code: String,
+    indent: usize,
+    uses_partitions: bool,
+    /// The identifiers of the parameters and assignments, by name.
+    idents: HashMap<String, String>,
+}
+
+impl Compiler {
+    fn ident(&self, name: &str) -> String {
+        self.idents
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| ident(name))
+    }
+
+    fn line(&mut self, line: impl std::fmt::Display) {
+        writeln!(self.code, "{:1$}{line}", "", self.indent * 2)
+            .expect("write to String should not fail");
+    }
+
+    fn rules(&mut self, rules: &[schema::EndpointRuleItem]) {
+        for (index, rule) in rules.iter().enumerate() {
+            self.rule(rule, index == rules.len() - 1);
+        }
+        // A rule without conditions always returns or throws.
+        if !rules.last().is_some_and(|rule| rule.conditions.is_empty()) {
+            self.line(r#"throw new __endpoints.EndpointError("no rule matched");"#);
+        }
+    }
+
+    /// Nests the rule in an `if` of its conditions, where assignments are `const`s in a block
+    /// of their own unless the rule is the last, so siblings can assign the same names.
+    fn rule(&mut self, rule: &schema::EndpointRuleItem, last: bool) {
+        let depth = self.indent;
+        let mut tests = vec![];
+        for condition in &rule.conditions {
+            let value = self.call(&condition.function);
+            let Some(name) = &condition.assign else {
+                tests.push(if returns_boolean(&condition.function) {
+                    value
+                } else {
+                    format!("__endpoints.matches({value})")
+                });
+                continue;
+            };
+            if !tests.is_empty() {
+                self.line(format_args!("if ({}) {{", tests.join(" && ")));
+                self.indent += 1;
+                tests.clear();
+            } else if self.indent == depth && !last {
+                self.line("{");
+                self.indent += 1;
+            }
+            let ident = self.ident(name);
+            self.line(format_args!("const {ident} = {value};"));
+            tests.push(format!("__endpoints.matches({ident})"));
+        }
+        if !tests.is_empty() {
+            self.line(format_args!("if ({}) {{", tests.join(" && ")));
+            self.indent += 1;
+        }
+
+        match &rule.rule {
+            schema::EndpointRule::Tree { rules } => self.rules(rules),
+            schema::EndpointRule::Error { error } => {
+                let message = self.template(error);
+                self.line(format_args!(
+                    "throw new __endpoints.EndpointError({message});"
+                ));
+            }
+            schema::EndpointRule::Endpoint { endpoint } => {
+                let url = self.expr(&endpoint.url);
+                let properties = serde_json::to_value(&endpoint.properties)
+                    .expect("properties should serialize");
+                let properties = self.value(&properties);
+                let headers = endpoint
+                    .headers
+                    .iter()
+                    .map(|(name, values)| {
+                        let values = values
+                            .iter()
+                            .map(|value| self.template(value))
+                            .collect::<Vec<_>>();
+                        format!("{}: [{}]", string(name), values.join(", "))
+                    })
+                    .collect::<Vec<_>>();
+                self.line("return {");
+                self.line(format_args!("  url: {url},"));
+                self.line(format_args!("  properties: {properties},"));
+                self.line(format_args!("  headers: {{{}}},", headers.join(", ")));
+                self.line("};");
+            }
+        }
+
+        while self.indent > depth {
+            self.indent -= 1;
+            self.line("}");
+        }
+    }
+
+    fn call(&mut self, function: &schema::EndpointRuleConditionFn) -> String {
+        use schema::EndpointRuleConditionFn as Fn;
+        match function {
+            Fn::Not(expr) => format!("!({})", self.expr(expr)),
+            Fn::BooleanEquals(lhs, rhs) | Fn::StringEquals(lhs, rhs) => {
+                format!("{} === {}", self.expr(lhs), self.expr(rhs))
+            }
+            Fn::GetAttr(expr, path) => {
+                let mut value = match expr {
+                    schema::EndpointRuleExpr::Reference { name } => self.ident(name),
+                    // Unlike references to assignments, the value might be unset.
+                    expr => format!("({})?", self.expr(expr)),
+                };
+                value.push_str(&attr_access(path));
+                value
+            }
+            Fn::IsSet(expr) => format!("{} !== undefined", self.expr(expr)),
+            Fn::ParseURL(expr) => format!("__endpoints.parseURL({})", self.expr(expr)),
+            Fn::IsValidHostLabel(expr, allow_sub_domains) => format!(
+                "__endpoints.isValidHostLabel({}, {allow_sub_domains})",
+                self.expr(expr)
+            ),
+            Fn::Substring(expr, start, stop, reverse) => format!(
+                "__endpoints.substring({}, {start}, {stop}, {reverse})",
+                self.expr(expr)
+            ),
+            Fn::UriEncode(expr) => format!("__endpoints.uriEncode({})", self.expr(expr)),
+            Fn::AwsPartition(expr) => {
+                self.uses_partitions = true;
+                format!("__endpoints.partition(__partitions, {})", self.expr(expr))
+            }
+            Fn::AwsParseArn(expr) => format!("__endpoints.parseArn({})", self.expr(expr)),
+            Fn::AwsIsVirtualHostableS3Bucket(expr, allow_sub_domains) => format!(
+                "__endpoints.isVirtualHostableS3Bucket({}, {allow_sub_domains})",
+                self.expr(expr)
+            ),
+        }
+    }
+
+    fn expr(&mut self, expr: &schema::EndpointRuleExpr) -> String {
+        use schema::EndpointRuleConditionFn as Fn;
+        match expr {
+            schema::EndpointRuleExpr::Condition(condition) => {
+                let value = self.call(&condition.function);
+                match condition.function {
+                    // `true === a === b` would compare `true === a` with `b`.
+                    Fn::BooleanEquals(..) | Fn::StringEquals(..) | Fn::IsSet(_) => {
+                        format!("({value})")
+                    }
+                    _ => value,
+                }
+            }
+            schema::EndpointRuleExpr::Reference { name } => self.ident(name),
+            schema::EndpointRuleExpr::String(template) => self.template(template),
+            schema::EndpointRuleExpr::Boolean(value) => value.to_string(),
+        }
+    }
+
+    /// A property value, where strings are templates.
+    fn value(&mut self, value: &Value) -> String {
+        match value {
+            Value::String(template) => self.template(template),
+            Value::Array(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.value(item))
+                    .collect::<Vec<_>>();
+                format!("[{}]", items.join(", "))
+            }
+            Value::Object(object) => {
+                let entries = object
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", string(key), self.value(value)))
+                    .collect::<Vec<_>>();
+                format!("{{{}}}", entries.join(", "))
+            }
+            Value::Null | Value::Bool(_) | Value::Number(_) => value.to_string(),
+        }
+    }
+
+    /// A string literal, or a template literal if the template has placeholders.
+    fn template(&mut self, template: &str) -> String {
+        let parts = endpoint_rules::template_parts(template).expect("templates should be checked");
+        if let [] | [TemplatePart::Text(_)] = parts.as_slice() {
+            let text = parts.into_iter().map(|part| match part {
+                TemplatePart::Text(text) => text,
+                TemplatePart::Value { .. } => unreachable!(),
+            });
+            return string(&text.collect::<String>());
+        }
+        let mut literal = String::from("`");
+        for part in parts {
+            match part {
+                TemplatePart::Text(text) => literal.push_str(
+                    &text
+                        .replace('\\', "\\\\")
+                        .replace('`', "\\`")
+                        .replace("${", "\\${"),
+                ),
+                TemplatePart::Value { name, path, .. } => {
+                    literal.push_str("${");
+                    literal.push_str(&self.ident(name));
+                    if let Some(path) = path {
+                        literal.push_str(&attr_access(path));
+                    }
+                    literal.push('}');
+                }
+            }
+        }
+        literal.push('`');
+        literal
+    }
+}
+
+fn returns_boolean(function: &schema::EndpointRuleConditionFn) -> bool {
+    use schema::EndpointRuleConditionFn as Fn;
+    matches!(
+        function,
+        Fn::Not(_)
+            | Fn::BooleanEquals(..)
+            | Fn::StringEquals(..)
+            | Fn::IsSet(_)
+            | Fn::IsValidHostLabel(..)
+            | Fn::AwsIsVirtualHostableS3Bucket(..)
+    )
+}
+
+/// A `getAttr` path such as `resourceId[0]` as property accesses.
+fn attr_access(path: &str) -> String {
+    let mut access = String::new();
+    for part in path.split('.') {
+        let (key, index) = match part.strip_suffix(']').and_then(|part| part.split_once('[')) {
+            Some((key, index)) => (key, Some(index)),
+            None => (part, None),
+        };
+        if !key.is_empty() {
+            access.push('.');
+            access.push_str(key);
+        }
+        if let Some(index) = index {
+            access.push_str(&format!("[{index}]"));
+        }
+    }
+    access
+}
+
+/// Gives each parameter and assigned name its own identifier, numbering those that would
+/// otherwise be the same, e.g. `a-b` and `a_b`.
+fn idents(rule_set: &schema::EndpointRuleSet) -> HashMap<String, String> {
+    fn assigned<'a>(rules: &'a [schema::EndpointRuleItem], names: &mut Vec<&'a str>) {
+        for rule in rules {
+            names.extend(
+                rule.conditions
+                    .iter()
+                    .filter_map(|condition| condition.assign.as_deref()),
+            );
+            if let schema::EndpointRule::Tree { rules } = &rule.rule {
+                assigned(rules, names);
+            }
+        }
+    }
+    let mut names = rule_set
+        .parameters
+        .keys()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    assigned(&rule_set.rules, &mut names);
+
+    let mut idents = HashMap::new();
+    let mut taken = HashSet::new();
+    for name in names {
+        if idents.contains_key(name) {
+            continue;
+        }
+        let base = ident(name);
+        let mut candidate = base.clone();
+        let mut number = 2;
+        while !taken.insert(candidate.clone()) {
+            candidate = format!("{base}_{number}");
+            number += 1;
+        }
+        idents.insert(name.to_string(), candidate);
+    }
+    idents
+}
+
+/// The name of a parameter or assignment as a TypeScript identifier.
+fn ident(name: &str) -> String {
+    const RESERVED: &[&str] = &[
+        "arguments",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "eval",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "function",
+        "if",
+        "import",
+        "in",
+        "instanceof",
+        "let",
+        "new",
+        "null",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+    ];
+    let name = name.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_");
+    // `__` names are the generated code's own.
+    if RESERVED.contains(&name.as_str())
+        || name.starts_with(|c: char| c.is_ascii_digit())
+        || name.starts_with("__")
+    {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+fn string(value: &str) -> String {
+    serde_json::to_string(value).expect("strings should serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn compiles() {
+        let rule_set = serde_json::from_value::<schema::EndpointRuleSet>(json!({
+            "parameters": {
+                "Region": { "required": false, "documentation": "", "type": "String" },
+                "UseFIPS": {
+                    "required": true,
+                    "default": false,
+                    "documentation": "",
+                    "type": "Boolean",
+                },
+                "Endpoint": { "required": false, "documentation": "", "type": "String" },
+            },
+            "rules": [
+                {
+                    "conditions": [
+                        { "fn": "isSet", "argv": [{ "ref": "Endpoint" }] },
+                        { "fn": "parseURL", "argv": [{ "ref": "Endpoint" }], "assign": "url" },
+                    ],
+                    "endpoint": {
+                        "url": "{url#scheme}://{url#authority}{url#normalizedPath}`v1`",
+                        "properties": {},
+                        "headers": {},
+                    },
+                    "type": "endpoint",
+                },
+                {
+                    "conditions": [
+                        { "fn": "isSet", "argv": [{ "ref": "Region" }] },
+                        {
+                            "fn": "aws.partition",
+                            "argv": [{ "ref": "Region" }],
+                            "assign": "partition",
+                        },
+                    ],
+                    "rules": [
+                        {
+                            "conditions": [
+                                { "fn": "booleanEquals", "argv": [{ "ref": "UseFIPS" }, true] },
+                                {
+                                    "fn": "booleanEquals",
+                                    "argv": [
+                                        {
+                                            "fn": "getAttr",
+                                            "argv": [{ "ref": "partition" }, "supportsFIPS"],
+                                        },
+                                        false,
+                                    ],
+                                },
+                            ],
+                            "error": "FIPS is not supported in {partition#name}",
+                            "type": "error",
+                        },
+                        {
+                            "conditions": [],
+                            "endpoint": {
+                                "url": "https://example.{Region}.{partition#dnsSuffix}",
+                                "properties": {
+                                    "authSchemes": [{
+                                        "name": "sigv4",
+                                        "signingName": "example",
+                                        "signingRegion": "{Region}",
+                                    }],
+                                },
+                                "headers": {},
+                            },
+                            "type": "endpoint",
+                        },
+                    ],
+                    "type": "tree",
+                },
+                { "conditions": [], "error": "Missing Region", "type": "error" },
+            ],
+        }))
+        .unwrap();
+
+        let compiled = compile(&rule_set).unwrap();
+        assert!(compiled.uses_partitions);
+        assert_eq!(
+            compiled.code,
+            r#"export function resolveEndpoint(__params: EndpointParams): __endpoints.Endpoint {
+  const Region = __params.Region;
+  const UseFIPS = __params.UseFIPS ?? false;
+  const Endpoint = __params.Endpoint;
+  if (Endpoint !== undefined) {
+    const url = __endpoints.parseURL(Endpoint);
+    if (__endpoints.matches(url)) {
+      return {
+        url: `${url.scheme}://${url.authority}${url.normalizedPath}\`v1\``,
+        properties: {},
+        headers: {},
+      };
+    }
+  }
+  if (Region !== undefined) {
+    const partition = __endpoints.partition(__partitions, Region);
+    if (__endpoints.matches(partition)) {
+      if (UseFIPS === true && partition.supportsFIPS === false) {
+        throw new __endpoints.EndpointError(`FIPS is not supported in ${partition.name}`);
+      }
+      return {
+        url: `https://example.${Region}.${partition.dnsSuffix}`,
+        properties: {"authSchemes": [{"name": "sigv4", "signingName": "example", "signingRegion": `${Region}`}]},
+        headers: {},
+      };
+    }
+  }
+  throw new __endpoints.EndpointError("Missing Region");
+}
+"#
+        );
+
+        let mut invalid = rule_set.clone();
+        invalid.rules[0].conditions[1].assign = Some("Region".to_string());
+        let error = compile(&invalid).unwrap_err();
+        assert_eq!(error.to_string(), "invalid endpoint rules");
+    }
+
+    #[test]
+    fn distinct_idents() {
+        let rule_set = serde_json::from_value::<schema::EndpointRuleSet>(json!({
+            "parameters": {
+                "a_b": { "required": true, "documentation": "", "type": "String" },
+            },
+            "rules": [
+                {
+                    "conditions": [
+                        { "fn": "parseURL", "argv": [{ "ref": "a_b" }], "assign": "a-b" },
+                    ],
+                    "endpoint": {
+                        "url": "{a-b#scheme}://{a_b}",
+                        "properties": {},
+                        "headers": {},
+                    },
+                    "type": "endpoint",
+                },
+            ],
+        }))
+        .unwrap();
+
+        let compiled = compile(&rule_set).unwrap();
+        assert_eq!(
+            compiled.code,
+            r#"export function resolveEndpoint(__params: EndpointParams): __endpoints.Endpoint {
+  const a_b = __params.a_b;
+  if (a_b === undefined) {
+    throw new __endpoints.EndpointError("missing required parameter a_b");
+  }
+  const a_b_2 = __endpoints.parseURL(a_b);
+  if (__endpoints.matches(a_b_2)) {
+    return {
+      url: `${a_b_2.scheme}://${a_b}`,
+      properties: {},
+      headers: {},
+    };
+  }
+  throw new __endpoints.EndpointError("no rule matched");
+}
+"#
+        );
+    }
+}
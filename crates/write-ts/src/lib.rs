@@ -1,9 +1,11 @@
 use std::path::Path;
 
-use miette::{IntoDiagnostic, Result};
+use miette::{IntoDiagnostic, Result, WrapErr};
 
 use laws_schema as schema;
 
+mod endpoint_rules;
+
 enum Protocol {
     AwsJson1_0,
     AwsJson1_1,
@@ -48,12 +50,6 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
     use std::fs;
     use std::io::Write;
 
-    let service_model_name = path
-        .file_stem()
-        .ok_or(miette::diagnostic!("no file stem for service model"))?
-        .to_str()
-        .ok_or(miette::diagnostic!("file stem is not valid UTF-8"))?;
-
     let mut f = std::io::BufWriter::new(fs::File::create(path).into_diagnostic()?);
 
     // Write service. Only have one service per model at the moment, but we should be able to
@@ -68,6 +64,8 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
         .ok_or(miette::diagnostic!("no service found in model"))?;
 
     let protocol = Protocol::from_service_traits(&service.traits)?;
+    let schema::EndpointRuleSetTrait::V1_0(rule_set) = &service.traits.endpoint_rule_set;
+    let endpoint_rules = endpoint_rules::compile(rule_set)?;
 
     writeln!(
         f,
@@ -76,6 +74,14 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
     )
     .into_diagnostic()?;
     writeln!(f, r#"import type * as __service from "../service.js";"#,).into_diagnostic()?;
+    writeln!(f, r#"import * as __endpoints from "../endpoints.js";"#,).into_diagnostic()?;
+    if endpoint_rules.uses_partitions {
+        writeln!(
+            f,
+            r#"import {{ partitions as __partitions }} from "./partitions.js";"#,
+        )
+        .into_diagnostic()?;
+    }
 
     if service.traits.auth_sigv4.is_some() {
         writeln!(f, r#"import * as __sigv4 from "../auth/sigv4.js";"#,).into_diagnostic()?;
@@ -115,6 +121,21 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
     writeln!(f, "}};").into_diagnostic()?;
     writeln!(f).into_diagnostic()?;
 
+    // Write endpoint resolution
+    writeln!(f, "export interface EndpointParams {{").into_diagnostic()?;
+    for (name, param) in &rule_set.parameters {
+        let documentation = Some(param.documentation.clone()).filter(|doc| !doc.is_empty());
+        doc_comment(&mut f, "    ", &documentation)?;
+        let type_ = match param.type_ {
+            schema::EndpointRuleSetParamType::String => "string",
+            schema::EndpointRuleSetParamType::Boolean => "boolean",
+        };
+        writeln!(f, "    readonly {name}?: {type_};").into_diagnostic()?;
+    }
+    writeln!(f, "}}").into_diagnostic()?;
+    writeln!(f).into_diagnostic()?;
+    writeln!(f, "{}", endpoint_rules.code).into_diagnostic()?;
+
     // Write send function
     doc_comment(&mut f, "", &service.traits.documentation)?;
     writeln!(
@@ -123,21 +144,25 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
     client: __service.ClientConfig,
     operation: __protocol.OperationConfig,
     input: OperationMap[Name]['input'],
+    endpointParams: EndpointParams = {{}},
 ): Promise<OperationMap[Name]['output']> {{"#
     )
     .into_diagnostic()?;
 
-    let endpoint_prefix = &service
-        .traits
-        .service
-        .endpoint_prefix
-        .as_deref()
-        .unwrap_or(service_model_name);
-
+    // Built-ins come from the client config, other parameters from the operation.
+    writeln!(f, "  const endpoint = resolveEndpoint({{").into_diagnostic()?;
+    for (name, param) in &rule_set.parameters {
+        if let Some(built_in) = &param.built_in {
+            let field = client_config_field(built_in);
+            writeln!(f, "    {name}: client.{field},").into_diagnostic()?;
+        }
+    }
     writeln!(
         f,
-        r#"  const endpoint = `{endpoint_prefix}.${{client.region}}.amazonaws.com`;
-  let request = __protocol.inputRequest(service, endpoint, operation, input);"#
+        r#"    ...endpointParams,
+  }});
+  let request = __protocol.inputRequest(service, endpoint.url, operation, input);
+  __endpoints.applyHeaders(request, endpoint);"#
     )
     .into_diagnostic()?;
 
@@ -146,7 +171,7 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
     if let Some(auth) = &service.traits.auth_sigv4 {
         writeln!(
             f,
-            "  request = await __sigv4.authenticate(request, client, {{ name: {name:?} }});",
+            "  request = await __sigv4.authenticate(request, client, __endpoints.sigv4Config(endpoint, {name:?}));",
             name = auth.name
         )
         .into_diagnostic()?;
@@ -191,6 +216,27 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
             let input = shape_id_to_ts(&shape.input.target);
             let output = shape_id_to_ts(&shape.output.target);
 
+            // Endpoint parameters bound to input members, and static ones.
+            let mut endpoint_params = vec![];
+            let mut context_members = vec![];
+            if let Some(schema::Shape::Structure(input)) = model.shapes.get(&shape.input.target) {
+                for (member_name, member) in &input.members {
+                    let Some(context_param) = &member.traits.context_param else {
+                        continue;
+                    };
+                    if rule_set.parameters.contains_key(&context_param.name) {
+                        let param = &context_param.name;
+                        endpoint_params.push(format!("{param}: input.{member_name}"));
+                        context_members.push(member_name.as_str());
+                    }
+                }
+            }
+            for (param, value) in shape.traits.static_context_params.iter().flatten() {
+                if rule_set.parameters.contains_key(param) {
+                    endpoint_params.push(format!("{param}: {}", value.value));
+                }
+            }
+
             writeln!(
                 f,
                 r#"export async function {name}(
@@ -218,7 +264,9 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
 
                     // expand "/foo/{bar}" => `/foo/${input.bar}`,
                     //    and "/foo/{bar+}/baz" => `/foo/${input.bar/baz`
-                    let path = http.uri.replace('{', "${input.").replace("+}", "}");
+                    let path = without_context_label(&http.uri, &context_members)
+                        .replace('{', "${input.")
+                        .replace("+}", "}");
                     writeln!(
                         f,
                         "{{ method: {method:?}, path: `{path}` }};",
@@ -237,7 +285,9 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
 
                     // expand "/foo/{bar}" => `/foo/${input.bar}`,
                     //    and "/foo/{bar+}/baz" => `/foo/${input.bar/baz`
-                    let path = http.uri.replace('{', "${input.").replace("+}", "}");
+                    let path = without_context_label(&http.uri, &context_members)
+                        .replace('{', "${input.")
+                        .replace("+}", "}");
                     writeln!(
                         f,
                         "{{ input: {input_name:?}, output: {output_name:?}, method: {method:?}, path: `{path}` }};",
@@ -246,11 +296,20 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
                     .into_diagnostic()?;
                 }
             }
-            writeln!(
-                f,
-                "  return await send<{name:?}>(client, operation, input);"
-            )
-            .into_diagnostic()?;
+            if endpoint_params.is_empty() {
+                writeln!(
+                    f,
+                    "  return await send<{name:?}>(client, operation, input);"
+                )
+                .into_diagnostic()?;
+            } else {
+                writeln!(
+                    f,
+                    "  return await send<{name:?}>(client, operation, input, {{ {} }});",
+                    endpoint_params.join(", ")
+                )
+                .into_diagnostic()?;
+            }
             writeln!(f, "}}").into_diagnostic()?;
             writeln!(f).into_diagnostic()?;
         }
@@ -279,8 +338,21 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
                 .or_else(|| service.traits.paginated.as_ref()?.output_token.as_deref())
                 .ok_or(miette::diagnostic!(
                     "no output token in paginated config for {name}"
-                ))?
-                .replace('.', "?."); // e.g. output.EngineDefaults?.Marker;
+                ))?;
+            let output_token = schema::jmespath::parse(output_token)?;
+            output_token
+                .type_check(model, &shape.output.target)
+                .wrap_err_with(|| format!("invalid output token for {name}"))?;
+            let output_token = member_path_to_ts("output", &output_token)?;
+            let items = paginated
+                .items
+                .as_deref()
+                .or_else(|| service.traits.paginated.as_ref()?.items.as_deref());
+            if let Some(items) = items {
+                schema::jmespath::parse(items)?
+                    .type_check(model, &shape.output.target)
+                    .wrap_err_with(|| format!("invalid items path for {name}"))?;
+            }
 
             writeln!(f, "export async function* paginate{name}(").into_diagnostic()?;
             writeln!(f, "  clientConfig: __service.ClientConfig,").into_diagnostic()?;
@@ -292,7 +364,7 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
                 f,
                 "    const output: {output} = await {name}(clientConfig, {{ ...input, {input_token}: token }});",
             ).into_diagnostic()?;
-            writeln!(f, "    token = output.{output_token};").into_diagnostic()?;
+            writeln!(f, "    token = {output_token};").into_diagnostic()?;
             writeln!(f, "    yield output;").into_diagnostic()?;
             writeln!(f, "  }} while (token);").into_diagnostic()?;
             writeln!(f, "}}").into_diagnostic()?;
@@ -391,6 +463,22 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
                 writeln!(f, "export type {} = boolean;", name.name).into_diagnostic()?;
                 writeln!(f).into_diagnostic()?;
             }
+            schema::Shape::Byte(shape) => {
+                doc_comment(&mut f, "", &shape.traits.documentation)?;
+                if name.name == "number" {
+                    write!(f, "// ").into_diagnostic()?;
+                }
+                writeln!(f, "export type {} = number; // i8", name.name).into_diagnostic()?;
+                writeln!(f).into_diagnostic()?;
+            }
+            schema::Shape::Short(shape) => {
+                doc_comment(&mut f, "", &shape.traits.documentation)?;
+                if name.name == "number" {
+                    write!(f, "// ").into_diagnostic()?;
+                }
+                writeln!(f, "export type {} = number; // i16", name.name).into_diagnostic()?;
+                writeln!(f).into_diagnostic()?;
+            }
             schema::Shape::Integer(shape) => {
                 doc_comment(&mut f, "", &shape.traits.documentation)?;
                 if name.name == "number" {
@@ -423,6 +511,24 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
                 writeln!(f, "export type {} = number; // f64", name.name).into_diagnostic()?;
                 writeln!(f).into_diagnostic()?;
             }
+            schema::Shape::BigInteger(shape) => {
+                doc_comment(&mut f, "", &shape.traits.documentation)?;
+                if name.name == "number" {
+                    write!(f, "// ").into_diagnostic()?;
+                }
+                writeln!(f, "export type {} = number; // BigInteger", name.name)
+                    .into_diagnostic()?;
+                writeln!(f).into_diagnostic()?;
+            }
+            schema::Shape::BigDecimal(shape) => {
+                doc_comment(&mut f, "", &shape.traits.documentation)?;
+                if name.name == "number" {
+                    write!(f, "// ").into_diagnostic()?;
+                }
+                writeln!(f, "export type {} = number; // BigDecimal", name.name)
+                    .into_diagnostic()?;
+                writeln!(f).into_diagnostic()?;
+            }
             schema::Shape::String(shape) => {
                 doc_comment(&mut f, "", &shape.traits.documentation)?;
                 if name.name == "string" {
@@ -517,23 +623,87 @@ pub fn write_service(model: &schema::Model, path: &Path) -> Result<()> {
         Ok(())
     }
 
-    fn shape_id_to_ts(shape: &schema::ShapeId) -> &str {
-        match shape.name.as_str() {
-            "Blob" => "Uint8Array",
-            "Boolean" => "boolean",
-            "Document" => "string",
-            "Double" => "number",
-            "Float" => "number",
-            "Integer" => "number",
-            "Long" => "number",
-            "PrimitiveBoolean" => "boolean",
-            "PrimitiveLong" => "number",
-            "String" => "string",
-            "Timestamp" => "string",
-            "Unit" => "void",
-            _ => &shape.name,
+    fn shape_id_to_ts(shape_id: &schema::ShapeId) -> &str {
+        // Only prelude shapes map to built-in types, other shapes have their own type alias.
+        let Some(shape) = schema::prelude::get(shape_id) else {
+            return &shape_id.name;
+        };
+        match shape {
+            schema::Shape::Blob(_) => "Uint8Array",
+            schema::Shape::Boolean(_) => "boolean",
+            schema::Shape::Byte(_)
+            | schema::Shape::Short(_)
+            | schema::Shape::Integer(_)
+            | schema::Shape::Long(_)
+            | schema::Shape::Float(_)
+            | schema::Shape::Double(_)
+            | schema::Shape::BigInteger(_)
+            | schema::Shape::BigDecimal(_) => "number",
+            schema::Shape::String(_) | schema::Shape::Document(_) | schema::Shape::Timestamp(_) => {
+                "string"
+            }
+            // smithy.api#Unit
+            schema::Shape::Structure(_) => "void",
+            _ => &shape_id.name,
+        }
+    }
+
+    // The endpoint rules put parameters bound to a leading label in the URL they resolve, e.g.
+    // S3's "/{Bucket}/{Key+}" as `https://${Bucket}.s3.amazonaws.com`, so it's left out of the
+    // path.
+    fn without_context_label(uri: &str, context_members: &[&str]) -> String {
+        for member in context_members {
+            if let Some(rest) = uri.strip_prefix(&format!("/{{{member}}}")) {
+                if rest.starts_with('/') {
+                    return rest.to_string();
+                } else if rest.is_empty() || rest.starts_with('?') {
+                    return format!("/{rest}");
+                }
+            }
+        }
+        uri.to_string()
+    }
+
+    fn client_config_field(built_in: &schema::EndpointRuleSetParamBuiltIn) -> &str {
+        use schema::EndpointRuleSetParamBuiltIn as BuiltIn;
+        match built_in {
+            BuiltIn::Region => "region",
+            BuiltIn::UseDualStack => "useDualStack",
+            BuiltIn::UseFIPS => "useFIPS",
+            BuiltIn::S3Accelerate => "useAccelerateEndpoint",
+            BuiltIn::S3DisableMultiRegionAccessPoints => "disableMultiregionAccessPoints",
+            BuiltIn::S3ForcePathStyle => "forcePathStyle",
+            BuiltIn::S3UseArnRegion | BuiltIn::S3ControlUseArnRegion => "useArnRegion",
+            BuiltIn::S3UseGlobalEndpoint | BuiltIn::StsUseGlobalEndpoint => "useGlobalEndpoint",
+            BuiltIn::SdkEndpoint => "endpoint",
+        }
+    }
+
+    // e.g. EngineDefaults.Marker => output.EngineDefaults?.Marker
+    fn member_path_to_ts(value: &str, path: &schema::jmespath::Expr) -> Result<String> {
+        use schema::jmespath::Expr;
+        match path {
+            Expr::Field(name) => Ok(format!("{value}.{name}")),
+            Expr::Subexpression(lhs, rhs) => match &**rhs {
+                Expr::Field(name) => Ok(format!("{}?.{name}", member_path_to_ts(value, lhs)?)),
+                _ => miette::bail!("only member paths are supported, got {path:?}"),
+            },
+            _ => miette::bail!("only member paths are supported, got {path:?}"),
         }
     }
 
     Ok(())
 }
+
+/// Writes the partitions `aws.partition` looks regions up in, which the services' endpoint
+/// resolvers import.
+pub fn write_partitions(partitions: &laws_endpoint_rules::Partitions, path: &Path) -> Result<()> {
+    let partitions = serde_json::to_string_pretty(&partitions.partitions).into_diagnostic()?;
+    let source = format!(
+        r#"import type * as __endpoints from "../endpoints.js";
+
+export const partitions: readonly __endpoints.Partition[] = {partitions};
+"#
+    );
+    std::fs::write(path, source).into_diagnostic()
+}
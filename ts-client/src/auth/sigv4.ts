@@ -2,6 +2,8 @@ import type {ClientConfig} from "../service.js";
 
 export interface ServiceConfig {
     name: string;
+    /** The region to sign for, if not the client's, e.g. `us-east-1` for global endpoints. */
+    region?: string;
 }
 
 export function createSigner(serviceConfig: ServiceConfig) {
@@ -30,7 +32,8 @@ export async function authenticate(
         headers.set('x-amz-security-token', sessionToken);
     }
 
-    const credentialScope = `${date.substring(0, 8)}/${clientConfig.region}/${serviceConfig.name}/aws4_request`;
+    const region = serviceConfig.region ?? clientConfig.region;
+    const credentialScope = `${date.substring(0, 8)}/${region}/${serviceConfig.name}/aws4_request`;
 
     const signedHeaders = Array.from(headers.keys(), (key) => key.toLowerCase())
         .sort()
@@ -50,7 +53,7 @@ export async function authenticate(
     const canonicalRequestHash = await sha256Hex(toUtf8(canonicalRequest));
     const stringToSign = `AWS4-HMAC-SHA256\n${date}\n${credentialScope}\n${canonicalRequestHash}`;
     const kDate = await hmac(toUtf8(`AWS4${secretAccessKey}`), date.substring(0, 8));
-    const kRegion = await hmac(kDate, clientConfig.region);
+    const kRegion = await hmac(kDate, region);
     const kService = await hmac(kRegion, serviceConfig.name);
     const kSigning = await hmac(kService, 'aws4_request');
     const signature = toHex(await hmac(kSigning, stringToSign));
@@ -0,0 +1,169 @@
+// The runtime for the endpoint resolvers compiled from Smithy endpoint rule sets: the standard
+// library functions rule conditions call, and the AWS extensions to it.
+
+export interface Endpoint {
+    readonly url: string;
+    readonly properties: { readonly [name: string]: unknown };
+    readonly headers: { readonly [name: string]: readonly string[] };
+}
+
+/** The signing name and region an endpoint's `sigv4` auth scheme asks for, else the service's name. */
+export function sigv4Config(endpoint: Endpoint, name: string): { name: string; region?: string } {
+    const authSchemes = endpoint.properties.authSchemes;
+    const scheme = Array.isArray(authSchemes)
+        ? authSchemes.find((candidate) => candidate?.name === "sigv4")
+        : undefined;
+    return {
+        name: typeof scheme?.signingName === "string" ? scheme.signingName : name,
+        region: typeof scheme?.signingRegion === "string" ? scheme.signingRegion : undefined,
+    };
+}
+
+/** Sets the headers an endpoint requires on the request. */
+export function applyHeaders(request: Request, endpoint: Endpoint): void {
+    for (const [name, values] of Object.entries(endpoint.headers)) {
+        request.headers.delete(name);
+        for (const value of values) {
+            request.headers.append(name, value);
+        }
+    }
+}
+
+/** An error rule matched, or no rule did. */
+export class EndpointError extends Error {
+    override name = "EndpointError";
+}
+
+/** Whether a condition's value matches, which it does unless it's `false` or unset. */
+export function matches<T>(value: T): value is Exclude<T, undefined | false> {
+    return value !== undefined && value !== false;
+}
+
+export interface Url {
+    readonly scheme: string;
+    readonly authority: string;
+    readonly path: string;
+    readonly normalizedPath: string;
+    readonly isIp: boolean;
+}
+
+/** `parseURL`: splits a URL without a query string into its parts. */
+export function parseURL(value: string): Url | undefined {
+    const match = /^([a-zA-Z][a-zA-Z0-9+.-]*):\/\/([^/]*)(.*)$/.exec(value);
+    if (!match || /[?#\s]/.test(value)) {
+        return undefined;
+    }
+    const [, scheme, authority, path] = match;
+    let host = authority.slice(authority.lastIndexOf("@") + 1);
+    if (host.startsWith("[")) {
+        const end = host.indexOf("]");
+        if (end < 0) {
+            return undefined;
+        }
+        host = host.slice(0, end + 1);
+    } else {
+        host = host.split(":")[0];
+    }
+    if (!host) {
+        return undefined;
+    }
+    const parts = host.split(".");
+    const isIpv4 = parts.length === 4 && parts.every((part) => /^\d{1,3}$/.test(part) && Number(part) < 256);
+    let normalizedPath = path;
+    if (!normalizedPath.startsWith("/")) {
+        normalizedPath = `/${normalizedPath}`;
+    }
+    if (!normalizedPath.endsWith("/")) {
+        normalizedPath = `${normalizedPath}/`;
+    }
+    return { scheme, authority, path, normalizedPath, isIp: isIpv4 || host.startsWith("[") };
+}
+
+/** `isValidHostLabel`: whether the value is a DNS host label, or dot separated labels. */
+export function isValidHostLabel(value: string, allowSubDomains: boolean): boolean {
+    if (allowSubDomains) {
+        return value.split(".").every((label) => isValidHostLabel(label, false));
+    }
+    return /^[a-zA-Z0-9][a-zA-Z0-9-]{0,62}$/.test(value);
+}
+
+/** `substring`: the characters from `start` up to `stop`, counted from the end if `reverse`. */
+export function substring(value: string, start: number, stop: number, reverse: boolean): string | undefined {
+    if (!/^[\x00-\x7f]*$/.test(value) || start >= stop || stop > value.length) {
+        return undefined;
+    }
+    if (reverse) {
+        return value.slice(value.length - stop, value.length - start);
+    }
+    return value.slice(start, stop);
+}
+
+/** `uriEncode`: percent-encodes everything but the RFC 3986 unreserved characters. */
+export function uriEncode(value: string): string {
+    return encodeURIComponent(value).replace(/[!'()*]/g, (c) => `%${c.charCodeAt(0).toString(16).toUpperCase()}`);
+}
+
+export interface PartitionOutputs {
+    readonly name: string;
+    readonly dnsSuffix: string;
+    readonly dualStackDnsSuffix: string;
+    readonly supportsFIPS: boolean;
+    readonly supportsDualStack: boolean;
+    readonly implicitGlobalRegion: string;
+}
+
+/** A partition from `partitions.json`, where listed regions may override its outputs. */
+export interface Partition {
+    readonly id: string;
+    readonly regionRegex: string;
+    readonly regions: { readonly [region: string]: Partial<PartitionOutputs> & { readonly description?: string } };
+    readonly outputs: PartitionOutputs;
+}
+
+/**
+ * `aws.partition`: the outputs of the partition listing the region, else the first whose
+ * `regionRegex` matches it, else `aws`.
+ */
+export function partition(partitions: readonly Partition[], region: string): PartitionOutputs | undefined {
+    const found = partitions.find((partition) => Object.hasOwn(partition.regions, region))
+        ?? partitions.find((partition) => new RegExp(partition.regionRegex).test(region))
+        ?? partitions.find((partition) => partition.id === "aws");
+    if (!found) {
+        return undefined;
+    }
+    const listed: Partition["regions"][string] = Object.hasOwn(found.regions, region) ? found.regions[region] : {};
+    const { description: _, ...overrides } = listed;
+    return { ...found.outputs, ...overrides };
+}
+
+export interface Arn {
+    readonly partition: string;
+    readonly service: string;
+    readonly region: string;
+    readonly accountId: string;
+    readonly resourceId: readonly string[];
+}
+
+/** `aws.parseArn`: splits an ARN into its parts, with the resource split on `:` and `/`. */
+export function parseArn(value: string): Arn | undefined {
+    const match = /^arn:([^:]+):([^:]+):([^:]*):([^:]*):(.+)$/s.exec(value);
+    if (!match) {
+        return undefined;
+    }
+    const [, partition, service, region, accountId, resource] = match;
+    return { partition, service, region, accountId, resourceId: resource.split(/[:/]/) };
+}
+
+/** `aws.isVirtualHostableS3Bucket`: whether the bucket name can be used as a host label. */
+export function isVirtualHostableS3Bucket(value: string, allowSubDomains: boolean): boolean {
+    const isLabel = (label: string) => /^[a-z0-9]([a-z0-9-]*[a-z0-9])?$/.test(label);
+    // As in Smithy's reference implementation, no two dots or dashes may be adjacent.
+    if (value.length < 3 || value.length > 63 || /[.-]{2}/.test(value)) {
+        return false;
+    }
+    if (!allowSubDomains) {
+        return isLabel(value);
+    }
+    const looksLikeIp = /^\d+\.\d+\.\d+\.\d+$/.test(value);
+    return value.split(".").every(isLabel) && !looksLikeIp;
+}
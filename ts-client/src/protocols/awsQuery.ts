@@ -15,7 +15,7 @@ export function inputRequest(
   operation: OperationConfig,
   input: unknown,
 ): Request {
-  return new Request(`https://${endpoint}/`, {
+  return new Request(endpoint, {
     method: "POST",
     headers: {
       "Content-Type": "application/x-www-form-urlencoded",
@@ -12,7 +12,7 @@ export function inputRequest(
     operation: OperationConfig,
     input: unknown,
 ): Request {
-    return new Request(`https://${endpoint}${operation.path}`, {
+    return new Request(`${endpoint.replace(/\/$/, "")}${operation.path}`, {
         method: operation.method,
         headers: {
             "Content-Type": "application/json",
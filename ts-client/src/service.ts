@@ -1,6 +1,22 @@
 export interface ClientConfig {
     region: string;
     credentials: () => Promise<Credentials>;
+    /** Use the FIPS endpoints, which fail to resolve in regions that don't have them. */
+    useFIPS?: boolean;
+    /** Use the dual-stack (IPv4 and IPv6) endpoints. */
+    useDualStack?: boolean;
+    /** A URL to send requests to instead of the service's endpoint, e.g. for local testing. */
+    endpoint?: string;
+    /** S3: Address buckets in the path rather than the host. */
+    forcePathStyle?: boolean;
+    /** S3: Use the transfer acceleration endpoints. */
+    useAccelerateEndpoint?: boolean;
+    /** S3: Don't use multi-region access point ARNs. */
+    disableMultiregionAccessPoints?: boolean;
+    /** S3 and S3 Control: Use the region in ARNs rather than the client's. */
+    useArnRegion?: boolean;
+    /** S3 and STS: Use the global endpoint in us-east-1 rather than the regional one. */
+    useGlobalEndpoint?: boolean;
 }
 
 export interface Credentials {